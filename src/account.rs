@@ -1,3 +1,5 @@
+mod totp;
+
 use std::{net::IpAddr, num::NonZeroU32};
 
 use anyhow::Result;
@@ -5,15 +7,172 @@ use argon2::{
     Argon2,
     password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use ring::{
     digest, pbkdf2,
     rand::{self, SecureRandom},
 };
 use serde::{Deserialize, Serialize};
-use strum_macros::{Display, EnumString};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+
+use self::totp::Totp;
+pub use self::totp::TotpEnrollment;
+use crate::{HostNetworkGroup, UniqueKey, tables::Value};
+
+/// The password policy applied when a password is set.
+///
+/// The default policy imposes no restrictions.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct PasswordPolicy {
+    /// The minimum number of characters.
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_digit: bool,
+    /// Whether a character other than letters and digits is required.
+    pub require_symbol: bool,
+    /// Words a password must not contain, compared case-insensitively.
+    pub banned_words: Vec<String>,
+    /// The number of most recent passwords, including the current one, that cannot be reused.
+    pub history_size: usize,
+    /// The number of days a password is valid for, or `None` if it never expires.
+    pub max_age_days: Option<u32>,
+    /// The number of days before expiry from which a password is reported as expiring.
+    pub expiry_warning_days: u32,
+}
+
+impl PasswordPolicy {
+    /// Returns the violations of the policy by `password`, except for reuse, which depends on
+    /// the passwords of an account.
+    #[must_use]
+    pub fn violations(&self, password: &str) -> Vec<PasswordViolation> {
+        let mut violations = Vec::new();
+        if password.chars().count() < self.min_length {
+            violations.push(PasswordViolation::TooShort(self.min_length));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push(PasswordViolation::NoUppercase);
+        }
+        if self.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push(PasswordViolation::NoLowercase);
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push(PasswordViolation::NoDigit);
+        }
+        if self.require_symbol && password.chars().all(char::is_alphanumeric) {
+            violations.push(PasswordViolation::NoSymbol);
+        }
+        let lowercase = password.to_lowercase();
+        violations.extend(
+            self.banned_words
+                .iter()
+                .filter(|word| !word.is_empty() && lowercase.contains(&word.to_lowercase()))
+                .map(|word| PasswordViolation::BannedWord(word.clone())),
+        );
+        violations
+    }
+}
+
+/// The account lockout policy applied when a sign-in fails.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct LockoutPolicy {
+    /// The number of consecutive failed sign-ins that locks an account out.
+    pub threshold: u8,
+    /// The number of minutes after the last failed sign-in when the count of failed sign-ins is
+    /// reset, or `None` if it is reset only by a successful sign-in.
+    pub observation_window_minutes: Option<i64>,
+    /// The lockout durations in minutes. The n-th lockout of an account since its last successful
+    /// sign-in lasts the n-th duration, or the last one if there are fewer durations.
+    pub durations_minutes: Vec<i64>,
+    /// The number of lockouts after which an account stays locked out until an administrator
+    /// unlocks it, or `None` if lockouts are always temporary.
+    pub permanent_after: Option<u32>,
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: 5,
+            observation_window_minutes: None,
+            durations_minutes: vec![30],
+            permanent_after: None,
+        }
+    }
+}
+
+impl LockoutPolicy {
+    /// Returns the duration of the given lockout, counting from 1.
+    fn duration(&self, lockout: u32) -> TimeDelta {
+        let index = usize::try_from(lockout.saturating_sub(1)).unwrap_or(usize::MAX);
+        let minutes = self
+            .durations_minutes
+            .get(index)
+            .or(self.durations_minutes.last())
+            .copied()
+            .unwrap_or_default();
+        TimeDelta::try_minutes(minutes).unwrap_or(TimeDelta::MAX)
+    }
+}
+
+/// Whether an account is locked out.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LockoutState {
+    Unlocked,
+    /// The account is locked out for the remaining time.
+    Locked(TimeDelta),
+    /// The account is locked out until an administrator unlocks it.
+    LockedPermanently,
+}
 
-use crate::{UniqueKey, tables::Value};
+/// The state of the password of an account.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PasswordStatus {
+    Ok,
+    /// The password expires within the warning window of the `PasswordPolicy`, in the given
+    /// number of days.
+    Expiring(i64),
+    Expired,
+    /// The password must be changed before the account is used, regardless of its age.
+    MustChange,
+}
+
+/// A way a password violates the `PasswordPolicy`.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum PasswordViolation {
+    #[error("shorter than {0} characters")]
+    TooShort(usize),
+    #[error("no uppercase letter")]
+    NoUppercase,
+    #[error("no lowercase letter")]
+    NoLowercase,
+    #[error("no digit")]
+    NoDigit,
+    #[error("no symbol")]
+    NoSymbol,
+    #[error("contains banned word \"{0}\"")]
+    BannedWord(String),
+    /// The password is one of the given number of most recent passwords.
+    #[error("one of the last {0} passwords")]
+    Reused(usize),
+}
+
+/// The error returned when a password violates the `PasswordPolicy`.
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("password policy violated: {}", join_violations(.violations))]
+pub struct PasswordPolicyError {
+    pub violations: Vec<PasswordViolation>,
+}
+
+fn join_violations(violations: &[PasswordViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
 /// Possible role types of `Account`.
 #[derive(Clone, Copy, Debug, Display, Eq, PartialEq, Deserialize, Serialize, EnumString)]
@@ -28,18 +187,119 @@ pub enum Role {
     SecurityMonitor,
 }
 
+impl Role {
+    /// Returns the permissions granted by the built-in role.
+    #[must_use]
+    pub fn permissions(self) -> Vec<Permission> {
+        use Resource::{
+            Account, Customer, Event, Incident, Model, Network, Node, Policy, Role, Sensor,
+            Setting, Triage,
+        };
+
+        let writable: &[Resource] = match self {
+            Self::SystemAdministrator => &[
+                Account, Customer, Event, Incident, Model, Network, Node, Policy, Role, Sensor,
+                Setting, Triage,
+            ],
+            Self::SecurityAdministrator => &[
+                Account, Customer, Event, Incident, Model, Network, Policy, Sensor, Triage,
+            ],
+            Self::SecurityManager => &[Event, Incident, Model, Network, Policy, Triage],
+            Self::SecurityMonitor => &[],
+        };
+        let readable = Resource::iter().filter(|resource| {
+            matches!(
+                self,
+                Self::SystemAdministrator | Self::SecurityAdministrator
+            ) || !matches!(resource, Account | Role | Setting)
+        });
+
+        let mut permissions: Vec<_> = readable
+            .map(|resource| Permission::new(resource, Action::Read))
+            .collect();
+        for resource in writable {
+            for action in [Action::Create, Action::Update, Action::Delete] {
+                permissions.push(Permission::new(*resource, action));
+            }
+        }
+        permissions
+    }
+}
+
+/// The role of an `Account`, either built-in or defined in the `roles` table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum RoleId {
+    BuiltIn(Role),
+    /// The ID of a `CustomRole`.
+    Custom(u32),
+}
+
+impl From<Role> for RoleId {
+    fn from(role: Role) -> Self {
+        Self::BuiltIn(role)
+    }
+}
+
+/// What a `Permission` grants access to.
+#[derive(
+    Clone, Copy, Debug, Display, EnumIter, EnumString, Eq, Hash, PartialEq, Deserialize, Serialize,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum Resource {
+    Account,
+    Customer,
+    Event,
+    Incident,
+    Model,
+    Network,
+    Node,
+    Policy,
+    Role,
+    Sensor,
+    Setting,
+    Triage,
+}
+
+/// What a `Permission` allows to do with a `Resource`.
+#[derive(
+    Clone, Copy, Debug, Display, EnumIter, EnumString, Eq, Hash, PartialEq, Deserialize, Serialize,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum Action {
+    Read,
+    Create,
+    Update,
+    Delete,
+}
+
+/// A permission to perform an `Action` on a `Resource`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+pub struct Permission {
+    pub resource: Resource,
+    pub action: Action,
+}
+
+impl Permission {
+    #[must_use]
+    pub fn new(resource: Resource, action: Action) -> Self {
+        Self { resource, action }
+    }
+}
+
 #[derive(Clone, Deserialize, Serialize, PartialEq, Debug)]
 pub struct Account {
     pub username: String,
     pub(crate) password: SaltedPassword,
-    pub role: Role,
+    pub role: RoleId,
     pub name: String,
     pub department: String,
     pub language: Option<String>,
     pub theme: Option<String>,
     pub(crate) creation_time: DateTime<Utc>,
     pub(crate) last_signin_time: Option<DateTime<Utc>>,
-    pub allow_access_from: Option<Vec<IpAddr>>,
+    /// The hosts, networks, and ranges the account can sign in from, or `None` if it can sign in
+    /// from anywhere.
+    pub allow_access_from: Option<HostNetworkGroup>,
     pub max_parallel_sessions: Option<u8>,
     pub(crate) password_hash_algorithm: PasswordHashAlgorithm,
     pub(crate) password_last_modified_at: DateTime<Utc>,
@@ -47,6 +307,14 @@ pub struct Account {
     pub failed_login_attempts: u8,
     pub locked_out_until: Option<DateTime<Utc>>,
     pub is_suspended: bool,
+    /// The previous passwords, the most recent first.
+    pub(crate) password_history: Vec<SaltedPassword>,
+    pub(crate) must_change_password: bool,
+    pub last_failed_login_at: Option<DateTime<Utc>>,
+    /// The number of lockouts since the last successful sign-in.
+    pub lockout_count: u32,
+    pub is_locked_permanently: bool,
+    pub(crate) totp: Option<Totp>,
 }
 
 impl Account {
@@ -54,22 +322,33 @@ impl Account {
 
     /// Creates a new `Account` with the given information
     ///
+    /// The password of a new account must be changed before the account is used, as it was set
+    /// by someone else; see `require_password_change`.
+    ///
     /// # Errors
     ///
-    /// Returns an error if account creation fails.
+    /// Returns an error if account creation fails. If `password` violates `policy`, the error is
+    /// a `PasswordPolicyError`.
+    ///
+    /// Use `Table<Account>::create` to check the password against the stored policy instead.
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         username: &str,
         password: &str,
-        role: Role,
+        role: RoleId,
         name: String,
         department: String,
         language: Option<String>,
         theme: Option<String>,
-        allow_access_from: Option<Vec<IpAddr>>,
+        allow_access_from: Option<HostNetworkGroup>,
         max_parallel_sessions: Option<u8>,
         customer_ids: Option<Vec<u32>>,
+        policy: &PasswordPolicy,
     ) -> Result<Self> {
+        let violations = policy.violations(password);
+        if !violations.is_empty() {
+            return Err(PasswordPolicyError { violations }.into());
+        }
         let password =
             SaltedPassword::new_with_hash_algorithm(password, &Self::DEFAULT_HASH_ALGORITHM)?;
         let now = Utc::now();
@@ -91,20 +370,48 @@ impl Account {
             failed_login_attempts: 0,
             locked_out_until: None,
             is_suspended: false,
+            password_history: Vec::new(),
+            must_change_password: true,
+            last_failed_login_at: None,
+            lockout_count: 0,
+            is_locked_permanently: false,
+            totp: None,
         })
     }
 
     /// Update `Account::password` with the given password using
-    /// `Account::DEFAULT_HASH_ALGORITHM`.
+    /// `Account::DEFAULT_HASH_ALGORITHM`. The current password is kept in
+    /// the password history as long as `policy` requires.
     ///
     /// # Errors
     ///
-    /// Returns an error if the salt for password cannot be generated.
-    pub fn update_password(&mut self, password: &str) -> Result<()> {
-        self.password =
-            SaltedPassword::new_with_hash_algorithm(password, &Self::DEFAULT_HASH_ALGORITHM)?;
+    /// Returns an error if the salt for password cannot be generated. If
+    /// `password` violates `policy`, the error is a `PasswordPolicyError`.
+    ///
+    /// This clears the requirement to change the password, if any.
+    pub fn update_password(&mut self, password: &str, policy: &PasswordPolicy) -> Result<()> {
+        let mut violations = policy.violations(password);
+        if std::iter::once(&self.password)
+            .chain(&self.password_history)
+            .take(policy.history_size)
+            .any(|previous| previous.is_match(password))
+        {
+            violations.push(PasswordViolation::Reused(policy.history_size));
+        }
+        if !violations.is_empty() {
+            return Err(PasswordPolicyError { violations }.into());
+        }
+
+        let previous = std::mem::replace(
+            &mut self.password,
+            SaltedPassword::new_with_hash_algorithm(password, &Self::DEFAULT_HASH_ALGORITHM)?,
+        );
+        self.password_history.insert(0, previous);
+        self.password_history
+            .truncate(policy.history_size.saturating_sub(1));
         self.password_hash_algorithm = Self::DEFAULT_HASH_ALGORITHM;
         self.password_last_modified_at = Utc::now();
+        self.must_change_password = false;
         Ok(())
     }
 
@@ -113,6 +420,14 @@ impl Account {
         self.password.is_match(provided)
     }
 
+    /// Returns `true` if the account can sign in from `ip`.
+    #[must_use]
+    pub fn is_access_allowed(&self, ip: IpAddr) -> bool {
+        self.allow_access_from
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(ip))
+    }
+
     #[must_use]
     pub fn creation_time(&self) -> DateTime<Utc> {
         self.creation_time
@@ -122,13 +437,43 @@ impl Account {
         self.last_signin_time = Some(Utc::now());
     }
 
-    /// Resets the last signin time to `None`.
+    /// Requires the password to be changed before the account is used.
     ///
     /// This is typically used when an administrator resets a user's
     /// password, forcing the user to change their password upon next
-    /// sign-in.
-    pub fn reset_last_signin_time(&mut self) {
-        self.last_signin_time = None;
+    /// sign-in. The requirement is cleared by `update_password`.
+    pub fn require_password_change(&mut self) {
+        self.must_change_password = true;
+    }
+
+    #[must_use]
+    pub fn must_change_password(&self) -> bool {
+        self.must_change_password
+    }
+
+    /// Returns the state of the password at `now` under `policy`.
+    #[must_use]
+    pub fn password_status(&self, policy: &PasswordPolicy, now: DateTime<Utc>) -> PasswordStatus {
+        if self.must_change_password {
+            return PasswordStatus::MustChange;
+        }
+        let Some(max_age_days) = policy.max_age_days else {
+            return PasswordStatus::Ok;
+        };
+        let Some(expiry) = self
+            .password_last_modified_at
+            .checked_add_signed(TimeDelta::days(max_age_days.into()))
+        else {
+            return PasswordStatus::Ok;
+        };
+        let remaining = expiry - now;
+        if remaining <= TimeDelta::zero() {
+            PasswordStatus::Expired
+        } else if remaining <= TimeDelta::days(policy.expiry_warning_days.into()) {
+            PasswordStatus::Expiring(remaining.num_days())
+        } else {
+            PasswordStatus::Ok
+        }
     }
 
     #[must_use]
@@ -140,6 +485,58 @@ impl Account {
     pub fn password_last_modified_at(&self) -> DateTime<Utc> {
         self.password_last_modified_at
     }
+
+    /// Returns whether the account is locked out at `now`.
+    #[must_use]
+    pub fn lockout_state(&self, now: DateTime<Utc>) -> LockoutState {
+        if self.is_locked_permanently {
+            return LockoutState::LockedPermanently;
+        }
+        match self.locked_out_until {
+            Some(until) if until > now => LockoutState::Locked(until - now),
+            _ => LockoutState::Unlocked,
+        }
+    }
+
+    /// Counts a failed sign-in at `now`, and locks the account out if
+    /// `policy` requires.
+    pub(crate) fn record_failed_login(&mut self, policy: &LockoutPolicy, now: DateTime<Utc>) {
+        if self.lockout_state(now) != LockoutState::Unlocked {
+            self.last_failed_login_at = Some(now);
+            return;
+        }
+        if self.locked_out_until.is_some() {
+            // The previous lockout has expired.
+            self.failed_login_attempts = 0;
+            self.locked_out_until = None;
+        }
+        if let (Some(window), Some(last)) =
+            (policy.observation_window_minutes, self.last_failed_login_at)
+            && TimeDelta::try_minutes(window)
+                .and_then(|window| last.checked_add_signed(window))
+                .is_some_and(|reset| reset <= now)
+        {
+            self.failed_login_attempts = 0;
+        }
+
+        self.failed_login_attempts = self.failed_login_attempts.saturating_add(1);
+        self.last_failed_login_at = Some(now);
+        if self.failed_login_attempts < policy.threshold {
+            return;
+        }
+        self.lockout_count = self.lockout_count.saturating_add(1);
+        if policy
+            .permanent_after
+            .is_some_and(|limit| self.lockout_count >= limit)
+        {
+            self.is_locked_permanently = true;
+        } else {
+            self.locked_out_until = Some(
+                now.checked_add_signed(policy.duration(self.lockout_count))
+                    .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            );
+        }
+    }
 }
 
 impl UniqueKey for Account {
@@ -306,7 +703,7 @@ mod tests {
         let account = Account::new(
             "test",
             "password",
-            Role::SecurityAdministrator,
+            Role::SecurityAdministrator.into(),
             String::new(),
             String::new(),
             None,
@@ -314,6 +711,7 @@ mod tests {
             None,
             None,
             Some(Vec::new()),
+            &PasswordPolicy::default(),
         );
         assert!(account.is_ok());
 
@@ -337,7 +735,7 @@ mod tests {
                 &PasswordHashAlgorithm::Pbkdf2HmacSha512,
             )
             .unwrap(),
-            role: Role::SecurityAdministrator,
+            role: Role::SecurityAdministrator.into(),
             department: String::new(),
             name: String::new(),
             language: None,
@@ -352,11 +750,21 @@ mod tests {
             failed_login_attempts: 0,
             locked_out_until: None,
             is_suspended: false,
+            password_history: Vec::new(),
+            must_change_password: false,
+            last_failed_login_at: None,
+            lockout_count: 0,
+            is_locked_permanently: false,
+            totp: None,
         };
         assert!(account.verify_password("password"));
         assert!(!account.verify_password("updated"));
 
-        assert!(account.update_password("updated").is_ok());
+        assert!(
+            account
+                .update_password("updated", &PasswordPolicy::default())
+                .is_ok()
+        );
 
         assert!(!account.verify_password("password"));
         assert!(account.verify_password("updated"));
@@ -367,11 +775,80 @@ mod tests {
     }
 
     #[test]
-    fn reset_last_signin_time() {
+    fn password_policy() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            require_uppercase: true,
+            require_lowercase: true,
+            require_digit: true,
+            require_symbol: true,
+            banned_words: vec!["Petabi".to_string()],
+            history_size: 2,
+            ..PasswordPolicy::default()
+        };
+        let err = Account::new(
+            "test",
+            "petabi1",
+            Role::SecurityAdministrator.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &policy,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PasswordPolicyError>()
+                .unwrap()
+                .violations,
+            vec![
+                PasswordViolation::TooShort(10),
+                PasswordViolation::NoUppercase,
+                PasswordViolation::NoSymbol,
+                PasswordViolation::BannedWord("Petabi".to_string()),
+            ]
+        );
+
+        let mut account = Account::new(
+            "test",
+            "Correct-horse1",
+            Role::SecurityAdministrator.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &policy,
+        )
+        .unwrap();
+        let err = account
+            .update_password("Correct-horse1", &policy)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PasswordPolicyError>()
+                .unwrap()
+                .violations,
+            vec![PasswordViolation::Reused(2)]
+        );
+        account.update_password("Battery-staple2", &policy).unwrap();
+        assert!(account.update_password("Correct-horse1", &policy).is_err());
+        account.update_password("Third-password3", &policy).unwrap();
+        assert_eq!(account.password_history.len(), 1);
+        account.update_password("Correct-horse1", &policy).unwrap();
+        assert!(account.verify_password("Correct-horse1"));
+    }
+
+    #[test]
+    fn last_signin_time() {
         let mut account = Account::new(
             "test",
             "password",
-            Role::SecurityAdministrator,
+            Role::SecurityAdministrator.into(),
             String::new(),
             String::new(),
             None,
@@ -379,6 +856,7 @@ mod tests {
             None,
             None,
             Some(Vec::new()),
+            &PasswordPolicy::default(),
         )
         .unwrap();
 
@@ -388,9 +866,133 @@ mod tests {
         // Update last signin time
         account.update_last_signin_time();
         assert!(account.last_signin_time().is_some());
+    }
 
-        // Reset last signin time
-        account.reset_last_signin_time();
-        assert_eq!(account.last_signin_time(), None);
+    #[test]
+    fn access_allowed() {
+        let mut account = Account::new(
+            "test",
+            "password",
+            Role::SecurityAdministrator.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        assert!(account.is_access_allowed("192.168.0.1".parse().unwrap()));
+
+        account.allow_access_from = Some(HostNetworkGroup::new(
+            vec!["192.168.0.1".parse().unwrap()],
+            vec!["10.0.1.0/24".parse().unwrap()],
+            vec!["172.16.0.10".parse().unwrap()..="172.16.0.20".parse().unwrap()],
+        ));
+        assert!(account.is_access_allowed("192.168.0.1".parse().unwrap()));
+        assert!(account.is_access_allowed("10.0.1.200".parse().unwrap()));
+        assert!(account.is_access_allowed("172.16.0.15".parse().unwrap()));
+        assert!(!account.is_access_allowed("192.168.0.2".parse().unwrap()));
+        assert!(!account.is_access_allowed("10.0.2.1".parse().unwrap()));
+        assert!(!account.is_access_allowed("172.16.0.21".parse().unwrap()));
+    }
+
+    #[test]
+    fn password_status() {
+        let mut account = Account::new(
+            "test",
+            "password",
+            Role::SecurityAdministrator.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        assert!(account.must_change_password());
+        assert_eq!(
+            account.password_status(&PasswordPolicy::default(), Utc::now()),
+            PasswordStatus::MustChange
+        );
+        account
+            .update_password("changed", &PasswordPolicy::default())
+            .unwrap();
+
+        let now = account.password_last_modified_at();
+        let policy = PasswordPolicy {
+            max_age_days: Some(90),
+            expiry_warning_days: 14,
+            ..PasswordPolicy::default()
+        };
+        assert_eq!(
+            account.password_status(&PasswordPolicy::default(), now + TimeDelta::days(1000)),
+            PasswordStatus::Ok
+        );
+        assert_eq!(account.password_status(&policy, now), PasswordStatus::Ok);
+        assert_eq!(
+            account.password_status(&policy, now + TimeDelta::days(80)),
+            PasswordStatus::Expiring(10)
+        );
+        assert_eq!(
+            account.password_status(&policy, now + TimeDelta::days(90)),
+            PasswordStatus::Expired
+        );
+        let never = PasswordPolicy {
+            max_age_days: Some(u32::MAX),
+            ..PasswordPolicy::default()
+        };
+        assert_eq!(account.password_status(&never, now), PasswordStatus::Ok);
+
+        account.require_password_change();
+        assert!(account.must_change_password());
+        assert_eq!(
+            account.password_status(&policy, now),
+            PasswordStatus::MustChange
+        );
+        account.update_password("updated", &policy).unwrap();
+        assert!(!account.must_change_password());
+        assert_eq!(
+            account.password_status(&policy, Utc::now()),
+            PasswordStatus::Ok
+        );
+    }
+
+    #[test]
+    fn lockout_overflow() {
+        let mut account = Account::new(
+            "test",
+            "password",
+            Role::SecurityAdministrator.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        let policy = LockoutPolicy {
+            threshold: 2,
+            observation_window_minutes: Some(i64::MAX),
+            durations_minutes: vec![i64::MAX],
+            permanent_after: None,
+        };
+        let now = Utc::now();
+        account.record_failed_login(&policy, now);
+        account.record_failed_login(&policy, now + TimeDelta::days(1));
+        assert_eq!(account.failed_login_attempts, 2);
+        assert_eq!(account.locked_out_until, Some(DateTime::<Utc>::MAX_UTC));
+        assert_ne!(
+            account.lockout_state(now + TimeDelta::days(2)),
+            LockoutState::Unlocked
+        );
     }
 }
@@ -0,0 +1,338 @@
+//! Time-based one-time passwords (RFC 6238) as a second factor of accounts.
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use data_encoding::BASE32_NOPAD;
+use ring::{
+    aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{Account, PasswordHashAlgorithm, SaltedPassword};
+
+/// The number of bytes of a TOTP secret, the length of an HMAC-SHA1 output as recommended by
+/// RFC 4226.
+const SECRET_LEN: usize = 20;
+/// The number of seconds a code is valid for.
+const STEP_SECONDS: i64 = 30;
+/// The number of digits of a code.
+const DIGITS: u32 = 6;
+/// The number of steps before and after the current one whose codes are accepted, to allow for
+/// clock drift between the server and the authenticator.
+const DRIFT_STEPS: u64 = 1;
+/// The number of recovery codes issued on enrolment.
+const RECOVERY_CODES: usize = 10;
+/// The number of random bytes of a recovery code, encoded in 8 base32 characters.
+const RECOVERY_CODE_LEN: usize = 5;
+
+/// The TOTP enrolment of an account.
+///
+/// The secret is encrypted with AES-256-GCM under a key that is not stored in the database, with
+/// the username as associated data.
+#[derive(Clone, Deserialize, Serialize, PartialEq, Debug)]
+pub(crate) struct Totp {
+    nonce: [u8; NONCE_LEN],
+    encrypted_secret: Vec<u8>,
+    /// The step of the last code accepted, which cannot be used again.
+    last_step: Option<u64>,
+    recovery_codes: Vec<SaltedPassword>,
+}
+
+/// What a user needs to set up an authenticator, returned only once on enrolment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TotpEnrollment {
+    /// The secret in base32 without padding.
+    pub secret: String,
+    /// The `otpauth://` URI of the secret, typically shown as a QR code.
+    pub uri: String,
+    /// One-time codes to sign in with when the authenticator is unavailable.
+    pub recovery_codes: Vec<String>,
+}
+
+impl Account {
+    /// Enrolls the account in TOTP, replacing the existing enrolment, if any.
+    ///
+    /// `key` is the 32-byte key the secret is encrypted with; it must be kept outside the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` is not 32 bytes long, or random number generation, encryption,
+    /// or hashing of a recovery code fails.
+    pub fn enroll_totp(&mut self, key: &[u8], issuer: &str) -> Result<TotpEnrollment> {
+        let rng = SystemRandom::new();
+        let mut secret = [0_u8; SECRET_LEN];
+        rng.fill(&mut secret)?;
+        let mut nonce = [0_u8; NONCE_LEN];
+        rng.fill(&mut nonce)?;
+        let mut encrypted_secret = secret.to_vec();
+        sealing_key(key)?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(self.username.as_bytes()),
+                &mut encrypted_secret,
+            )
+            .map_err(|_| anyhow!("cannot encrypt TOTP secret"))?;
+
+        let mut recovery_codes = Vec::with_capacity(RECOVERY_CODES);
+        let mut hashes = Vec::with_capacity(RECOVERY_CODES);
+        for _ in 0..RECOVERY_CODES {
+            let mut code = [0_u8; RECOVERY_CODE_LEN];
+            rng.fill(&mut code)?;
+            let code = BASE32_NOPAD.encode(&code);
+            hashes.push(SaltedPassword::new_with_hash_algorithm(
+                &code,
+                &PasswordHashAlgorithm::Argon2id,
+            )?);
+            recovery_codes.push(code);
+        }
+
+        self.totp = Some(Totp {
+            nonce,
+            encrypted_secret,
+            last_step: None,
+            recovery_codes: hashes,
+        });
+        let secret = BASE32_NOPAD.encode(&secret);
+        let issuer = percent_encode(issuer);
+        let uri = format!(
+            "otpauth://totp/{issuer}:{}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={STEP_SECONDS}",
+            percent_encode(&self.username)
+        );
+        Ok(TotpEnrollment {
+            secret,
+            uri,
+            recovery_codes,
+        })
+    }
+
+    /// Removes the TOTP enrolment, including the recovery codes.
+    pub fn disable_totp(&mut self) {
+        self.totp = None;
+    }
+
+    #[must_use]
+    pub fn has_totp(&self) -> bool {
+        self.totp.is_some()
+    }
+
+    /// Returns the number of recovery codes not used yet.
+    #[must_use]
+    pub fn remaining_recovery_codes(&self) -> usize {
+        self.totp
+            .as_ref()
+            .map_or(0, |totp| totp.recovery_codes.len())
+    }
+
+    /// Verifies a TOTP code at `now`. A code of the current step or a step within the drift
+    /// window is accepted, unless its step is not later than that of the last code accepted.
+    /// Codes are compared in constant time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account is not enrolled in TOTP, or the secret cannot be decrypted
+    /// with `key`.
+    pub fn verify_totp(&mut self, code: &str, key: &[u8], now: DateTime<Utc>) -> Result<bool> {
+        let Some(totp) = &mut self.totp else {
+            bail!("TOTP is not enrolled");
+        };
+        let mut secret = totp.encrypted_secret.clone();
+        let secret: &[u8] = sealing_key(key)?
+            .open_in_place(
+                Nonce::assume_unique_for_key(totp.nonce),
+                Aad::from(self.username.as_bytes()),
+                &mut secret,
+            )
+            .map_err(|_| anyhow!("cannot decrypt TOTP secret"))?;
+
+        let current = u64::try_from(now.timestamp() / STEP_SECONDS).context("invalid time")?;
+        let first = current
+            .saturating_sub(DRIFT_STEPS)
+            .max(totp.last_step.map_or(0, |step| step + 1));
+        // Every step in the window is checked so that the time taken does not reveal which one
+        // matched.
+        let Some(step) = (first..=current + DRIFT_STEPS).fold(None, |matched, step| {
+            let is_match = constant_time_eq(hotp(secret, step).as_bytes(), code.as_bytes());
+            matched.or(is_match.then_some(step))
+        }) else {
+            return Ok(false);
+        };
+        totp.last_step = Some(step);
+        Ok(true)
+    }
+
+    /// Signs in with a recovery code, which cannot be used again. Returns `false` if the code
+    /// does not match any unused recovery code.
+    pub fn use_recovery_code(&mut self, code: &str) -> bool {
+        let Some(totp) = &mut self.totp else {
+            return false;
+        };
+        let code = code.trim().to_ascii_uppercase();
+        let Some(index) = totp
+            .recovery_codes
+            .iter()
+            .position(|hash| hash.is_match(&code))
+        else {
+            return false;
+        };
+        totp.recovery_codes.remove(index);
+        true
+    }
+}
+
+fn sealing_key(key: &[u8]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, key)
+        .map_err(|_| anyhow!("TOTP encryption key must be 32 bytes long"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Compares two byte strings in time that depends only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Percent-encodes `s` for a label or a query value of an `otpauth://` URI, leaving only the
+/// unreserved characters of RFC 3986 as they are.
+fn percent_encode(s: &str) -> String {
+    use std::fmt::Write;
+
+    let mut encoded = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            write!(encoded, "%{byte:02X}").expect("writing to a string never fails");
+        }
+    }
+    encoded
+}
+
+/// Returns the HOTP code (RFC 4226) of the given counter.
+fn hotp(secret: &[u8], counter: u64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let tag = hmac::sign(&key, &counter.to_be_bytes());
+    let digest = tag.as_ref();
+    let offset = usize::from(digest[digest.len() - 1] & 0x0f);
+    let binary = u32::from_be_bytes(
+        digest[offset..offset + 4]
+            .try_into()
+            .expect("4 bytes within the digest"),
+    ) & 0x7fff_ffff;
+    format!(
+        "{:0width$}",
+        binary % 10_u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeDelta, TimeZone, Utc};
+    use data_encoding::BASE32_NOPAD;
+
+    use super::{STEP_SECONDS, constant_time_eq, hotp, percent_encode};
+    use crate::{PasswordPolicy, Role, types::Account};
+
+    #[test]
+    fn rfc6238_vectors() {
+        let secret = b"12345678901234567890";
+        assert_eq!(hotp(secret, 59 / 30), "287082");
+        assert_eq!(hotp(secret, 1_111_111_109 / 30), "081804");
+        assert_eq!(hotp(secret, 2_000_000_000 / 30), "279037");
+    }
+
+    #[test]
+    fn compare() {
+        assert!(constant_time_eq(b"123456", b"123456"));
+        assert!(!constant_time_eq(b"123456", b"123457"));
+        assert!(!constant_time_eq(b"123456", b"12345"));
+    }
+
+    #[test]
+    fn uri_encoding() {
+        assert_eq!(percent_encode("REview-1.0_~"), "REview-1.0_~");
+        assert_eq!(percent_encode("Acme Inc:a&b"), "Acme%20Inc%3Aa%26b");
+        assert_eq!(percent_encode("한"), "%ED%95%9C");
+    }
+
+    #[test]
+    fn verify() {
+        let key = [7_u8; 32];
+        let mut account = Account::new(
+            "test",
+            "password",
+            Role::SecurityAdministrator.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        assert!(account.verify_totp("000000", &key, Utc::now()).is_err());
+        assert!(account.enroll_totp(&[0; 16], "REview").is_err());
+
+        let enrollment = account.enroll_totp(&key, "REview").unwrap();
+        assert!(account.has_totp());
+        assert!(
+            enrollment
+                .uri
+                .starts_with("otpauth://totp/REview:test?secret=")
+        );
+        let secret = BASE32_NOPAD.decode(enrollment.secret.as_bytes()).unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let step = u64::try_from(now.timestamp() / STEP_SECONDS).unwrap();
+
+        assert!(
+            account
+                .verify_totp(&hotp(&secret, step - 1), &key, now)
+                .unwrap()
+        );
+        assert!(
+            !account
+                .verify_totp(&hotp(&secret, step - 2), &key, now)
+                .unwrap()
+        );
+        assert!(
+            !account
+                .verify_totp(&hotp(&secret, step + 2), &key, now)
+                .unwrap()
+        );
+        // A code cannot be replayed, nor can an earlier one be used.
+        assert!(
+            !account
+                .verify_totp(&hotp(&secret, step - 1), &key, now)
+                .unwrap()
+        );
+        assert!(
+            account
+                .verify_totp(&hotp(&secret, step), &key, now)
+                .unwrap()
+        );
+        assert!(
+            !account
+                .verify_totp(&hotp(&secret, step), &key, now)
+                .unwrap()
+        );
+        let later = now + TimeDelta::seconds(STEP_SECONDS);
+        assert!(
+            account
+                .verify_totp(&hotp(&secret, step + 1), &key, later)
+                .unwrap()
+        );
+        assert!(account.verify_totp("000000", &[8_u8; 32], later).is_err());
+
+        assert_eq!(account.remaining_recovery_codes(), 10);
+        let code = enrollment.recovery_codes[3].to_ascii_lowercase();
+        assert!(account.use_recovery_code(&code));
+        assert!(!account.use_recovery_code(&code));
+        assert_eq!(account.remaining_recovery_codes(), 9);
+
+        account.disable_totp();
+        assert!(!account.has_totp());
+    }
+}
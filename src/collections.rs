@@ -179,6 +179,26 @@ where
     fn make_indexed_key(key: Cow<[u8]>, index: u32) -> Cow<[u8]>;
     fn value(&self) -> Vec<u8>;
     fn set_index(&mut self, index: u32);
+
+    /// Checks that the entry can be stored in `db`. `IndexedTable` calls this before storing an
+    /// entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be stored.
+    fn check_insert(&self, _db: &rocksdb::OptimisticTransactionDB) -> Result<()> {
+        Ok(())
+    }
+
+    /// Checks that the entry with the given ID can be removed from `db`. `IndexedTable` calls this
+    /// before removing an entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the entry cannot be removed.
+    fn check_remove(_db: &rocksdb::OptimisticTransactionDB, _id: u32) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub trait Indexed {
@@ -1,7 +1,12 @@
 #![allow(clippy::too_many_lines)]
+#[cfg(feature = "archive")]
+mod archive;
+mod attribute;
+mod beaconing;
 mod bootp;
 mod common;
 mod conn;
+mod custom;
 mod dcerpc;
 mod dhcp;
 mod dns;
@@ -12,11 +17,13 @@ mod ldap;
 mod log;
 mod malformed_dns;
 mod mqtt;
+mod ndjson;
 mod network;
 mod nfs;
 mod ntlm;
 mod radius;
 mod rdp;
+mod redaction;
 mod smb;
 mod smtp;
 mod ssh;
@@ -24,33 +31,44 @@ mod sysmon;
 mod tls;
 mod tor;
 mod unusual_destination_pattern;
+mod volume_exfiltration;
 
 use std::{
+    borrow::Cow,
     collections::HashMap,
     convert::TryInto,
     fmt::{self},
     net::IpAddr,
     num::NonZeroU8,
+    sync::OnceLock,
 };
 
 use aho_corasick::AhoCorasickBuilder;
 use anyhow::{Context, Result, bail};
+use attrievent::attribute::RawEventAttrKind;
 use chrono::{DateTime, TimeZone, Utc, serde::ts_nanoseconds};
 use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 use rand::{RngCore, rng};
 pub use rocksdb::Direction;
 use rocksdb::IteratorMode;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+use tracing::warn;
 
-use self::common::Match;
+#[cfg(feature = "archive")]
+pub use self::archive::{ArchiveFile, ArchiveFormat};
 pub use self::{
+    attribute::EventAttr,
+    beaconing::{Beaconing, BeaconingFields},
     bootp::{BlocklistBootp, BlocklistBootpFields},
     common::TriageScore,
     conn::{
         BlocklistConn, BlocklistConnFields, ExternalDdos, ExternalDdosFields, MultiHostPortScan,
         MultiHostPortScanFields, PortScan, PortScanFields,
     },
+    custom::{CustomEvent, CustomEventFields, CustomValue, CustomValueKind},
     dcerpc::{BlocklistDceRpc, BlocklistDceRpcFields},
     dhcp::{BlocklistDhcp, BlocklistDhcpFields},
     dns::{
@@ -64,26 +82,39 @@ pub use self::{
         BlocklistHttp, BlocklistHttpFields, DgaFields, DomainGenerationAlgorithm, HttpEventFields,
         HttpThreat, HttpThreatFields, NonBrowser, RepeatedHttpSessions, RepeatedHttpSessionsFields,
     },
-    kerberos::{BlocklistKerberos, BlocklistKerberosFields},
+    kerberos::{
+        AsRepRoasting, AsRepRoastingFields, BlocklistKerberos, BlocklistKerberosFields,
+        Kerberoasting, KerberoastingFields,
+    },
     ldap::{BlocklistLdap, LdapBruteForce, LdapBruteForceFields, LdapEventFields, LdapPlainText},
-    log::ExtraThreat,
+    log::{ExtraThreat, LogFields},
     malformed_dns::{BlocklistMalformedDns, BlocklistMalformedDnsFields},
     mqtt::{BlocklistMqtt, BlocklistMqttFields},
+    ndjson::{ImportReport, RejectedLine},
     network::NetworkThreat,
     nfs::{BlocklistNfs, BlocklistNfsFields},
-    ntlm::{BlocklistNtlm, BlocklistNtlmFields},
+    ntlm::{BlocklistNtlm, BlocklistNtlmFields, NtlmPasswordSpray, NtlmPasswordSprayFields},
     radius::{BlocklistRadius, BlocklistRadiusFields},
     rdp::{BlocklistRdp, BlocklistRdpFields, RdpBruteForce, RdpBruteForceFields},
+    redaction::{FieldClass, RedactionAction, RedactionPolicy},
     smb::{BlocklistSmb, BlocklistSmbFields},
     smtp::{BlocklistSmtp, BlocklistSmtpFields},
-    ssh::{BlocklistSsh, BlocklistSshFields},
+    ssh::{BlocklistSsh, BlocklistSshFields, SshBruteForce, SshBruteForceFields},
     sysmon::WindowsThreat,
     tls::{BlocklistTls, BlocklistTlsFields, SuspiciousTlsTraffic},
     tor::{TorConnection, TorConnectionConn},
     unusual_destination_pattern::{UnusualDestinationPattern, UnusualDestinationPatternFields},
+    volume_exfiltration::{VolumeExfiltration, VolumeExfiltrationFields},
+};
+use self::{
+    common::Match,
+    redaction::{Redact, redact_fields},
 };
 use super::{
-    Customer, EventCategory, Network, TriagePolicy, TriagePolicyInput,
+    CorrelationKey, CustomEventSchema, Customer, DedupEntry, EventCategory, EventEnrichment,
+    Incident, IndexedTable, Iterable, LogTemplate, RuleTechniques, Sensor, SensorIngest,
+    SeverityOverride, SeverityOverrides, Table, TriagePolicy, TriagePolicyInput,
+    tables::{CorrelationInput, Enricher, RegexCache},
     types::{Endpoint, HostNetworkGroup},
 };
 
@@ -101,6 +132,13 @@ const HIGH: NonZeroU8 =
 const DNS_COVERT_CHANNEL: &str = "DNS Covert Channel";
 const HTTP_THREAT: &str = "HTTP Threat";
 const RDP_BRUTE_FORCE: &str = "RDP Brute Force";
+const SSH_BRUTE_FORCE: &str = "SSH Brute Force";
+const BEACONING: &str = "Beaconing";
+const VOLUME_EXFILTRATION: &str = "Volume Exfiltration";
+const KERBEROASTING: &str = "Kerberoasting";
+const AS_REP_ROASTING: &str = "AS-REP Roasting";
+const NTLM_PASSWORD_SPRAY: &str = "NTLM Password Spray";
+const CUSTOM_EVENT: &str = "Custom Event";
 const REPEATED_HTTP_SESSIONS: &str = "Repeated HTTP Sessions";
 const TOR_CONNECTION: &str = "Tor Connection";
 const TOR_CONNECTION_CONN: &str = "Tor Connection Conn";
@@ -132,6 +170,32 @@ pub enum Event {
     /// Brute force attacks against RDP, attempting to guess passwords.
     RdpBruteForce(RdpBruteForce),
 
+    /// Brute force attacks against SSH, including password spraying across multiple hosts.
+    SshBruteForce(SshBruteForce),
+
+    /// Periodic outbound connections from a host to a single destination, such as the heartbeat of
+    /// a command-and-control implant.
+    Beaconing(Beaconing),
+
+    /// An internal host sending far more data to external destinations than its baseline,
+    /// regardless of the protocol used.
+    VolumeExfiltration(VolumeExfiltration),
+
+    /// Bursts of Kerberos TGS requests for service tickets with RC4 encryption, which are requested
+    /// to crack the passwords of service accounts offline.
+    Kerberoasting(Kerberoasting),
+
+    /// Kerberos AS requests for accounts that do not require pre-authentication, whose replies can
+    /// be cracked offline.
+    AsRepRoasting(AsRepRoasting),
+
+    /// NTLM authentication attempts against many accounts with a few common passwords.
+    NtlmPasswordSpray(NtlmPasswordSpray),
+
+    /// An event reported by a detector without a kind of its own, whose attributes are described
+    /// by a registered `CustomEventSchema`.
+    CustomEvent(CustomEvent),
+
     /// Multiple HTTP sessions with the same source and destination that occur within a short time.
     /// This is a sign of a possible unauthorized communication channel.
     RepeatedHttpSessions(RepeatedHttpSessions),
@@ -216,6 +280,55 @@ impl fmt::Display for Event {
                     event.time.to_rfc3339(),
                 )
             }
+            Event::SshBruteForce(event) => {
+                write!(
+                    f,
+                    "time={:?} event_kind={event_kind:?} category={category:?} {event}",
+                    event.time.to_rfc3339(),
+                )
+            }
+            Event::Beaconing(event) => {
+                write!(
+                    f,
+                    "time={:?} event_kind={event_kind:?} category={category:?} {event}",
+                    event.time.to_rfc3339(),
+                )
+            }
+            Event::VolumeExfiltration(event) => {
+                write!(
+                    f,
+                    "time={:?} event_kind={event_kind:?} category={category:?} {event}",
+                    event.time.to_rfc3339(),
+                )
+            }
+            Event::Kerberoasting(event) => {
+                write!(
+                    f,
+                    "time={:?} event_kind={event_kind:?} category={category:?} {event}",
+                    event.time.to_rfc3339(),
+                )
+            }
+            Event::AsRepRoasting(event) => {
+                write!(
+                    f,
+                    "time={:?} event_kind={event_kind:?} category={category:?} {event}",
+                    event.time.to_rfc3339(),
+                )
+            }
+            Event::NtlmPasswordSpray(event) => {
+                write!(
+                    f,
+                    "time={:?} event_kind={event_kind:?} category={category:?} {event}",
+                    event.time.to_rfc3339(),
+                )
+            }
+            Event::CustomEvent(event) => {
+                write!(
+                    f,
+                    "time={:?} event_kind={event_kind:?} category={category:?} {event}",
+                    event.time.to_rfc3339(),
+                )
+            }
             Event::RepeatedHttpSessions(event) => {
                 write!(
                     f,
@@ -515,490 +628,424 @@ impl Event {
     /// Returns whether the event matches the given filter. If the event matches, returns the
     /// triage score for the event.
     ///
+    /// The countries, customers, and networks of the addresses are taken from `enrichment`, the
+    /// stored enrichment of the event. Without it, the event matches no country filter, and the
+    /// customer and endpoint filters are checked against the addresses.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the filter contains a country filter but the ip2location database is
-    /// not available.
+    /// Returns an error if matching the event against the filter fails.
     pub fn matches(
         &self,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<(bool, Option<Vec<TriageScore>>)> {
         match self {
-            Event::DnsCovertChannel(event) => event.matches(locator, filter),
-            Event::HttpThreat(event) => event.matches(locator, filter),
-            Event::RdpBruteForce(event) => event.matches(locator, filter),
-            Event::RepeatedHttpSessions(event) => event.matches(locator, filter),
-            Event::TorConnection(event) => event.matches(locator, filter),
-            Event::TorConnectionConn(event) => event.matches(locator, filter),
-            Event::DomainGenerationAlgorithm(event) => event.matches(locator, filter),
-            Event::FtpBruteForce(event) => event.matches(locator, filter),
-            Event::FtpPlainText(event) => event.matches(locator, filter),
-            Event::PortScan(event) => event.matches(locator, filter),
-            Event::MultiHostPortScan(event) => event.matches(locator, filter),
-            Event::ExternalDdos(event) => event.matches(locator, filter),
-            Event::NonBrowser(event) => event.matches(locator, filter),
-            Event::LdapBruteForce(event) => event.matches(locator, filter),
-            Event::LdapPlainText(event) => event.matches(locator, filter),
-            Event::CryptocurrencyMiningPool(event) => event.matches(locator, filter),
+            Event::DnsCovertChannel(event) => event.matches(enrichment, filter),
+            Event::HttpThreat(event) => event.matches(enrichment, filter),
+            Event::RdpBruteForce(event) => event.matches(enrichment, filter),
+            Event::SshBruteForce(event) => event.matches(enrichment, filter),
+            Event::Beaconing(event) => event.matches(enrichment, filter),
+            Event::VolumeExfiltration(event) => event.matches(enrichment, filter),
+            Event::Kerberoasting(event) => event.matches(enrichment, filter),
+            Event::AsRepRoasting(event) => event.matches(enrichment, filter),
+            Event::NtlmPasswordSpray(event) => event.matches(enrichment, filter),
+            Event::CustomEvent(event) => event.matches(enrichment, filter),
+            Event::RepeatedHttpSessions(event) => event.matches(enrichment, filter),
+            Event::TorConnection(event) => event.matches(enrichment, filter),
+            Event::TorConnectionConn(event) => event.matches(enrichment, filter),
+            Event::DomainGenerationAlgorithm(event) => event.matches(enrichment, filter),
+            Event::FtpBruteForce(event) => event.matches(enrichment, filter),
+            Event::FtpPlainText(event) => event.matches(enrichment, filter),
+            Event::PortScan(event) => event.matches(enrichment, filter),
+            Event::MultiHostPortScan(event) => event.matches(enrichment, filter),
+            Event::ExternalDdos(event) => event.matches(enrichment, filter),
+            Event::NonBrowser(event) => event.matches(enrichment, filter),
+            Event::LdapBruteForce(event) => event.matches(enrichment, filter),
+            Event::LdapPlainText(event) => event.matches(enrichment, filter),
+            Event::CryptocurrencyMiningPool(event) => event.matches(enrichment, filter),
             Event::Blocklist(record_type) => match record_type {
-                RecordType::Bootp(bootp_event) => bootp_event.matches(locator, filter),
-                RecordType::Conn(conn_event) => conn_event.matches(locator, filter),
-                RecordType::DceRpc(dcerpc_event) => dcerpc_event.matches(locator, filter),
-                RecordType::Dhcp(dhcp_event) => dhcp_event.matches(locator, filter),
-                RecordType::Dns(dns_event) => dns_event.matches(locator, filter),
-                RecordType::Ftp(ftp_event) => ftp_event.matches(locator, filter),
-                RecordType::Http(http_event) => http_event.matches(locator, filter),
-                RecordType::Kerberos(kerberos_event) => kerberos_event.matches(locator, filter),
-                RecordType::Ldap(ldap_event) => ldap_event.matches(locator, filter),
+                RecordType::Bootp(bootp_event) => bootp_event.matches(enrichment, filter),
+                RecordType::Conn(conn_event) => conn_event.matches(enrichment, filter),
+                RecordType::DceRpc(dcerpc_event) => dcerpc_event.matches(enrichment, filter),
+                RecordType::Dhcp(dhcp_event) => dhcp_event.matches(enrichment, filter),
+                RecordType::Dns(dns_event) => dns_event.matches(enrichment, filter),
+                RecordType::Ftp(ftp_event) => ftp_event.matches(enrichment, filter),
+                RecordType::Http(http_event) => http_event.matches(enrichment, filter),
+                RecordType::Kerberos(kerberos_event) => kerberos_event.matches(enrichment, filter),
+                RecordType::Ldap(ldap_event) => ldap_event.matches(enrichment, filter),
                 RecordType::MalformedDns(malformed_dns_event) => {
-                    malformed_dns_event.matches(locator, filter)
-                }
-                RecordType::Mqtt(mqtt_event) => mqtt_event.matches(locator, filter),
-                RecordType::Nfs(nfs_event) => nfs_event.matches(locator, filter),
-                RecordType::Ntlm(ntlm_event) => ntlm_event.matches(locator, filter),
-                RecordType::Radius(radius_event) => radius_event.matches(locator, filter),
-                RecordType::Rdp(rdp_event) => rdp_event.matches(locator, filter),
-                RecordType::Smb(smb_event) => smb_event.matches(locator, filter),
-                RecordType::Smtp(smtp_event) => smtp_event.matches(locator, filter),
-                RecordType::Ssh(ssh_event) => ssh_event.matches(locator, filter),
-                RecordType::Tls(tls_event) => tls_event.matches(locator, filter),
-                RecordType::UnusualDestinationPattern(event) => event.matches(locator, filter),
+                    malformed_dns_event.matches(enrichment, filter)
+                }
+                RecordType::Mqtt(mqtt_event) => mqtt_event.matches(enrichment, filter),
+                RecordType::Nfs(nfs_event) => nfs_event.matches(enrichment, filter),
+                RecordType::Ntlm(ntlm_event) => ntlm_event.matches(enrichment, filter),
+                RecordType::Radius(radius_event) => radius_event.matches(enrichment, filter),
+                RecordType::Rdp(rdp_event) => rdp_event.matches(enrichment, filter),
+                RecordType::Smb(smb_event) => smb_event.matches(enrichment, filter),
+                RecordType::Smtp(smtp_event) => smtp_event.matches(enrichment, filter),
+                RecordType::Ssh(ssh_event) => ssh_event.matches(enrichment, filter),
+                RecordType::Tls(tls_event) => tls_event.matches(enrichment, filter),
+                RecordType::UnusualDestinationPattern(event) => event.matches(enrichment, filter),
             },
-            Event::WindowsThreat(event) => event.matches(locator, filter),
-            Event::NetworkThreat(event) => event.matches(locator, filter),
-            Event::ExtraThreat(event) => event.matches(locator, filter),
-            Event::LockyRansomware(event) => event.matches(locator, filter),
-            Event::SuspiciousTlsTraffic(event) => event.matches(locator, filter),
+            Event::WindowsThreat(event) => event.matches(enrichment, filter),
+            Event::NetworkThreat(event) => event.matches(enrichment, filter),
+            Event::ExtraThreat(event) => event.matches(enrichment, filter),
+            Event::LockyRansomware(event) => event.matches(enrichment, filter),
+            Event::SuspiciousTlsTraffic(event) => event.matches(enrichment, filter),
         }
     }
 
     fn address_pair(
         &self,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<(Option<IpAddr>, Option<IpAddr>)> {
-        let mut addr_pair = (None, None);
+        if self.matches(enrichment, filter)?.0 {
+            Ok(self.primary_addrs())
+        } else {
+            Ok((None, None))
+        }
+    }
+
+    /// Returns the source and destination addresses that represent the event in statistics.
+    fn primary_addrs(&self) -> (Option<IpAddr>, Option<IpAddr>) {
         match self {
-            Event::DnsCovertChannel(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::HttpThreat(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::RdpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), None);
-                }
-            }
-            Event::RepeatedHttpSessions(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::TorConnection(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::TorConnectionConn(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::DomainGenerationAlgorithm(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::FtpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::FtpPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::PortScan(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::MultiHostPortScan(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), None);
-                }
-            }
-            Event::ExternalDdos(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (None, Some(event.dst_addr));
-                }
-            }
-            Event::NonBrowser(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::LdapBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::LdapPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::CryptocurrencyMiningPool(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
+            Event::DnsCovertChannel(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::HttpThreat(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::RdpBruteForce(event) => (Some(event.src_addr), None),
+            Event::SshBruteForce(event) => (Some(event.src_addr), None),
+            Event::Beaconing(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::VolumeExfiltration(event) => {
+                (Some(event.src_addr), event.dst_addrs.first().copied())
+            }
+            Event::Kerberoasting(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::AsRepRoasting(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::NtlmPasswordSpray(event) => {
+                (Some(event.src_addr), event.dst_addrs.first().copied())
+            }
+            Event::CustomEvent(event) => (
+                event.src_addrs.first().copied(),
+                event.dst_addrs.first().copied(),
+            ),
+            Event::RepeatedHttpSessions(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::TorConnection(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::TorConnectionConn(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::DomainGenerationAlgorithm(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::FtpBruteForce(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::FtpPlainText(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::PortScan(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::MultiHostPortScan(event) => (Some(event.src_addr), None),
+            Event::ExternalDdos(event) => (None, Some(event.dst_addr)),
+            Event::NonBrowser(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::LdapBruteForce(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::LdapPlainText(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::CryptocurrencyMiningPool(event) => (Some(event.src_addr), Some(event.dst_addr)),
             Event::Blocklist(record_type) => match record_type {
                 RecordType::Bootp(bootp_event) => {
-                    if bootp_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(bootp_event.src_addr), Some(bootp_event.dst_addr));
-                    }
+                    (Some(bootp_event.src_addr), Some(bootp_event.dst_addr))
                 }
                 RecordType::Conn(conn_event) => {
-                    if conn_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(conn_event.src_addr), Some(conn_event.dst_addr));
-                    }
+                    (Some(conn_event.src_addr), Some(conn_event.dst_addr))
                 }
                 RecordType::DceRpc(dcerpc_event) => {
-                    if dcerpc_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(dcerpc_event.src_addr), Some(dcerpc_event.dst_addr));
-                    }
+                    (Some(dcerpc_event.src_addr), Some(dcerpc_event.dst_addr))
                 }
                 RecordType::Dhcp(dhcp_event) => {
-                    if dhcp_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(dhcp_event.src_addr), Some(dhcp_event.dst_addr));
-                    }
-                }
-                RecordType::Dns(dns_event) => {
-                    if dns_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(dns_event.src_addr), Some(dns_event.dst_addr));
-                    }
-                }
-                RecordType::Ftp(ftp_event) => {
-                    if ftp_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(ftp_event.src_addr), Some(ftp_event.dst_addr));
-                    }
+                    (Some(dhcp_event.src_addr), Some(dhcp_event.dst_addr))
                 }
+                RecordType::Dns(dns_event) => (Some(dns_event.src_addr), Some(dns_event.dst_addr)),
+                RecordType::Ftp(ftp_event) => (Some(ftp_event.src_addr), Some(ftp_event.dst_addr)),
                 RecordType::Http(http_event) => {
-                    if http_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(http_event.src_addr), Some(http_event.dst_addr));
-                    }
+                    (Some(http_event.src_addr), Some(http_event.dst_addr))
                 }
                 RecordType::Kerberos(kerberos_event) => {
-                    if kerberos_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(kerberos_event.src_addr), Some(kerberos_event.dst_addr));
-                    }
+                    (Some(kerberos_event.src_addr), Some(kerberos_event.dst_addr))
                 }
                 RecordType::Ldap(ldap_event) => {
-                    if ldap_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(ldap_event.src_addr), Some(ldap_event.dst_addr));
-                    }
-                }
-                RecordType::MalformedDns(malformed_dns_event) => {
-                    if malformed_dns_event.matches(locator, filter)?.0 {
-                        addr_pair = (
-                            Some(malformed_dns_event.orig_addr),
-                            Some(malformed_dns_event.resp_addr),
-                        );
-                    }
+                    (Some(ldap_event.src_addr), Some(ldap_event.dst_addr))
                 }
+                RecordType::MalformedDns(malformed_dns_event) => (
+                    Some(malformed_dns_event.orig_addr),
+                    Some(malformed_dns_event.resp_addr),
+                ),
                 RecordType::Mqtt(mqtt_event) => {
-                    if mqtt_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(mqtt_event.src_addr), Some(mqtt_event.dst_addr));
-                    }
-                }
-                RecordType::Nfs(nfs_event) => {
-                    if nfs_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(nfs_event.src_addr), Some(nfs_event.dst_addr));
-                    }
+                    (Some(mqtt_event.src_addr), Some(mqtt_event.dst_addr))
                 }
+                RecordType::Nfs(nfs_event) => (Some(nfs_event.src_addr), Some(nfs_event.dst_addr)),
                 RecordType::Ntlm(ntlm_event) => {
-                    if ntlm_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(ntlm_event.src_addr), Some(ntlm_event.dst_addr));
-                    }
+                    (Some(ntlm_event.src_addr), Some(ntlm_event.dst_addr))
                 }
                 RecordType::Radius(radius_event) => {
-                    if radius_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(radius_event.src_addr), Some(radius_event.dst_addr));
-                    }
-                }
-                RecordType::Rdp(rdp_event) => {
-                    if rdp_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(rdp_event.src_addr), Some(rdp_event.dst_addr));
-                    }
-                }
-                RecordType::Smb(smb_event) => {
-                    if smb_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(smb_event.src_addr), Some(smb_event.dst_addr));
-                    }
+                    (Some(radius_event.src_addr), Some(radius_event.dst_addr))
                 }
+                RecordType::Rdp(rdp_event) => (Some(rdp_event.src_addr), Some(rdp_event.dst_addr)),
+                RecordType::Smb(smb_event) => (Some(smb_event.src_addr), Some(smb_event.dst_addr)),
                 RecordType::Smtp(smtp_event) => {
-                    if smtp_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(smtp_event.src_addr), Some(smtp_event.dst_addr));
-                    }
-                }
-                RecordType::Ssh(ssh_event) => {
-                    if ssh_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(ssh_event.src_addr), Some(ssh_event.dst_addr));
-                    }
-                }
-                RecordType::Tls(tls_event) => {
-                    if tls_event.matches(locator, filter)?.0 {
-                        addr_pair = (Some(tls_event.src_addr), Some(tls_event.dst_addr));
-                    }
+                    (Some(smtp_event.src_addr), Some(smtp_event.dst_addr))
                 }
+                RecordType::Ssh(ssh_event) => (Some(ssh_event.src_addr), Some(ssh_event.dst_addr)),
+                RecordType::Tls(tls_event) => (Some(tls_event.src_addr), Some(tls_event.dst_addr)),
                 RecordType::UnusualDestinationPattern(event) => {
-                    if event.matches(locator, filter)?.0 {
-                        // UnusualDestinationPattern has multiple destination IPs but no source
-                        // Use first destination IP if available
-                        addr_pair = (None, event.destination_ips.first().copied());
-                    }
+                    // UnusualDestinationPattern has multiple destination IPs but no source
+                    // Use first destination IP if available
+                    (None, event.destination_ips.first().copied())
                 }
             },
-            Event::WindowsThreat(_event) => {}
-            Event::NetworkThreat(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.orig_addr), Some(event.resp_addr));
-                }
-            }
-            Event::ExtraThreat(_event) => {}
-            Event::LockyRansomware(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
-            Event::SuspiciousTlsTraffic(event) => {
-                if event.matches(locator, filter)?.0 {
-                    addr_pair = (Some(event.src_addr), Some(event.dst_addr));
-                }
-            }
+            Event::WindowsThreat(_event) => (None, None),
+            Event::NetworkThreat(event) => (Some(event.orig_addr), Some(event.resp_addr)),
+            Event::ExtraThreat(event) => (event.src_addr(), event.dst_addr()),
+            Event::LockyRansomware(event) => (Some(event.src_addr), Some(event.dst_addr)),
+            Event::SuspiciousTlsTraffic(event) => (Some(event.src_addr), Some(event.dst_addr)),
         }
-        Ok(addr_pair)
     }
 
     fn kind(
         &self,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<Option<&'static str>> {
         let mut kind = None;
         match self {
             Event::DnsCovertChannel(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(DNS_COVERT_CHANNEL);
                 }
             }
             Event::HttpThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(HTTP_THREAT);
                 }
             }
             Event::RdpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(RDP_BRUTE_FORCE);
                 }
             }
+            Event::SshBruteForce(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    kind = Some(SSH_BRUTE_FORCE);
+                }
+            }
+            Event::Beaconing(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    kind = Some(BEACONING);
+                }
+            }
+            Event::VolumeExfiltration(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    kind = Some(VOLUME_EXFILTRATION);
+                }
+            }
+            Event::Kerberoasting(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    kind = Some(KERBEROASTING);
+                }
+            }
+            Event::AsRepRoasting(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    kind = Some(AS_REP_ROASTING);
+                }
+            }
+            Event::NtlmPasswordSpray(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    kind = Some(NTLM_PASSWORD_SPRAY);
+                }
+            }
+            Event::CustomEvent(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    kind = Some(CUSTOM_EVENT);
+                }
+            }
             Event::RepeatedHttpSessions(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(REPEATED_HTTP_SESSIONS);
                 }
             }
             Event::TorConnection(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(TOR_CONNECTION);
                 }
             }
             Event::TorConnectionConn(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(TOR_CONNECTION_CONN);
                 }
             }
             Event::DomainGenerationAlgorithm(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(DOMAIN_GENERATION_ALGORITHM);
                 }
             }
             Event::FtpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(FTP_BRUTE_FORCE);
                 }
             }
             Event::FtpPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(FTP_PLAIN_TEXT);
                 }
             }
             Event::PortScan(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(PORT_SCAN);
                 }
             }
             Event::MultiHostPortScan(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(MULTI_HOST_PORT_SCAN);
                 }
             }
             Event::ExternalDdos(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(EXTERNAL_DDOS);
                 }
             }
             Event::NonBrowser(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(NON_BROWSER);
                 }
             }
             Event::LdapBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(LDAP_BRUTE_FORCE);
                 }
             }
             Event::LdapPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(LDAP_PLAIN_TEXT);
                 }
             }
             Event::CryptocurrencyMiningPool(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(CRYPTOCURRENCY_MINING_POOL);
                 }
             }
             Event::Blocklist(record_type) => match record_type {
                 RecordType::Bootp(bootp_event) => {
-                    if bootp_event.matches(locator, filter)?.0 {
+                    if bootp_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Conn(conn_event) => {
-                    if conn_event.matches(locator, filter)?.0 {
+                    if conn_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::DceRpc(dcerpc_event) => {
-                    if dcerpc_event.matches(locator, filter)?.0 {
+                    if dcerpc_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Dhcp(dhcp_event) => {
-                    if dhcp_event.matches(locator, filter)?.0 {
+                    if dhcp_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Dns(dns_event) => {
-                    if dns_event.matches(locator, filter)?.0 {
+                    if dns_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Ftp(ftp_event) => {
-                    if ftp_event.matches(locator, filter)?.0 {
+                    if ftp_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Http(http_event) => {
-                    if http_event.matches(locator, filter)?.0 {
+                    if http_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Kerberos(kerberos_event) => {
-                    if kerberos_event.matches(locator, filter)?.0 {
+                    if kerberos_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Ldap(ldap_event) => {
-                    if ldap_event.matches(locator, filter)?.0 {
+                    if ldap_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::MalformedDns(malformed_dns_event) => {
-                    if malformed_dns_event.matches(locator, filter)?.0 {
+                    if malformed_dns_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Mqtt(mqtt_event) => {
-                    if mqtt_event.matches(locator, filter)?.0 {
+                    if mqtt_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Nfs(nfs_event) => {
-                    if nfs_event.matches(locator, filter)?.0 {
+                    if nfs_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Ntlm(ntlm_event) => {
-                    if ntlm_event.matches(locator, filter)?.0 {
+                    if ntlm_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Radius(radius_event) => {
-                    if radius_event.matches(locator, filter)?.0 {
+                    if radius_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Rdp(rdp_event) => {
-                    if rdp_event.matches(locator, filter)?.0 {
+                    if rdp_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Smb(smb_event) => {
-                    if smb_event.matches(locator, filter)?.0 {
+                    if smb_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Smtp(smtp_event) => {
-                    if smtp_event.matches(locator, filter)?.0 {
+                    if smtp_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Ssh(ssh_event) => {
-                    if ssh_event.matches(locator, filter)?.0 {
+                    if ssh_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::Tls(tls_event) => {
-                    if tls_event.matches(locator, filter)?.0 {
+                    if tls_event.matches(enrichment, filter)?.0 {
                         kind = Some(BLOCKLIST);
                     }
                 }
                 RecordType::UnusualDestinationPattern(event) => {
-                    if event.matches(locator, filter)?.0 {
+                    if event.matches(enrichment, filter)?.0 {
                         kind = Some(UNUSUAL_DESTINATION_PATTERN);
                     }
                 }
             },
             Event::WindowsThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(WINDOWS_THREAT_EVENT);
                 }
             }
             Event::NetworkThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(NETWORK_THREAT_EVENT);
                 }
             }
             Event::ExtraThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(MISC_LOG_THREAT);
                 }
             }
             Event::LockyRansomware(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(LOCKY_RANSOMWARE);
                 }
             }
             Event::SuspiciousTlsTraffic(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     kind = Some(SUSPICIOUS_TLS_TRAFFIC);
                 }
             }
@@ -1011,6 +1058,13 @@ impl Event {
             Event::DnsCovertChannel(e) => (EventKind::DnsCovertChannel, e.category()),
             Event::HttpThreat(e) => (EventKind::HttpThreat, e.category()),
             Event::RdpBruteForce(e) => (EventKind::RdpBruteForce, e.category()),
+            Event::SshBruteForce(e) => (EventKind::SshBruteForce, e.category()),
+            Event::Beaconing(e) => (EventKind::Beaconing, e.category()),
+            Event::VolumeExfiltration(e) => (EventKind::VolumeExfiltration, e.category()),
+            Event::Kerberoasting(e) => (EventKind::Kerberoasting, e.category()),
+            Event::AsRepRoasting(e) => (EventKind::AsRepRoasting, e.category()),
+            Event::NtlmPasswordSpray(e) => (EventKind::NtlmPasswordSpray, e.category()),
+            Event::CustomEvent(e) => (EventKind::CustomEvent, e.category()),
             Event::RepeatedHttpSessions(e) => (EventKind::RepeatedHttpSessions, e.category()),
             Event::TorConnection(e) => (EventKind::TorConnection, e.category()),
             Event::TorConnectionConn(e) => (EventKind::TorConnectionConn, e.category()),
@@ -1067,10 +1121,30 @@ impl Event {
         kind.categories()
     }
 
+    /// Returns the level of the event after applying the severity overrides, if any.
+    #[must_use]
+    pub fn level(&self, overrides: Option<&SeverityOverrides>) -> NonZeroU8 {
+        self.as_match().level(overrides)
+    }
+
+    /// Returns the level assigned by the detector, regardless of severity overrides.
+    #[must_use]
+    pub fn detector_level(&self) -> NonZeroU8 {
+        self.as_match().detector_level()
+    }
+
+    /// Returns the MITRE ATT&CK technique IDs of the event. The techniques of the TI rule that the
+    /// event matched take precedence over those of the event kind.
+    #[must_use]
+    pub fn techniques<'a>(&'a self, rule_techniques: &'a RuleTechniques) -> Vec<&'a str> {
+        self.as_match().techniques(rule_techniques)
+    }
+
     // TODO: Need to implement country counting for `WindowsThreat`.
     // 1. for Network Connection: count country via ip
     // 2. for other Sysmon events: count the country by KR because the event does not have ip address.
-    /// Counts the number of events per country.
+    /// Counts the number of events per country, taking the countries from the stored enrichment
+    /// of the event. Addresses without a stored country are counted as "ZZ".
     ///
     /// # Errors
     ///
@@ -1078,42 +1152,29 @@ impl Event {
     pub fn count_country(
         &self,
         counter: &mut HashMap<String, usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let addr_pair = self.address_pair(locator, filter)?;
+        let (src_addr, dst_addr) = self.address_pair(enrichment, filter)?;
+        let country_of = |addr: IpAddr| {
+            enrichment
+                .and_then(|enrichment| enrichment.get(addr))
+                .and_then(|info| info.country.clone())
+                .unwrap_or_else(|| "ZZ".to_string())
+        };
 
-        let mut src_country = "ZZ".to_string();
-        let mut dst_country = "ZZ".to_string();
-        if let Some(locator) = locator {
-            if let Some(src_addr) = addr_pair.0 {
-                src_country = crate::util::find_ip_country(locator, src_addr);
+        match (src_addr.map(country_of), dst_addr.map(country_of)) {
+            // If origin and destination countries are different, count each one
+            (Some(src_country), Some(dst_country)) => {
+                if src_country != dst_country {
+                    *counter.entry(src_country).or_insert(0) += 1;
+                }
+                *counter.entry(dst_country).or_insert(0) += 1;
             }
-            if let Some(dst_addr) = addr_pair.1 {
-                dst_country = crate::util::find_ip_country(locator, dst_addr);
+            (None, Some(country)) | (Some(country), None) => {
+                *counter.entry(country).or_insert(0) += 1;
             }
-        }
-
-        // If origin and destination countries are different, count each one
-        if src_country != dst_country && addr_pair.0.is_some() && addr_pair.1.is_some() {
-            counter
-                .entry(src_country.clone())
-                .and_modify(|e| *e += 1)
-                .or_insert(1);
-        }
-        // If destination exists, count destination country (handles same country case)
-        if addr_pair.1.is_some() {
-            counter
-                .entry(dst_country)
-                .and_modify(|e| *e += 1)
-                .or_insert(1);
-        }
-        // If destination is None but origin exists, count origin country
-        else if addr_pair.0.is_some() {
-            counter
-                .entry(src_country)
-                .and_modify(|e| *e += 1)
-                .or_insert(1);
+            (None, None) => {}
         }
 
         Ok(())
@@ -1127,215 +1188,250 @@ impl Event {
     pub fn count_category(
         &self,
         counter: &mut HashMap<EventCategory, usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
         let mut category = None;
         match self {
             Event::DnsCovertChannel(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::HttpThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::RdpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
+                    category = event.category();
+                }
+            }
+            Event::SshBruteForce(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    category = event.category();
+                }
+            }
+            Event::Beaconing(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    category = event.category();
+                }
+            }
+            Event::VolumeExfiltration(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    category = event.category();
+                }
+            }
+            Event::Kerberoasting(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    category = event.category();
+                }
+            }
+            Event::AsRepRoasting(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    category = event.category();
+                }
+            }
+            Event::NtlmPasswordSpray(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    category = event.category();
+                }
+            }
+            Event::CustomEvent(event) => {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::RepeatedHttpSessions(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::TorConnection(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::TorConnectionConn(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::DomainGenerationAlgorithm(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::FtpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::FtpPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::PortScan(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::MultiHostPortScan(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::ExternalDdos(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::NonBrowser(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::LdapBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::LdapPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::CryptocurrencyMiningPool(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::Blocklist(record_type) => match record_type {
                 RecordType::Bootp(bootp_event) => {
-                    if bootp_event.matches(locator, filter)?.0 {
+                    if bootp_event.matches(enrichment, filter)?.0 {
                         category = bootp_event.category();
                     }
                 }
                 RecordType::Conn(conn_event) => {
-                    if conn_event.matches(locator, filter)?.0 {
+                    if conn_event.matches(enrichment, filter)?.0 {
                         category = conn_event.category();
                     }
                 }
                 RecordType::DceRpc(dcerpc_event) => {
-                    if dcerpc_event.matches(locator, filter)?.0 {
+                    if dcerpc_event.matches(enrichment, filter)?.0 {
                         category = dcerpc_event.category();
                     }
                 }
                 RecordType::Dhcp(dhcp_event) => {
-                    if dhcp_event.matches(locator, filter)?.0 {
+                    if dhcp_event.matches(enrichment, filter)?.0 {
                         category = dhcp_event.category();
                     }
                 }
                 RecordType::Dns(dns_event) => {
-                    if dns_event.matches(locator, filter)?.0 {
+                    if dns_event.matches(enrichment, filter)?.0 {
                         category = dns_event.category();
                     }
                 }
                 RecordType::Ftp(ftp_event) => {
-                    if ftp_event.matches(locator, filter)?.0 {
+                    if ftp_event.matches(enrichment, filter)?.0 {
                         category = ftp_event.category();
                     }
                 }
                 RecordType::Http(http_event) => {
-                    if http_event.matches(locator, filter)?.0 {
+                    if http_event.matches(enrichment, filter)?.0 {
                         category = http_event.category();
                     }
                 }
                 RecordType::Kerberos(kerberos_event) => {
-                    if kerberos_event.matches(locator, filter)?.0 {
+                    if kerberos_event.matches(enrichment, filter)?.0 {
                         category = kerberos_event.category();
                     }
                 }
                 RecordType::Ldap(ldap_event) => {
-                    if ldap_event.matches(locator, filter)?.0 {
+                    if ldap_event.matches(enrichment, filter)?.0 {
                         category = ldap_event.category();
                     }
                 }
                 RecordType::MalformedDns(malformed_dns_event) => {
-                    if malformed_dns_event.matches(locator, filter)?.0 {
+                    if malformed_dns_event.matches(enrichment, filter)?.0 {
                         category = malformed_dns_event.category();
                     }
                 }
                 RecordType::Mqtt(mqtt_event) => {
-                    if mqtt_event.matches(locator, filter)?.0 {
+                    if mqtt_event.matches(enrichment, filter)?.0 {
                         category = mqtt_event.category();
                     }
                 }
                 RecordType::Nfs(nfs_event) => {
-                    if nfs_event.matches(locator, filter)?.0 {
+                    if nfs_event.matches(enrichment, filter)?.0 {
                         category = nfs_event.category();
                     }
                 }
                 RecordType::Ntlm(ntlm_event) => {
-                    if ntlm_event.matches(locator, filter)?.0 {
+                    if ntlm_event.matches(enrichment, filter)?.0 {
                         category = ntlm_event.category();
                     }
                 }
                 RecordType::Radius(radius_event) => {
-                    if radius_event.matches(locator, filter)?.0 {
+                    if radius_event.matches(enrichment, filter)?.0 {
                         category = radius_event.category();
                     }
                 }
                 RecordType::Rdp(rdp_event) => {
-                    if rdp_event.matches(locator, filter)?.0 {
+                    if rdp_event.matches(enrichment, filter)?.0 {
                         category = rdp_event.category();
                     }
                 }
                 RecordType::Smb(smb_event) => {
-                    if smb_event.matches(locator, filter)?.0 {
+                    if smb_event.matches(enrichment, filter)?.0 {
                         category = smb_event.category();
                     }
                 }
                 RecordType::Smtp(smtp_event) => {
-                    if smtp_event.matches(locator, filter)?.0 {
+                    if smtp_event.matches(enrichment, filter)?.0 {
                         category = smtp_event.category();
                     }
                 }
                 RecordType::Ssh(ssh_event) => {
-                    if ssh_event.matches(locator, filter)?.0 {
+                    if ssh_event.matches(enrichment, filter)?.0 {
                         category = ssh_event.category();
                     }
                 }
                 RecordType::Tls(tls_event) => {
-                    if tls_event.matches(locator, filter)?.0 {
+                    if tls_event.matches(enrichment, filter)?.0 {
                         category = tls_event.category();
                     }
                 }
                 RecordType::UnusualDestinationPattern(event) => {
-                    if event.matches(locator, filter)?.0 {
+                    if event.matches(enrichment, filter)?.0 {
                         category = event.category();
                     }
                 }
             },
             Event::WindowsThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::NetworkThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::ExtraThreat(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::LockyRansomware(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
             Event::SuspiciousTlsTraffic(event) => {
-                if event.matches(locator, filter)?.0 {
+                if event.matches(enrichment, filter)?.0 {
                     category = event.category();
                 }
             }
@@ -1356,10 +1452,10 @@ impl Event {
     pub fn count_ip_address(
         &self,
         counter: &mut HashMap<IpAddr, usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let addr_pair = self.address_pair(locator, filter)?;
+        let addr_pair = self.address_pair(enrichment, filter)?;
 
         if let Some(src_addr) = addr_pair.0 {
             counter.entry(src_addr).and_modify(|e| *e += 1).or_insert(1);
@@ -1379,10 +1475,10 @@ impl Event {
     pub fn count_ip_address_pair(
         &self,
         counter: &mut HashMap<(IpAddr, IpAddr), usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let addr_pair = self.address_pair(locator, filter)?;
+        let addr_pair = self.address_pair(enrichment, filter)?;
 
         if let Some(src_addr) = addr_pair.0
             && let Some(dst_addr) = addr_pair.1
@@ -1404,11 +1500,11 @@ impl Event {
     pub fn count_ip_address_pair_and_kind(
         &self,
         counter: &mut HashMap<(IpAddr, IpAddr, &'static str), usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let addr_pair = self.address_pair(locator, filter)?;
-        let kind = self.kind(locator, filter)?;
+        let addr_pair = self.address_pair(enrichment, filter)?;
+        let kind = self.kind(enrichment, filter)?;
 
         if let Some(src_addr) = addr_pair.0
             && let Some(dst_addr) = addr_pair.1
@@ -1431,10 +1527,10 @@ impl Event {
     pub fn count_src_ip_address(
         &self,
         counter: &mut HashMap<IpAddr, usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let addr_pair = self.address_pair(locator, filter)?;
+        let addr_pair = self.address_pair(enrichment, filter)?;
 
         if let Some(src_addr) = addr_pair.0 {
             counter.entry(src_addr).and_modify(|e| *e += 1).or_insert(1);
@@ -1451,10 +1547,10 @@ impl Event {
     pub fn count_dst_ip_address(
         &self,
         counter: &mut HashMap<IpAddr, usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let addr_pair = self.address_pair(locator, filter)?;
+        let addr_pair = self.address_pair(enrichment, filter)?;
 
         if let Some(dst_addr) = addr_pair.1 {
             counter.entry(dst_addr).and_modify(|e| *e += 1).or_insert(1);
@@ -1463,7 +1559,7 @@ impl Event {
         Ok(())
     }
 
-    /// Counts the number of events per event kind.
+    /// Counts the number of events per event kind. Custom events are counted per schema.
     ///
     /// # Errors
     ///
@@ -1471,17 +1567,19 @@ impl Event {
     pub fn count_kind(
         &self,
         counter: &mut HashMap<String, usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let kind = if let Event::HttpThreat(event) = self {
-            if event.matches(locator, filter)?.0 {
-                Some(event.attack_kind.clone())
-            } else {
-                None
-            }
-        } else {
-            self.kind(locator, filter)?.map(ToString::to_string)
+        let kind = match self {
+            Event::HttpThreat(event) => event
+                .matches(enrichment, filter)?
+                .0
+                .then(|| event.attack_kind.clone()),
+            Event::CustomEvent(event) => event
+                .matches(enrichment, filter)?
+                .0
+                .then(|| event.schema.clone()),
+            _ => self.kind(enrichment, filter)?.map(ToString::to_string),
         };
 
         if let Some(kind) = kind {
@@ -1491,6 +1589,32 @@ impl Event {
         Ok(())
     }
 
+    /// Counts the number of events per MITRE ATT&CK technique. An event with more than one
+    /// technique is counted for each of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if matching the event against the filter fails.
+    pub fn count_technique(
+        &self,
+        counter: &mut HashMap<String, usize>,
+        enrichment: Option<&EventEnrichment>,
+        filter: &EventFilter,
+        rule_techniques: &RuleTechniques,
+    ) -> Result<()> {
+        let event = self.as_match();
+        if !event.matches(enrichment, filter)?.0 {
+            return Ok(());
+        }
+        for technique in event.techniques(rule_techniques) {
+            counter
+                .entry(technique.to_string())
+                .and_modify(|e| *e += 1)
+                .or_insert(1);
+        }
+        Ok(())
+    }
+
     /// Counts the number of events per level.
     ///
     /// # Errors
@@ -1499,216 +1623,251 @@ impl Event {
     pub fn count_level(
         &self,
         counter: &mut HashMap<NonZeroU8, usize>,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
         let mut level = None;
         match self {
             Event::DnsCovertChannel(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::HttpThreat(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::RdpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
+                }
+            }
+            Event::SshBruteForce(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
+                }
+            }
+            Event::Beaconing(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
+                }
+            }
+            Event::VolumeExfiltration(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
+                }
+            }
+            Event::Kerberoasting(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
+                }
+            }
+            Event::AsRepRoasting(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
+                }
+            }
+            Event::NtlmPasswordSpray(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
+                }
+            }
+            Event::CustomEvent(event) => {
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::RepeatedHttpSessions(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::TorConnection(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::TorConnectionConn(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::DomainGenerationAlgorithm(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::FtpBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::FtpPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::PortScan(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::MultiHostPortScan(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::ExternalDdos(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::NonBrowser(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::LdapBruteForce(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::LdapPlainText(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::CryptocurrencyMiningPool(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::Blocklist(record_type) => match record_type {
                 RecordType::Bootp(bootp_event) => {
-                    if bootp_event.matches(locator, filter)?.0 {
-                        level = Some(bootp_event.level());
+                    if bootp_event.matches(enrichment, filter)?.0 {
+                        level = Some(bootp_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Conn(conn_event) => {
-                    if conn_event.matches(locator, filter)?.0 {
-                        level = Some(conn_event.level());
+                    if conn_event.matches(enrichment, filter)?.0 {
+                        level = Some(conn_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::DceRpc(dcerpc_event) => {
-                    if dcerpc_event.matches(locator, filter)?.0 {
-                        level = Some(dcerpc_event.level());
+                    if dcerpc_event.matches(enrichment, filter)?.0 {
+                        level = Some(dcerpc_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Dhcp(dhcp_event) => {
-                    if dhcp_event.matches(locator, filter)?.0 {
-                        level = Some(dhcp_event.level());
+                    if dhcp_event.matches(enrichment, filter)?.0 {
+                        level = Some(dhcp_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Dns(dns_event) => {
-                    if dns_event.matches(locator, filter)?.0 {
-                        level = Some(dns_event.level());
+                    if dns_event.matches(enrichment, filter)?.0 {
+                        level = Some(dns_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Ftp(ftp_event) => {
-                    if ftp_event.matches(locator, filter)?.0 {
-                        level = Some(ftp_event.level());
+                    if ftp_event.matches(enrichment, filter)?.0 {
+                        level = Some(ftp_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Http(http_event) => {
-                    if http_event.matches(locator, filter)?.0 {
-                        level = Some(http_event.level());
+                    if http_event.matches(enrichment, filter)?.0 {
+                        level = Some(http_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Kerberos(kerberos_event) => {
-                    if kerberos_event.matches(locator, filter)?.0 {
-                        level = Some(kerberos_event.level());
+                    if kerberos_event.matches(enrichment, filter)?.0 {
+                        level = Some(kerberos_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Ldap(ldap_event) => {
-                    if ldap_event.matches(locator, filter)?.0 {
-                        level = Some(ldap_event.level());
+                    if ldap_event.matches(enrichment, filter)?.0 {
+                        level = Some(ldap_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::MalformedDns(malformed_dns_event) => {
-                    if malformed_dns_event.matches(locator, filter)?.0 {
-                        level = Some(malformed_dns_event.level());
+                    if malformed_dns_event.matches(enrichment, filter)?.0 {
+                        level = Some(malformed_dns_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Mqtt(mqtt_event) => {
-                    if mqtt_event.matches(locator, filter)?.0 {
-                        level = Some(mqtt_event.level());
+                    if mqtt_event.matches(enrichment, filter)?.0 {
+                        level = Some(mqtt_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Nfs(nfs_event) => {
-                    if nfs_event.matches(locator, filter)?.0 {
-                        level = Some(nfs_event.level());
+                    if nfs_event.matches(enrichment, filter)?.0 {
+                        level = Some(nfs_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Ntlm(ntlm_event) => {
-                    if ntlm_event.matches(locator, filter)?.0 {
-                        level = Some(ntlm_event.level());
+                    if ntlm_event.matches(enrichment, filter)?.0 {
+                        level = Some(ntlm_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Radius(radius_event) => {
-                    if radius_event.matches(locator, filter)?.0 {
-                        level = Some(radius_event.level());
+                    if radius_event.matches(enrichment, filter)?.0 {
+                        level = Some(radius_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Rdp(rdp_event) => {
-                    if rdp_event.matches(locator, filter)?.0 {
-                        level = Some(rdp_event.level());
+                    if rdp_event.matches(enrichment, filter)?.0 {
+                        level = Some(rdp_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Smb(smb_event) => {
-                    if smb_event.matches(locator, filter)?.0 {
-                        level = Some(smb_event.level());
+                    if smb_event.matches(enrichment, filter)?.0 {
+                        level = Some(smb_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Smtp(smtp_event) => {
-                    if smtp_event.matches(locator, filter)?.0 {
-                        level = Some(smtp_event.level());
+                    if smtp_event.matches(enrichment, filter)?.0 {
+                        level = Some(smtp_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Ssh(ssh_event) => {
-                    if ssh_event.matches(locator, filter)?.0 {
-                        level = Some(ssh_event.level());
+                    if ssh_event.matches(enrichment, filter)?.0 {
+                        level = Some(ssh_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::Tls(tls_event) => {
-                    if tls_event.matches(locator, filter)?.0 {
-                        level = Some(tls_event.level());
+                    if tls_event.matches(enrichment, filter)?.0 {
+                        level = Some(tls_event.level(filter.severity_overrides.as_ref()));
                     }
                 }
                 RecordType::UnusualDestinationPattern(event) => {
-                    if event.matches(locator, filter)?.0 {
-                        level = Some(event.level());
+                    if event.matches(enrichment, filter)?.0 {
+                        level = Some(event.level(filter.severity_overrides.as_ref()));
                     }
                 }
             },
             Event::WindowsThreat(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::NetworkThreat(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::ExtraThreat(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::LockyRansomware(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
             Event::SuspiciousTlsTraffic(event) => {
-                if event.matches(locator, filter)?.0 {
-                    level = Some(event.level());
+                if event.matches(enrichment, filter)?.0 {
+                    level = Some(event.level(filter.severity_overrides.as_ref()));
                 }
             }
         }
@@ -1720,7 +1879,8 @@ impl Event {
         Ok(())
     }
 
-    /// Counts the number of events per network.
+    /// Counts the number of events per network, taking the first of the networks containing
+    /// each address from the stored enrichment of the event.
     ///
     /// # Errors
     ///
@@ -1728,21 +1888,20 @@ impl Event {
     pub fn count_network(
         &self,
         counter: &mut HashMap<u32, usize>,
-        networks: &[Network],
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<()> {
-        let addr_pair = self.address_pair(locator, filter)?;
-
-        if let Some(src_addr) = addr_pair.0
-            && let Some(id) = find_network(src_addr, networks)
-        {
-            counter.entry(id).and_modify(|e| *e += 1).or_insert(1);
-        }
-        if let Some(dst_addr) = addr_pair.1
-            && let Some(id) = find_network(dst_addr, networks)
-        {
-            counter.entry(id).and_modify(|e| *e += 1).or_insert(1);
+        let Some(enrichment) = enrichment else {
+            return Ok(());
+        };
+        let (src_addr, dst_addr) = self.address_pair(Some(enrichment), filter)?;
+        for addr in [src_addr, dst_addr].into_iter().flatten() {
+            if let Some(&id) = enrichment
+                .get(addr)
+                .and_then(|info| info.network_ids.first())
+            {
+                counter.entry(id).and_modify(|e| *e += 1).or_insert(1);
+            }
         }
 
         Ok(())
@@ -1760,13 +1919,34 @@ impl Event {
             Event::RdpBruteForce(event) => {
                 event.triage_scores = Some(triage_scores);
             }
-            Event::RepeatedHttpSessions(event) => {
+            Event::SshBruteForce(event) => {
                 event.triage_scores = Some(triage_scores);
             }
-            Event::TorConnection(event) => {
+            Event::Beaconing(event) => {
                 event.triage_scores = Some(triage_scores);
             }
-            Event::TorConnectionConn(event) => {
+            Event::VolumeExfiltration(event) => {
+                event.triage_scores = Some(triage_scores);
+            }
+            Event::Kerberoasting(event) => {
+                event.triage_scores = Some(triage_scores);
+            }
+            Event::AsRepRoasting(event) => {
+                event.triage_scores = Some(triage_scores);
+            }
+            Event::NtlmPasswordSpray(event) => {
+                event.triage_scores = Some(triage_scores);
+            }
+            Event::CustomEvent(event) => {
+                event.triage_scores = Some(triage_scores);
+            }
+            Event::RepeatedHttpSessions(event) => {
+                event.triage_scores = Some(triage_scores);
+            }
+            Event::TorConnection(event) => {
+                event.triage_scores = Some(triage_scores);
+            }
+            Event::TorConnectionConn(event) => {
                 event.triage_scores = Some(triage_scores);
             }
             Event::DomainGenerationAlgorithm(event) => {
@@ -1879,6 +2059,166 @@ impl Event {
         }
     }
 
+    /// Redacts the user names, passwords, cookies, bodies, log contents, and classified custom
+    /// attributes in the event according to the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the policy is invalid.
+    pub fn redact(&mut self, policy: &RedactionPolicy) -> Result<()> {
+        policy.verify()?;
+        match self {
+            Event::HttpThreat(event) => event.redact(policy),
+            Event::TorConnection(event) => event.redact(policy),
+            Event::DomainGenerationAlgorithm(event) => event.redact(policy),
+            Event::FtpBruteForce(event) => event.redact(policy),
+            Event::FtpPlainText(event) => event.redact(policy),
+            Event::NonBrowser(event) => event.redact(policy),
+            Event::LdapBruteForce(event) => event.redact(policy),
+            Event::Kerberoasting(event) => event.redact(policy),
+            Event::AsRepRoasting(event) => event.redact(policy),
+            Event::NtlmPasswordSpray(event) => event.redact(policy),
+            Event::Blocklist(RecordType::Ftp(event)) => event.redact(policy),
+            Event::Blocklist(RecordType::Http(event)) => event.redact(policy),
+            Event::Blocklist(RecordType::Ntlm(event)) => event.redact(policy),
+            Event::Blocklist(RecordType::Radius(event)) => event.redact(policy),
+            Event::Blocklist(RecordType::Rdp(event)) => event.redact(policy),
+            Event::LdapPlainText(event) => event.redact(policy),
+            Event::Blocklist(RecordType::Ldap(event)) => event.redact(policy),
+            Event::ExtraThreat(event) => event.redact(policy),
+            Event::CustomEvent(event) => event.redact(policy),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if a source or destination address of the event is in the networks of any
+    /// of the customers.
+    pub(crate) fn involves(&self, customers: &[Customer]) -> bool {
+        let event = self.as_match();
+        customers.iter().any(|customer| {
+            event
+                .src_addrs()
+                .iter()
+                .chain(event.dst_addrs())
+                .any(|&addr| customer.contains(addr))
+        })
+    }
+
+    /// Returns the event as a `Match` trait object.
+    fn as_match(&self) -> &dyn Match {
+        match self {
+            Event::DnsCovertChannel(event) => event,
+            Event::HttpThreat(event) => event,
+            Event::RdpBruteForce(event) => event,
+            Event::SshBruteForce(event) => event,
+            Event::Beaconing(event) => event,
+            Event::VolumeExfiltration(event) => event,
+            Event::Kerberoasting(event) => event,
+            Event::AsRepRoasting(event) => event,
+            Event::NtlmPasswordSpray(event) => event,
+            Event::CustomEvent(event) => event,
+            Event::RepeatedHttpSessions(event) => event,
+            Event::TorConnection(event) => event,
+            Event::TorConnectionConn(event) => event,
+            Event::DomainGenerationAlgorithm(event) => event,
+            Event::FtpBruteForce(event) => event,
+            Event::FtpPlainText(event) => event,
+            Event::PortScan(event) => event,
+            Event::MultiHostPortScan(event) => event,
+            Event::ExternalDdos(event) => event,
+            Event::NonBrowser(event) => event,
+            Event::LdapBruteForce(event) => event,
+            Event::LdapPlainText(event) => event,
+            Event::CryptocurrencyMiningPool(event) => event,
+            Event::Blocklist(record_type) => match record_type {
+                RecordType::Bootp(bootp_event) => bootp_event,
+                RecordType::Conn(conn_event) => conn_event,
+                RecordType::DceRpc(dcerpc_event) => dcerpc_event,
+                RecordType::Dhcp(dhcp_event) => dhcp_event,
+                RecordType::Dns(dns_event) => dns_event,
+                RecordType::Ftp(ftp_event) => ftp_event,
+                RecordType::Http(http_event) => http_event,
+                RecordType::Kerberos(kerberos_event) => kerberos_event,
+                RecordType::Ldap(ldap_event) => ldap_event,
+                RecordType::MalformedDns(malformed_dns_event) => malformed_dns_event,
+                RecordType::Mqtt(mqtt_event) => mqtt_event,
+                RecordType::Nfs(nfs_event) => nfs_event,
+                RecordType::Ntlm(ntlm_event) => ntlm_event,
+                RecordType::Radius(radius_event) => radius_event,
+                RecordType::Rdp(rdp_event) => rdp_event,
+                RecordType::Smb(smb_event) => smb_event,
+                RecordType::Smtp(smtp_event) => smtp_event,
+                RecordType::Ssh(ssh_event) => ssh_event,
+                RecordType::Tls(tls_event) => tls_event,
+                RecordType::UnusualDestinationPattern(event) => event,
+            },
+            Event::WindowsThreat(event) => event,
+            Event::NetworkThreat(event) => event,
+            Event::ExtraThreat(event) => event,
+            Event::LockyRansomware(event) => event,
+            Event::SuspiciousTlsTraffic(event) => event,
+        }
+    }
+
+    /// Returns the triage scores of the event.
+    fn triage_scores(&self) -> Option<&[TriageScore]> {
+        match self {
+            Event::DnsCovertChannel(event) => event.triage_scores.as_deref(),
+            Event::HttpThreat(event) => event.triage_scores.as_deref(),
+            Event::RdpBruteForce(event) => event.triage_scores.as_deref(),
+            Event::SshBruteForce(event) => event.triage_scores.as_deref(),
+            Event::Beaconing(event) => event.triage_scores.as_deref(),
+            Event::VolumeExfiltration(event) => event.triage_scores.as_deref(),
+            Event::Kerberoasting(event) => event.triage_scores.as_deref(),
+            Event::AsRepRoasting(event) => event.triage_scores.as_deref(),
+            Event::NtlmPasswordSpray(event) => event.triage_scores.as_deref(),
+            Event::CustomEvent(event) => event.triage_scores.as_deref(),
+            Event::RepeatedHttpSessions(event) => event.triage_scores.as_deref(),
+            Event::TorConnection(event) => event.triage_scores.as_deref(),
+            Event::TorConnectionConn(event) => event.triage_scores.as_deref(),
+            Event::DomainGenerationAlgorithm(event) => event.triage_scores.as_deref(),
+            Event::FtpBruteForce(event) => event.triage_scores.as_deref(),
+            Event::FtpPlainText(event) => event.triage_scores.as_deref(),
+            Event::PortScan(event) => event.triage_scores.as_deref(),
+            Event::MultiHostPortScan(event) => event.triage_scores.as_deref(),
+            Event::ExternalDdos(event) => event.triage_scores.as_deref(),
+            Event::NonBrowser(event) => event.triage_scores.as_deref(),
+            Event::LdapBruteForce(event) => event.triage_scores.as_deref(),
+            Event::LdapPlainText(event) => event.triage_scores.as_deref(),
+            Event::CryptocurrencyMiningPool(event) => event.triage_scores.as_deref(),
+            Event::Blocklist(record_type) => match record_type {
+                RecordType::Bootp(bootp_event) => bootp_event.triage_scores.as_deref(),
+                RecordType::Conn(conn_event) => conn_event.triage_scores.as_deref(),
+                RecordType::DceRpc(dcerpc_event) => dcerpc_event.triage_scores.as_deref(),
+                RecordType::Dhcp(dhcp_event) => dhcp_event.triage_scores.as_deref(),
+                RecordType::Dns(dns_event) => dns_event.triage_scores.as_deref(),
+                RecordType::Ftp(ftp_event) => ftp_event.triage_scores.as_deref(),
+                RecordType::Http(http_event) => http_event.triage_scores.as_deref(),
+                RecordType::Kerberos(kerberos_event) => kerberos_event.triage_scores.as_deref(),
+                RecordType::Ldap(ldap_event) => ldap_event.triage_scores.as_deref(),
+                RecordType::MalformedDns(malformed_dns_event) => {
+                    malformed_dns_event.triage_scores.as_deref()
+                }
+                RecordType::Mqtt(mqtt_event) => mqtt_event.triage_scores.as_deref(),
+                RecordType::Nfs(nfs_event) => nfs_event.triage_scores.as_deref(),
+                RecordType::Ntlm(ntlm_event) => ntlm_event.triage_scores.as_deref(),
+                RecordType::Radius(radius_event) => radius_event.triage_scores.as_deref(),
+                RecordType::Rdp(rdp_event) => rdp_event.triage_scores.as_deref(),
+                RecordType::Smb(smb_event) => smb_event.triage_scores.as_deref(),
+                RecordType::Smtp(smtp_event) => smtp_event.triage_scores.as_deref(),
+                RecordType::Ssh(ssh_event) => ssh_event.triage_scores.as_deref(),
+                RecordType::Tls(tls_event) => tls_event.triage_scores.as_deref(),
+                RecordType::UnusualDestinationPattern(event) => event.triage_scores.as_deref(),
+            },
+            Event::WindowsThreat(event) => event.triage_scores.as_deref(),
+            Event::NetworkThreat(event) => event.triage_scores.as_deref(),
+            Event::ExtraThreat(event) => event.triage_scores.as_deref(),
+            Event::LockyRansomware(event) => event.triage_scores.as_deref(),
+            Event::SuspiciousTlsTraffic(event) => event.triage_scores.as_deref(),
+        }
+    }
+
     /// Generate syslog msgid and message body for RFC5424.
     #[must_use]
     pub fn syslog_message(&self) -> (String, String, String) {
@@ -1887,16 +2227,9 @@ impl Event {
     }
 }
 
-fn find_network(ip: IpAddr, networks: &[Network]) -> Option<u32> {
-    for net in networks {
-        if net.networks.contains(ip) {
-            return Some(net.id);
-        }
-    }
-    None
-}
-
-#[derive(Serialize, Clone, Copy, Debug, Deserialize, Eq, FromPrimitive, PartialEq, ToPrimitive)]
+#[derive(
+    Serialize, Clone, Copy, Debug, Deserialize, EnumIter, Eq, FromPrimitive, PartialEq, ToPrimitive,
+)]
 #[allow(clippy::module_name_repetitions)]
 pub enum EventKind {
     DnsCovertChannel,
@@ -1940,6 +2273,13 @@ pub enum EventKind {
     BlocklistRadius,
     BlocklistMalformedDns,
     UnusualDestinationPattern,
+    SshBruteForce,
+    Beaconing,
+    VolumeExfiltration,
+    Kerberoasting,
+    AsRepRoasting,
+    NtlmPasswordSpray,
+    CustomEvent,
 }
 
 impl EventKind {
@@ -1995,8 +2335,177 @@ impl EventKind {
             Self::BlocklistDhcp => &[EventCategory::InitialAccess],
             Self::BlocklistRadius => &[EventCategory::InitialAccess],
             Self::UnusualDestinationPattern => &[EventCategory::Reconnaissance],
+            Self::SshBruteForce => &[EventCategory::CredentialAccess],
+            Self::Beaconing => &[EventCategory::CommandAndControl],
+            Self::VolumeExfiltration => &[EventCategory::Exfiltration],
+            Self::Kerberoasting => &[EventCategory::CredentialAccess],
+            Self::AsRepRoasting => &[EventCategory::CredentialAccess],
+            Self::NtlmPasswordSpray => &[EventCategory::CredentialAccess],
+            Self::CustomEvent => &[
+                EventCategory::Reconnaissance,
+                EventCategory::InitialAccess,
+                EventCategory::Execution,
+                EventCategory::CredentialAccess,
+                EventCategory::Discovery,
+                EventCategory::LateralMovement,
+                EventCategory::CommandAndControl,
+                EventCategory::Exfiltration,
+                EventCategory::Impact,
+                EventCategory::Collection,
+                EventCategory::DefenseEvasion,
+                EventCategory::Persistence,
+                EventCategory::PrivilegeEscalation,
+                EventCategory::ResourceDevelopment,
+            ],
+        }
+    }
+
+    /// Returns the MITRE ATT&CK technique IDs that this event kind represents.
+    ///
+    /// Blocklist kinds and the kinds detected by TI rules have no techniques of their own; the
+    /// techniques of the matched rule, if any, apply to such events instead.
+    #[must_use]
+    #[allow(clippy::match_same_arms)]
+    pub fn techniques(&self) -> &'static [&'static str] {
+        match self {
+            Self::DnsCovertChannel => &["T1071.004", "T1048"],
+            Self::HttpThreat => &["T1190"],
+            Self::RdpBruteForce => &["T1110"],
+            Self::RepeatedHttpSessions => &["T1071.001"],
+            Self::TorConnection => &["T1090.003"],
+            Self::TorConnectionConn => &["T1090.003"],
+            Self::DomainGenerationAlgorithm => &["T1568.002"],
+            Self::FtpBruteForce => &["T1110"],
+            Self::FtpPlainText => &["T1552"],
+            Self::PortScan => &["T1046"],
+            Self::MultiHostPortScan => &["T1046"],
+            Self::NonBrowser => &["T1071.001"],
+            Self::LdapBruteForce => &["T1110"],
+            Self::LdapPlainText => &["T1552"],
+            Self::ExternalDdos => &["T1498"],
+            Self::CryptocurrencyMiningPool => &["T1496"],
+            Self::LockyRansomware => &["T1486"],
+            Self::SuspiciousTlsTraffic => &["T1573"],
+            Self::UnusualDestinationPattern => &["T1071"],
+            Self::SshBruteForce => &["T1110"],
+            Self::Beaconing => &["T1071"],
+            Self::VolumeExfiltration => &["T1048"],
+            Self::Kerberoasting => &["T1558.003"],
+            Self::AsRepRoasting => &["T1558.004"],
+            Self::NtlmPasswordSpray => &["T1110.003"],
+            Self::CustomEvent => &[],
+            Self::ExtraThreat
+            | Self::WindowsThreat
+            | Self::NetworkThreat
+            | Self::BlocklistBootp
+            | Self::BlocklistConn
+            | Self::BlocklistDceRpc
+            | Self::BlocklistDhcp
+            | Self::BlocklistDns
+            | Self::BlocklistFtp
+            | Self::BlocklistHttp
+            | Self::BlocklistKerberos
+            | Self::BlocklistLdap
+            | Self::BlocklistMalformedDns
+            | Self::BlocklistMqtt
+            | Self::BlocklistNfs
+            | Self::BlocklistNtlm
+            | Self::BlocklistRadius
+            | Self::BlocklistRdp
+            | Self::BlocklistSmb
+            | Self::BlocklistSmtp
+            | Self::BlocklistSsh
+            | Self::BlocklistTls => &[],
+        }
+    }
+
+    /// Returns the attributes of the event kind that triage rules can refer to.
+    ///
+    /// Custom events have no attributes of their own; theirs are described by their registered
+    /// schemas.
+    #[must_use]
+    pub fn attributes(&self) -> &'static [EventAttr] {
+        attribute::attributes(*self)
+    }
+
+    /// Returns the event kinds that have the given attribute.
+    pub fn with_attribute(kind: RawEventAttrKind) -> impl Iterator<Item = (Self, EventAttr)> {
+        Self::iter().filter_map(move |event_kind| {
+            event_kind
+                .attributes()
+                .iter()
+                .find(|attr| attr.kind == kind)
+                .map(|attr| (event_kind, *attr))
+        })
+    }
+
+    /// Applies the visitor to the type of the fields of the event kind, as stored in
+    /// `EventMessage::fields`.
+    #[allow(clippy::match_same_arms)]
+    pub(crate) fn visit_fields<V: FieldsVisitor>(self, visitor: V) -> V::Output {
+        match self {
+            Self::DnsCovertChannel => visitor.visit::<DnsEventFields>(),
+            Self::HttpThreat => visitor.visit::<HttpThreatFields>(),
+            Self::RdpBruteForce => visitor.visit::<RdpBruteForceFields>(),
+            Self::SshBruteForce => visitor.visit::<SshBruteForceFields>(),
+            Self::Beaconing => visitor.visit::<BeaconingFields>(),
+            Self::VolumeExfiltration => visitor.visit::<VolumeExfiltrationFields>(),
+            Self::Kerberoasting => visitor.visit::<KerberoastingFields>(),
+            Self::AsRepRoasting => visitor.visit::<AsRepRoastingFields>(),
+            Self::NtlmPasswordSpray => visitor.visit::<NtlmPasswordSprayFields>(),
+            Self::CustomEvent => visitor.visit::<CustomEventFields>(),
+            Self::RepeatedHttpSessions => visitor.visit::<RepeatedHttpSessionsFields>(),
+            Self::TorConnection => visitor.visit::<HttpEventFields>(),
+            Self::TorConnectionConn => visitor.visit::<BlocklistConnFields>(),
+            Self::DomainGenerationAlgorithm => visitor.visit::<DgaFields>(),
+            Self::FtpBruteForce => visitor.visit::<FtpBruteForceFields>(),
+            Self::FtpPlainText => visitor.visit::<FtpEventFields>(),
+            Self::PortScan => visitor.visit::<PortScanFields>(),
+            Self::MultiHostPortScan => visitor.visit::<MultiHostPortScanFields>(),
+            Self::NonBrowser => visitor.visit::<HttpEventFields>(),
+            Self::LdapBruteForce => visitor.visit::<LdapBruteForceFields>(),
+            Self::LdapPlainText => visitor.visit::<LdapEventFields>(),
+            Self::ExternalDdos => visitor.visit::<ExternalDdosFields>(),
+            Self::CryptocurrencyMiningPool => visitor.visit::<CryptocurrencyMiningPoolFields>(),
+            Self::BlocklistBootp => visitor.visit::<BlocklistBootpFields>(),
+            Self::BlocklistConn => visitor.visit::<BlocklistConnFields>(),
+            Self::BlocklistDceRpc => visitor.visit::<BlocklistDceRpcFields>(),
+            Self::BlocklistDhcp => visitor.visit::<BlocklistDhcpFields>(),
+            Self::BlocklistDns => visitor.visit::<BlocklistDnsFields>(),
+            Self::BlocklistFtp => visitor.visit::<FtpEventFields>(),
+            Self::BlocklistHttp => visitor.visit::<BlocklistHttpFields>(),
+            Self::BlocklistKerberos => visitor.visit::<BlocklistKerberosFields>(),
+            Self::BlocklistLdap => visitor.visit::<LdapEventFields>(),
+            Self::BlocklistMalformedDns => visitor.visit::<BlocklistMalformedDnsFields>(),
+            Self::BlocklistMqtt => visitor.visit::<BlocklistMqttFields>(),
+            Self::BlocklistNfs => visitor.visit::<BlocklistNfsFields>(),
+            Self::BlocklistNtlm => visitor.visit::<BlocklistNtlmFields>(),
+            Self::BlocklistRadius => visitor.visit::<BlocklistRadiusFields>(),
+            Self::BlocklistRdp => visitor.visit::<BlocklistRdpFields>(),
+            Self::BlocklistSmb => visitor.visit::<BlocklistSmbFields>(),
+            Self::BlocklistSmtp => visitor.visit::<BlocklistSmtpFields>(),
+            Self::BlocklistSsh => visitor.visit::<BlocklistSshFields>(),
+            Self::BlocklistTls => visitor.visit::<BlocklistTlsFields>(),
+            Self::WindowsThreat => visitor.visit::<WindowsThreat>(),
+            Self::NetworkThreat => visitor.visit::<NetworkThreat>(),
+            Self::ExtraThreat => visitor.visit::<ExtraThreat>(),
+            Self::LockyRansomware => visitor.visit::<DnsEventFields>(),
+            Self::SuspiciousTlsTraffic => visitor.visit::<BlocklistTlsFields>(),
+            Self::UnusualDestinationPattern => visitor.visit::<UnusualDestinationPatternFields>(),
         }
     }
+
+    /// Returns the event kind with the given name, as formatted by `Debug`.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::iter().find(|kind| format!("{kind:?}") == name)
+    }
+}
+
+/// An operation on the type of the fields of an event kind, applied by `EventKind::visit_fields`.
+pub(crate) trait FieldsVisitor {
+    type Output;
+
+    fn visit<T: Serialize + DeserializeOwned>(self) -> Self::Output;
 }
 
 /// Machine Learning Method.
@@ -2022,6 +2531,8 @@ pub struct EventFilter {
     confidence_min: Option<f32>,
     confidence_max: Option<f32>,
     triage_policies: Option<Vec<TriagePolicyInput>>,
+    techniques: Option<(Vec<String>, RuleTechniques)>,
+    severity_overrides: Option<SeverityOverrides>,
 }
 
 impl EventFilter {
@@ -2079,14 +2590,48 @@ impl EventFilter {
             confidence_min,
             confidence_max,
             triage_policies,
+            techniques: None,
+            severity_overrides: None,
         }
     }
 
+    /// Matches only events with any of the given MITRE ATT&CK techniques, looking up the
+    /// techniques of TI rules in `rule_techniques`. A technique also matches its sub-techniques.
+    #[must_use]
+    pub fn with_techniques(
+        mut self,
+        techniques: Vec<String>,
+        rule_techniques: RuleTechniques,
+    ) -> Self {
+        self.techniques = Some((techniques, rule_techniques));
+        self
+    }
+
+    /// Applies the severity overrides to the levels of events when filtering and counting them.
+    #[must_use]
+    pub fn with_severity_overrides(mut self, overrides: SeverityOverrides) -> Self {
+        self.severity_overrides = Some(overrides);
+        self
+    }
+
     #[must_use]
     pub fn has_country(&self) -> bool {
         self.countries.is_some()
     }
 
+    /// Limits the customers to match to those in `allowed`. A filter without customers matches
+    /// all of `allowed`.
+    pub(crate) fn restrict_customers(&mut self, allowed: &[Customer]) {
+        let customers = match self.customers.take() {
+            Some(customers) => customers
+                .into_iter()
+                .filter(|customer| allowed.iter().any(|a| a.id == customer.id))
+                .collect(),
+            None => allowed.to_vec(),
+        };
+        self.customers = Some(customers);
+    }
+
     pub fn moderate_kinds(&mut self) {
         if let Some(kinds) = self.kinds.as_mut() {
             moderate_kinds_by(kinds, &["dns", "covert", "channel"], "dns covert channel");
@@ -2096,6 +2641,20 @@ impl EventFilter {
                 "repeated http sessions",
             );
             moderate_kinds_by(kinds, &["rdp", "brute", "force"], "rdp brute force");
+            moderate_kinds_by(kinds, &["ssh", "brute", "force"], "ssh brute force");
+            moderate_kinds_by(kinds, &["beaconing", "beacon"], "beaconing");
+            moderate_kinds_by(kinds, &["volume", "exfiltration"], "volume exfiltration");
+            moderate_kinds_by(
+                kinds,
+                &["kerberoasting", "kerberos", "roasting"],
+                "kerberoasting",
+            );
+            moderate_kinds_by(kinds, &["as-rep", "asrep", "roasting"], "as-rep roasting");
+            moderate_kinds_by(
+                kinds,
+                &["ntlm", "password", "spray", "spraying"],
+                "ntlm password spray",
+            );
             moderate_kinds_by(kinds, &["tor", "connection"], "tor exit nodes");
             moderate_kinds_by(kinds, &["tor", "connection", "conn"], "tor exit nodes");
             moderate_kinds_by(kinds, &["domain", "generation", "algorithm"], "dga");
@@ -2266,6 +2825,24 @@ impl EventMessage {
                 .map(|fields| fields.syslog_rfc5424()),
             EventKind::RdpBruteForce => bincode::deserialize::<RdpBruteForceFields>(&self.fields)
                 .map(|fields| fields.syslog_rfc5424()),
+            EventKind::SshBruteForce => bincode::deserialize::<SshBruteForceFields>(&self.fields)
+                .map(|fields| fields.syslog_rfc5424()),
+            EventKind::Beaconing => bincode::deserialize::<BeaconingFields>(&self.fields)
+                .map(|fields| fields.syslog_rfc5424()),
+            EventKind::VolumeExfiltration => {
+                bincode::deserialize::<VolumeExfiltrationFields>(&self.fields)
+                    .map(|fields| fields.syslog_rfc5424())
+            }
+            EventKind::Kerberoasting => bincode::deserialize::<KerberoastingFields>(&self.fields)
+                .map(|fields| fields.syslog_rfc5424()),
+            EventKind::AsRepRoasting => bincode::deserialize::<AsRepRoastingFields>(&self.fields)
+                .map(|fields| fields.syslog_rfc5424()),
+            EventKind::NtlmPasswordSpray => {
+                bincode::deserialize::<NtlmPasswordSprayFields>(&self.fields)
+                    .map(|fields| fields.syslog_rfc5424())
+            }
+            EventKind::CustomEvent => bincode::deserialize::<CustomEventFields>(&self.fields)
+                .map(|fields| fields.syslog_rfc5424()),
             EventKind::RepeatedHttpSessions => {
                 bincode::deserialize::<RepeatedHttpSessionsFields>(&self.fields)
                     .map(|fields| fields.syslog_rfc5424())
@@ -2381,17 +2958,121 @@ impl EventMessage {
             )),
         }
     }
+
+    /// Redacts the user names, passwords, cookies, bodies, log contents, and classified custom
+    /// attributes in the event fields according to the policy. This can be called before the event
+    /// is stored so that sensitive values never reach the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the policy is invalid or the deserialization of the event fields
+    /// fails.
+    pub fn redact(&mut self, policy: &RedactionPolicy) -> Result<()> {
+        policy.verify()?;
+        let fields = &mut self.fields;
+        match self.kind {
+            EventKind::HttpThreat => redact_fields::<HttpThreatFields>(fields, policy),
+            EventKind::TorConnection | EventKind::NonBrowser => {
+                redact_fields::<HttpEventFields>(fields, policy)
+            }
+            EventKind::DomainGenerationAlgorithm | EventKind::BlocklistHttp => {
+                redact_fields::<DgaFields>(fields, policy)
+            }
+            EventKind::FtpBruteForce => redact_fields::<FtpBruteForceFields>(fields, policy),
+            EventKind::FtpPlainText | EventKind::BlocklistFtp => {
+                redact_fields::<FtpEventFields>(fields, policy)
+            }
+            EventKind::LdapBruteForce => redact_fields::<LdapBruteForceFields>(fields, policy),
+            EventKind::Kerberoasting => redact_fields::<KerberoastingFields>(fields, policy),
+            EventKind::AsRepRoasting => redact_fields::<AsRepRoastingFields>(fields, policy),
+            EventKind::NtlmPasswordSpray => {
+                redact_fields::<NtlmPasswordSprayFields>(fields, policy)
+            }
+            EventKind::BlocklistNtlm => redact_fields::<BlocklistNtlmFields>(fields, policy),
+            EventKind::BlocklistRadius => redact_fields::<BlocklistRadiusFields>(fields, policy),
+            EventKind::BlocklistRdp => redact_fields::<BlocklistRdpFields>(fields, policy),
+            EventKind::LdapPlainText | EventKind::BlocklistLdap => {
+                redact_fields::<LdapEventFields>(fields, policy)
+            }
+            EventKind::ExtraThreat => redact_fields::<ExtraThreat>(fields, policy),
+            EventKind::CustomEvent => redact_fields::<CustomEventFields>(fields, policy),
+            _ => Ok(()),
+        }
+        .with_context(|| {
+            format!(
+                "failed to redact event fields. time={:?}, event_kind={:?}",
+                self.time.to_rfc3339(),
+                self.kind
+            )
+        })
+    }
+
+    /// Formats the event as `syslog_rfc5424` does, after redacting it according to the policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the deserialization of the event fields fails.
+    pub fn syslog_rfc5424_redacted(
+        &self,
+        policy: &RedactionPolicy,
+    ) -> Result<(String, String, String)> {
+        let mut redacted = EventMessage {
+            time: self.time,
+            kind: self.kind,
+            fields: self.fields.clone(),
+        };
+        redacted.redact(policy)?;
+        redacted.syslog_rfc5424()
+    }
+
+    /// Returns the SHA-256 digest of the time, kind, and fields of the event, which can be used as
+    /// the dedup key of `EventDb::put_idempotent` by producers without sequence numbers.
+    #[must_use]
+    pub fn content_hash(&self) -> Vec<u8> {
+        let bytes = bincode::serialize(self).expect("serializable");
+        ring::digest::digest(&ring::digest::SHA256, &bytes)
+            .as_ref()
+            .to_vec()
+    }
 }
 
+/// The events in the database.
+///
+/// Each event is stored along with its enrichment, the country and ASN of each address looked up
+/// in the ip2location database given by `with_locator`, and the customer and networks containing
+/// it. The severity overrides, customers, and networks used to correlate and enrich events are
+/// loaded when first needed and kept for the lifetime of the `EventDb`. Get a new one from
+/// `Store::events` to see changes made to them afterwards.
 #[allow(clippy::module_name_repetitions)]
 pub struct EventDb<'a> {
     inner: &'a rocksdb::OptimisticTransactionDB,
+    locator: Option<&'a ip2location::DB>,
+    /// The compiled regular expressions of the log templates applied to extra threats.
+    regexes: RegexCache,
+    overrides: OnceLock<SeverityOverrides>,
+    customers: OnceLock<Vec<Customer>>,
+    enricher: OnceLock<Enricher>,
 }
 
 impl<'a> EventDb<'a> {
     #[must_use]
     pub fn new(inner: &'a rocksdb::OptimisticTransactionDB) -> EventDb<'a> {
-        Self { inner }
+        Self {
+            inner,
+            locator: None,
+            regexes: RegexCache::default(),
+            overrides: OnceLock::new(),
+            customers: OnceLock::new(),
+            enricher: OnceLock::new(),
+        }
+    }
+
+    /// Sets the ip2location database to look up the countries and ASNs of the addresses of the
+    /// events stored afterwards. Without it, no countries or ASNs are stored.
+    #[must_use]
+    pub fn with_locator(mut self, locator: &'a ip2location::DB) -> Self {
+        self.locator = Some(locator);
+        self
     }
 
     /// Creates an iterator over key-value pairs, starting from `key`.
@@ -2410,62 +3091,328 @@ impl<'a> EventDb<'a> {
         EventIterator { inner: iter }
     }
 
-    /// Stores a new event into the database.
+    /// Stores a new event into the database along with its enrichment, in the same transaction.
+    ///
+    /// The event is counted in the ingest statistics of its sensor if the sensor is registered,
+    /// and if a correlation policy is set, added to the incident it belongs to. A failure to do
+    /// either does not undo storing the event.
+    ///
+    /// The fields of an `ExtraThreat` without extracted fields are extracted from its content if
+    /// there is a log template for its service.
     ///
     /// # Errors
     ///
-    /// Returns an error if a database operation fails.
+    /// Returns an error if a custom event does not conform to its registered schema, or a database
+    /// operation fails.
     pub fn put(&self, event: &EventMessage) -> Result<i128> {
-        use anyhow::anyhow;
-        let mut key = (i128::from(event.time.timestamp_nanos_opt().unwrap_or(i64::MAX)) << 64)
-            | (event
-                .kind
-                .to_i128()
-                .ok_or(anyhow!("`EventKind` exceeds i128::MAX"))?
-                << 32);
-        loop {
+        self.store(event, None)
+    }
+
+    /// Stores a new event into the database, unless an event with the same dedup key has been
+    /// stored within the TTL of the dedup policy, in which case the key of that event is returned
+    /// without storing it again. This lets producers retry a put safely.
+    ///
+    /// The dedup key is chosen by the producer so that it identifies the event, e.g., the sensor
+    /// name followed by a sequence number, or `EventMessage::content_hash`. Otherwise, this works
+    /// the same as `put`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dedup key is empty, a custom event does not conform to its
+    /// registered schema, or a database operation fails.
+    pub fn put_idempotent(&self, event: &EventMessage, dedup_key: &[u8]) -> Result<i128> {
+        if dedup_key.is_empty() {
+            bail!("dedup key must not be empty");
+        }
+        self.store(event, Some(dedup_key))
+    }
+
+    /// Stores a new event along with its enrichment. An event that cannot be decoded is stored
+    /// without enrichment.
+    fn store(&self, event: &EventMessage, dedup_key: Option<&[u8]>) -> Result<i128> {
+        let value = self.prepare(event)?;
+        let base = Self::base_key(event)?;
+        let decoded = decode(base.to_be_bytes().into(), value.as_ref().into()).ok();
+        let enrichments =
+            Table::<EventEnrichment>::open(self.inner).context("no event enrichments table")?;
+        let mut enrichment = decoded
+            .as_ref()
+            .map(|(_, event)| self.enrichment_of(base, event))
+            .transpose()?;
+        let dedup = match dedup_key {
+            Some(dedup_key) => {
+                let table =
+                    Table::<DedupEntry>::open(self.inner).context("no event dedup keys table")?;
+                let policy = Table::<String>::open(self.inner)
+                    .context("no configs table")?
+                    .dedup_policy()?;
+                Some((table, policy, dedup_key))
+            }
+            None => None,
+        };
+        let now = Utc::now();
+        let key = loop {
             let txn = self.inner.transaction();
-            if txn
-                .get_for_update(key.to_be_bytes(), super::EXCLUSIVE)
-                .context("cannot read from event database")?
-                .is_some()
+            if let Some((table, policy, dedup_key)) = &dedup
+                && let Some(original) =
+                    table.lookup_with_transaction(dedup_key, policy, now, &txn)?
             {
-                let start = i128::from(rng().next_u32());
-                key |= start;
-                #[allow(clippy::cast_possible_wrap)] // bit pattern
-                while txn
-                    .get_for_update(key.to_be_bytes(), super::EXCLUSIVE)
-                    .context("cannot read from event database")?
-                    .is_some()
-                {
-                    let next = (key + 1) & 0xffff_ffff;
-                    if next == start {
-                        bail!("too many events with the same timestamp");
-                    }
-                    key = key & 0xffff_ffff_ffff_ffff_ffff_ffff_0000_0000_u128 as i128 | next;
-                }
+                return Ok(original);
             }
-            txn.put(key.to_be_bytes(), event.fields.as_slice())
+            let key = Self::free_key(&txn, base)?;
+            txn.put(key.to_be_bytes(), value.as_ref())
                 .context("cannot write event")?;
+            if let Some((table, _, dedup_key)) = &dedup {
+                let entry = DedupEntry {
+                    dedup_key: dedup_key.to_vec(),
+                    event: key,
+                    time: now,
+                };
+                table.record_with_transaction(&entry, &txn)?;
+            }
+            if let Some(enrichment) = &mut enrichment {
+                enrichment.key = key;
+                enrichments.put_with_transaction(enrichment, &txn)?;
+            }
             match txn.commit() {
-                Ok(()) => break,
+                Ok(()) => break key,
                 Err(e) => {
                     if !e.as_ref().starts_with("Resource busy:") {
                         return Err(e).context("failed to store event");
                     }
                 }
             }
+        };
+        if let Some((table, policy, _)) = &dedup
+            && let Err(e) = table.prune(policy, now)
+        {
+            warn!("failed to prune event dedup keys: {e:#}");
+        }
+        let Some((_, stored)) = decoded else {
+            warn!("cannot decode event {key} to record its ingest or correlate it");
+            return Ok(key);
+        };
+        if let Err(e) = self.record_ingest(&stored, now) {
+            warn!("failed to record the ingest of event {key}: {e:#}");
+        }
+        if let Err(e) = self.correlate(key, &stored) {
+            warn!("failed to correlate event {key}: {e:#}");
+        }
+        Ok(key)
+    }
+
+    /// Checks the fields of the event, and returns them as they should be stored.
+    fn prepare<'e>(&self, event: &'e EventMessage) -> Result<Cow<'e, [u8]>> {
+        match event.kind {
+            EventKind::CustomEvent => {
+                let fields: CustomEventFields =
+                    bincode::deserialize(&event.fields).context("invalid custom event fields")?;
+                Table::<CustomEventSchema>::open(self.inner)
+                    .context("no custom event schemas table")?
+                    .validate(&fields)?;
+            }
+            EventKind::ExtraThreat => {
+                let mut threat: ExtraThreat =
+                    bincode::deserialize(&event.fields).context("invalid extra threat fields")?;
+                if threat.fields.is_none() {
+                    threat.fields = Table::<LogTemplate>::open(self.inner)
+                        .context("no log templates table")?
+                        .extract_with(&threat.service, &threat.content, &self.regexes)?;
+                    if threat.fields.is_some() {
+                        return Ok(Cow::Owned(bincode::serialize(&threat)?));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(Cow::Borrowed(event.fields.as_slice()))
+    }
+
+    /// Returns the key of the event without the lower 32 bits that tell apart events of the same
+    /// kind with the same timestamp.
+    fn base_key(event: &EventMessage) -> Result<i128> {
+        use anyhow::anyhow;
+        Ok(
+            (i128::from(event.time.timestamp_nanos_opt().unwrap_or(i64::MAX)) << 64)
+                | (event
+                    .kind
+                    .to_i128()
+                    .ok_or(anyhow!("`EventKind` exceeds i128::MAX"))?
+                    << 32),
+        )
+    }
+
+    /// Returns a key starting with `base` that is used neither in the database nor in the
+    /// transaction.
+    fn free_key(
+        txn: &rocksdb::Transaction<rocksdb::OptimisticTransactionDB>,
+        base: i128,
+    ) -> Result<i128> {
+        let mut key = base;
+        if txn
+            .get_for_update(key.to_be_bytes(), super::EXCLUSIVE)
+            .context("cannot read from event database")?
+            .is_some()
+        {
+            let start = i128::from(rng().next_u32());
+            key |= start;
+            #[allow(clippy::cast_possible_wrap)] // bit pattern
+            while txn
+                .get_for_update(key.to_be_bytes(), super::EXCLUSIVE)
+                .context("cannot read from event database")?
+                .is_some()
+            {
+                let next = (key + 1) & 0xffff_ffff;
+                if next == start {
+                    bail!("too many events with the same timestamp");
+                }
+                key = key & 0xffff_ffff_ffff_ffff_ffff_ffff_0000_0000_u128 as i128 | next;
+            }
         }
         Ok(key)
     }
 
-    /// Updates an old key-value pair to a new one.
+    /// Returns the enrichment of the event to be stored with the given key.
+    fn enrichment_of(&self, key: i128, event: &Event) -> Result<EventEnrichment> {
+        let enricher = cached(&self.enricher, || {
+            Table::<EventEnrichment>::open(self.inner)
+                .context("no event enrichments table")?
+                .enricher()
+        })?;
+        let event = event.as_match();
+        Ok(enricher.enrichment(
+            self.locator,
+            key,
+            event.src_addrs(),
+            event.dst_addrs(),
+            None,
+        ))
+    }
+
+    /// Returns the enrichment of the event with the given key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a database operation fails.
+    pub fn enrichment(&self, key: i128) -> Result<Option<EventEnrichment>> {
+        Table::<EventEnrichment>::open(self.inner)
+            .context("no event enrichments table")?
+            .get(key)
+    }
+
+    /// Recomputes the enrichment of all the events, e.g., after customer networks have changed or
+    /// for events stored before enrichment was introduced. Without an ip2location database set by
+    /// `with_locator`, the previously stored countries and ASNs are kept. Returns the number of
+    /// events enriched; events that cannot be decoded are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a database operation fails.
+    pub fn reenrich(&self) -> Result<usize> {
+        let enrichments =
+            Table::<EventEnrichment>::open(self.inner).context("no event enrichments table")?;
+        let enricher = enrichments.enricher()?;
+        let mut count = 0;
+        for (key, event) in self.iter_forward().filter_map(Result::ok) {
+            let event = event.as_match();
+            enrichments.enrich(
+                &enricher,
+                self.locator,
+                key,
+                event.src_addrs(),
+                event.dst_addrs(),
+            )?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Counts the event in the ingest statistics of its sensor, unless the sensor is not
+    /// registered.
+    fn record_ingest(&self, event: &Event, time: DateTime<Utc>) -> Result<()> {
+        let sensor = event.as_match().sensor();
+        if Table::<Sensor>::open(self.inner)
+            .context("no sensors table")?
+            .get(sensor)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        let (kind, _) = event.kind_and_category();
+        Table::<SensorIngest>::open(self.inner)
+            .context("no sensor ingest table")?
+            .record(sensor, kind, time)
+    }
+
+    /// Adds the event stored with the given key to its incident.
+    fn correlate(&self, key: i128, event: &Event) -> Result<()> {
+        let Some(policy) = Table::<String>::open(self.inner)
+            .context("no configs table")?
+            .correlation_policy()?
+        else {
+            return Ok(());
+        };
+        let overrides = cached(&self.overrides, || {
+            Table::<SeverityOverride>::open(self.inner)
+                .context("no severity overrides table")?
+                .load()
+        })?;
+        let (kind, _) = event.kind_and_category();
+        let event_match = event.as_match();
+        let first_addr = |addrs: &[IpAddr]| addrs.iter().copied().find(|a| !a.is_unspecified());
+        let src_addr = first_addr(event_match.src_addrs());
+        let dst_addr = first_addr(event_match.dst_addrs());
+        let customer = if policy.keys.contains(&CorrelationKey::Customer) {
+            let customers = cached(&self.customers, || {
+                IndexedTable::<Customer>::open(self.inner)
+                    .context("no customers table")?
+                    .iter(Direction::Forward, None)
+                    .collect::<Result<Vec<_>>>()
+            })?;
+            let owner = |addr: Option<IpAddr>| {
+                let addr = addr?;
+                customers
+                    .iter()
+                    .find(|customer| customer.contains(addr))
+                    .map(|customer| customer.id)
+            };
+            owner(src_addr).or_else(|| owner(dst_addr))
+        } else {
+            None
+        };
+        let input = CorrelationInput {
+            key,
+            time: Utc.timestamp_nanos((key >> 64).try_into().expect("valid i64")),
+            kind,
+            src_addr,
+            dst_addr,
+            sensor: event_match.sensor(),
+            customer,
+            level: event_match.level(Some(overrides)),
+            triage_score: event.triage_scores().map_or(0.0, |scores| {
+                scores.iter().map(|s| s.score).fold(0.0, f64::max)
+            }),
+        };
+        Table::<Incident>::open(self.inner)
+            .context("no incidents table")?
+            .correlate(&policy, &input)?;
+        Ok(())
+    }
+
+    /// Updates an old key-value pair to a new one, replacing the enrichment of the old event with
+    /// that of the new one.
     ///
     /// # Errors
     ///
     /// Returns an error if the old value does not match the value in the database, the old key does
     /// not exist, or the database operation fails.
     pub fn update(&self, old: (&[u8], &[u8]), new: (&[u8], &[u8])) -> Result<()> {
+        let enrichments =
+            Table::<EventEnrichment>::open(self.inner).context("no event enrichments table")?;
+        let enrichment = decode(new.0.into(), new.1.into())
+            .ok()
+            .map(|(key, event)| self.enrichment_of(key, &event))
+            .transpose()?;
         loop {
             let txn = self.inner.transaction();
             if let Some(old_value) = txn
@@ -2483,6 +3430,10 @@ impl<'a> EventDb<'a> {
             if old.0 != new.0 {
                 txn.delete(old.0).context("failed to delete old entry")?;
             }
+            enrichments.delete_with_transaction(old.0, &txn)?;
+            if let Some(enrichment) = &enrichment {
+                enrichments.put_with_transaction(enrichment, &txn)?;
+            }
 
             match txn.commit() {
                 Ok(()) => break,
@@ -2505,387 +3456,424 @@ pub struct EventIterator<'i> {
     >,
 }
 
+/// Returns the value of `cell`, initializing it with `init` if it is empty.
+fn cached<T>(cell: &OnceLock<T>, init: impl FnOnce() -> Result<T>) -> Result<&T> {
+    if let Some(value) = cell.get() {
+        return Ok(value);
+    }
+    let value = init()?;
+    Ok(cell.get_or_init(|| value))
+}
+
 impl Iterator for EventIterator<'_> {
     type Item = Result<(i128, Event), InvalidEvent>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let (k, v) = self.inner.next().transpose().ok().flatten()?;
+        Some(decode(k, v))
+    }
+}
 
-        let key: [u8; 16] = if let Ok(key) = k.as_ref().try_into() {
-            key
-        } else {
-            return Some(Err(InvalidEvent::Key(k)));
-        };
-        let key = i128::from_be_bytes(key);
-        let time = Utc.timestamp_nanos((key >> 64).try_into().expect("valid i64"));
-        let kind_num = (key & 0xffff_ffff_0000_0000) >> 32;
-        let Some(kind) = EventKind::from_i128(kind_num) else {
-            return Some(Err(InvalidEvent::Key(k)));
-        };
-        match kind {
-            EventKind::BlocklistBootp => {
-                let Ok(fields) = bincode::deserialize::<BlocklistBootpFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Bootp(BlocklistBootp::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistConn => {
-                let Ok(fields) = bincode::deserialize::<BlocklistConnFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Conn(BlocklistConn::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistDceRpc => {
-                let Ok(fields) = bincode::deserialize::<BlocklistDceRpcFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::DceRpc(BlocklistDceRpc::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistDhcp => {
-                let Ok(fields) = bincode::deserialize::<BlocklistDhcpFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Dhcp(BlocklistDhcp::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistDns => {
-                let Ok(fields) = bincode::deserialize::<BlocklistDnsFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Dns(BlocklistDns::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistFtp => {
-                let Ok(fields) = bincode::deserialize::<FtpEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Ftp(BlocklistFtp::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistHttp => {
-                let Ok(fields) = bincode::deserialize::<BlocklistHttpFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Http(BlocklistHttp::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistKerberos => {
-                let Ok(fields) = bincode::deserialize::<BlocklistKerberosFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Kerberos(BlocklistKerberos::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistLdap => {
-                let Ok(fields) = bincode::deserialize::<LdapEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Ldap(BlocklistLdap::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistMalformedDns => {
-                let Ok(fields) = bincode::deserialize::<BlocklistMalformedDnsFields>(v.as_ref())
-                else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::MalformedDns(BlocklistMalformedDns::new(
-                        time, fields,
-                    ))),
-                )))
-            }
-            EventKind::BlocklistMqtt => {
-                let Ok(fields) = bincode::deserialize::<BlocklistMqttFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Mqtt(BlocklistMqtt::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistNfs => {
-                let Ok(fields) = bincode::deserialize::<BlocklistNfsFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Nfs(BlocklistNfs::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistNtlm => {
-                let Ok(fields) = bincode::deserialize::<BlocklistNtlmFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Ntlm(BlocklistNtlm::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistRadius => {
-                let Ok(fields) = bincode::deserialize::<BlocklistRadiusFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Radius(BlocklistRadius::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistRdp => {
-                let Ok(fields) = bincode::deserialize::<BlocklistRdpFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Rdp(BlocklistRdp::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistSmb => {
-                let Ok(fields) = bincode::deserialize::<BlocklistSmbFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Smb(BlocklistSmb::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistSmtp => {
-                let Ok(fields) = bincode::deserialize::<BlocklistSmtpFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Smtp(BlocklistSmtp::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistSsh => {
-                let Ok(fields) = bincode::deserialize::<BlocklistSshFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Ssh(BlocklistSsh::new(time, fields))),
-                )))
-            }
-            EventKind::BlocklistTls => {
-                let Ok(fields) = bincode::deserialize::<BlocklistTlsFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::Tls(BlocklistTls::new(time, fields))),
-                )))
-            }
-            EventKind::CryptocurrencyMiningPool => {
-                let Ok(fields) = bincode::deserialize::<CryptocurrencyMiningPoolFields>(v.as_ref())
-                else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::CryptocurrencyMiningPool(CryptocurrencyMiningPool::new(time, fields)),
-                )))
-            }
-            EventKind::DnsCovertChannel => {
-                let Ok(fields) = bincode::deserialize::<DnsEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::DnsCovertChannel(DnsCovertChannel::new(time, fields)),
-                )))
-            }
-            EventKind::DomainGenerationAlgorithm => {
-                let Ok(fields) = bincode::deserialize::<DgaFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::DomainGenerationAlgorithm(DomainGenerationAlgorithm::new(time, fields)),
-                )))
-            }
-            EventKind::ExternalDdos => {
-                let Ok(fields) = bincode::deserialize::<ExternalDdosFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::ExternalDdos(ExternalDdos::new(time, &fields)),
-                )))
-            }
-            EventKind::ExtraThreat => {
-                let Ok(fields) = bincode::deserialize::<ExtraThreat>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((key, Event::ExtraThreat(fields))))
-            }
-            EventKind::FtpBruteForce => {
-                let Ok(fields) = bincode::deserialize::<FtpBruteForceFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::FtpBruteForce(FtpBruteForce::new(time, &fields)),
-                )))
-            }
-            EventKind::FtpPlainText => {
-                let Ok(fields) = bincode::deserialize::<FtpEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::FtpPlainText(FtpPlainText::new(time, fields)),
-                )))
-            }
-            EventKind::HttpThreat => {
-                let Ok(fields) = bincode::deserialize::<HttpThreatFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::HttpThreat(HttpThreat::new(fields.time, fields)),
-                )))
-            }
-            EventKind::LdapBruteForce => {
-                let Ok(fields) = bincode::deserialize::<LdapBruteForceFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::LdapBruteForce(LdapBruteForce::new(time, &fields)),
-                )))
-            }
-            EventKind::LdapPlainText => {
-                let Ok(fields) = bincode::deserialize::<LdapEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::LdapPlainText(LdapPlainText::new(time, fields)),
-                )))
-            }
-            EventKind::LockyRansomware => {
-                let Ok(fields) = bincode::deserialize::<DnsEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::LockyRansomware(LockyRansomware::new(time, fields)),
-                )))
-            }
-            EventKind::MultiHostPortScan => {
-                let Ok(fields) = bincode::deserialize::<MultiHostPortScanFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::MultiHostPortScan(MultiHostPortScan::new(time, &fields)),
-                )))
-            }
-            EventKind::NetworkThreat => {
-                let Ok(fields) = bincode::deserialize::<NetworkThreat>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((key, Event::NetworkThreat(fields))))
-            }
-            EventKind::NonBrowser => {
-                let Ok(fields) = bincode::deserialize::<HttpEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((key, Event::NonBrowser(NonBrowser::new(time, &fields)))))
-            }
-            EventKind::PortScan => {
-                let Ok(fields) = bincode::deserialize::<PortScanFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((key, Event::PortScan(PortScan::new(time, &fields)))))
-            }
-            EventKind::RdpBruteForce => {
-                let Ok(fields) = bincode::deserialize::<RdpBruteForceFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::RdpBruteForce(RdpBruteForce::new(time, &fields)),
-                )))
-            }
-            EventKind::RepeatedHttpSessions => {
-                let Ok(fields) = bincode::deserialize::<RepeatedHttpSessionsFields>(v.as_ref())
-                else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::RepeatedHttpSessions(RepeatedHttpSessions::new(time, &fields)),
-                )))
-            }
-            EventKind::SuspiciousTlsTraffic => {
-                let Ok(fields) = bincode::deserialize::<BlocklistTlsFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::SuspiciousTlsTraffic(SuspiciousTlsTraffic::new(time, fields)),
-                )))
-            }
-            EventKind::UnusualDestinationPattern => {
-                let Ok(fields) =
-                    bincode::deserialize::<UnusualDestinationPatternFields>(v.as_ref())
-                else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::Blocklist(RecordType::UnusualDestinationPattern(
-                        UnusualDestinationPattern::new(time, fields),
-                    )),
-                )))
-            }
-            EventKind::TorConnection => {
-                let Ok(fields) = bincode::deserialize::<HttpEventFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::TorConnection(TorConnection::new(time, &fields)),
-                )))
-            }
-            EventKind::TorConnectionConn => {
-                let Ok(fields) = bincode::deserialize::<BlocklistConnFields>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((
-                    key,
-                    Event::TorConnectionConn(TorConnectionConn::new(time, fields)),
-                )))
-            }
-            EventKind::WindowsThreat => {
-                let Ok(fields) = bincode::deserialize::<WindowsThreat>(v.as_ref()) else {
-                    return Some(Err(InvalidEvent::Value(v)));
-                };
-                Some(Ok((key, Event::WindowsThreat(fields))))
-            }
+/// Decodes an event from its key and value in the event database.
+fn decode(k: Box<[u8]>, v: Box<[u8]>) -> Result<(i128, Event), InvalidEvent> {
+    let key: [u8; 16] = if let Ok(key) = k.as_ref().try_into() {
+        key
+    } else {
+        return Err(InvalidEvent::Key(k));
+    };
+    let key = i128::from_be_bytes(key);
+    let time = Utc.timestamp_nanos((key >> 64).try_into().expect("valid i64"));
+    let kind_num = (key & 0xffff_ffff_0000_0000) >> 32;
+    let Some(kind) = EventKind::from_i128(kind_num) else {
+        return Err(InvalidEvent::Key(k));
+    };
+    match kind {
+        EventKind::BlocklistBootp => {
+            let Ok(fields) = bincode::deserialize::<BlocklistBootpFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Bootp(BlocklistBootp::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistConn => {
+            let Ok(fields) = bincode::deserialize::<BlocklistConnFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Conn(BlocklistConn::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistDceRpc => {
+            let Ok(fields) = bincode::deserialize::<BlocklistDceRpcFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::DceRpc(BlocklistDceRpc::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistDhcp => {
+            let Ok(fields) = bincode::deserialize::<BlocklistDhcpFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Dhcp(BlocklistDhcp::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistDns => {
+            let Ok(fields) = bincode::deserialize::<BlocklistDnsFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Dns(BlocklistDns::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistFtp => {
+            let Ok(fields) = bincode::deserialize::<FtpEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Ftp(BlocklistFtp::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistHttp => {
+            let Ok(fields) = bincode::deserialize::<BlocklistHttpFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Http(BlocklistHttp::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistKerberos => {
+            let Ok(fields) = bincode::deserialize::<BlocklistKerberosFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Kerberos(BlocklistKerberos::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistLdap => {
+            let Ok(fields) = bincode::deserialize::<LdapEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Ldap(BlocklistLdap::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistMalformedDns => {
+            let Ok(fields) = bincode::deserialize::<BlocklistMalformedDnsFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::MalformedDns(BlocklistMalformedDns::new(
+                    time, fields,
+                ))),
+            ))
+        }
+        EventKind::BlocklistMqtt => {
+            let Ok(fields) = bincode::deserialize::<BlocklistMqttFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Mqtt(BlocklistMqtt::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistNfs => {
+            let Ok(fields) = bincode::deserialize::<BlocklistNfsFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Nfs(BlocklistNfs::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistNtlm => {
+            let Ok(fields) = bincode::deserialize::<BlocklistNtlmFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Ntlm(BlocklistNtlm::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistRadius => {
+            let Ok(fields) = bincode::deserialize::<BlocklistRadiusFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Radius(BlocklistRadius::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistRdp => {
+            let Ok(fields) = bincode::deserialize::<BlocklistRdpFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Rdp(BlocklistRdp::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistSmb => {
+            let Ok(fields) = bincode::deserialize::<BlocklistSmbFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Smb(BlocklistSmb::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistSmtp => {
+            let Ok(fields) = bincode::deserialize::<BlocklistSmtpFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Smtp(BlocklistSmtp::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistSsh => {
+            let Ok(fields) = bincode::deserialize::<BlocklistSshFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Ssh(BlocklistSsh::new(time, fields))),
+            ))
+        }
+        EventKind::BlocklistTls => {
+            let Ok(fields) = bincode::deserialize::<BlocklistTlsFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::Tls(BlocklistTls::new(time, fields))),
+            ))
+        }
+        EventKind::CryptocurrencyMiningPool => {
+            let Ok(fields) = bincode::deserialize::<CryptocurrencyMiningPoolFields>(v.as_ref())
+            else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::CryptocurrencyMiningPool(CryptocurrencyMiningPool::new(time, fields)),
+            ))
+        }
+        EventKind::DnsCovertChannel => {
+            let Ok(fields) = bincode::deserialize::<DnsEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::DnsCovertChannel(DnsCovertChannel::new(time, fields)),
+            ))
+        }
+        EventKind::DomainGenerationAlgorithm => {
+            let Ok(fields) = bincode::deserialize::<DgaFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::DomainGenerationAlgorithm(DomainGenerationAlgorithm::new(time, fields)),
+            ))
+        }
+        EventKind::ExternalDdos => {
+            let Ok(fields) = bincode::deserialize::<ExternalDdosFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::ExternalDdos(ExternalDdos::new(time, &fields))))
+        }
+        EventKind::ExtraThreat => {
+            let Ok(fields) = bincode::deserialize::<ExtraThreat>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::ExtraThreat(fields)))
+        }
+        EventKind::FtpBruteForce => {
+            let Ok(fields) = bincode::deserialize::<FtpBruteForceFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::FtpBruteForce(FtpBruteForce::new(time, &fields))))
+        }
+        EventKind::FtpPlainText => {
+            let Ok(fields) = bincode::deserialize::<FtpEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::FtpPlainText(FtpPlainText::new(time, fields))))
+        }
+        EventKind::HttpThreat => {
+            let Ok(fields) = bincode::deserialize::<HttpThreatFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::HttpThreat(HttpThreat::new(fields.time, fields))))
+        }
+        EventKind::LdapBruteForce => {
+            let Ok(fields) = bincode::deserialize::<LdapBruteForceFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::LdapBruteForce(LdapBruteForce::new(time, &fields)),
+            ))
+        }
+        EventKind::LdapPlainText => {
+            let Ok(fields) = bincode::deserialize::<LdapEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::LdapPlainText(LdapPlainText::new(time, fields))))
+        }
+        EventKind::LockyRansomware => {
+            let Ok(fields) = bincode::deserialize::<DnsEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::LockyRansomware(LockyRansomware::new(time, fields)),
+            ))
+        }
+        EventKind::MultiHostPortScan => {
+            let Ok(fields) = bincode::deserialize::<MultiHostPortScanFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::MultiHostPortScan(MultiHostPortScan::new(time, &fields)),
+            ))
+        }
+        EventKind::NetworkThreat => {
+            let Ok(fields) = bincode::deserialize::<NetworkThreat>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::NetworkThreat(fields)))
+        }
+        EventKind::NonBrowser => {
+            let Ok(fields) = bincode::deserialize::<HttpEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::NonBrowser(NonBrowser::new(time, &fields))))
+        }
+        EventKind::PortScan => {
+            let Ok(fields) = bincode::deserialize::<PortScanFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::PortScan(PortScan::new(time, &fields))))
+        }
+        EventKind::RdpBruteForce => {
+            let Ok(fields) = bincode::deserialize::<RdpBruteForceFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::RdpBruteForce(RdpBruteForce::new(time, &fields))))
+        }
+        EventKind::SshBruteForce => {
+            let Ok(fields) = bincode::deserialize::<SshBruteForceFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::SshBruteForce(SshBruteForce::new(time, fields))))
+        }
+        EventKind::Beaconing => {
+            let Ok(fields) = bincode::deserialize::<BeaconingFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::Beaconing(Beaconing::new(time, fields))))
+        }
+        EventKind::VolumeExfiltration => {
+            let Ok(fields) = bincode::deserialize::<VolumeExfiltrationFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::VolumeExfiltration(VolumeExfiltration::new(time, fields)),
+            ))
+        }
+        EventKind::Kerberoasting => {
+            let Ok(fields) = bincode::deserialize::<KerberoastingFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::Kerberoasting(Kerberoasting::new(time, fields))))
+        }
+        EventKind::AsRepRoasting => {
+            let Ok(fields) = bincode::deserialize::<AsRepRoastingFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::AsRepRoasting(AsRepRoasting::new(time, fields))))
+        }
+        EventKind::NtlmPasswordSpray => {
+            let Ok(fields) = bincode::deserialize::<NtlmPasswordSprayFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::NtlmPasswordSpray(NtlmPasswordSpray::new(time, fields)),
+            ))
+        }
+        EventKind::CustomEvent => {
+            let Ok(fields) = bincode::deserialize::<CustomEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::CustomEvent(CustomEvent::new(time, fields))))
+        }
+        EventKind::RepeatedHttpSessions => {
+            let Ok(fields) = bincode::deserialize::<RepeatedHttpSessionsFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::RepeatedHttpSessions(RepeatedHttpSessions::new(time, &fields)),
+            ))
+        }
+        EventKind::SuspiciousTlsTraffic => {
+            let Ok(fields) = bincode::deserialize::<BlocklistTlsFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::SuspiciousTlsTraffic(SuspiciousTlsTraffic::new(time, fields)),
+            ))
+        }
+        EventKind::UnusualDestinationPattern => {
+            let Ok(fields) = bincode::deserialize::<UnusualDestinationPatternFields>(v.as_ref())
+            else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::Blocklist(RecordType::UnusualDestinationPattern(
+                    UnusualDestinationPattern::new(time, fields),
+                )),
+            ))
+        }
+        EventKind::TorConnection => {
+            let Ok(fields) = bincode::deserialize::<HttpEventFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::TorConnection(TorConnection::new(time, &fields))))
+        }
+        EventKind::TorConnectionConn => {
+            let Ok(fields) = bincode::deserialize::<BlocklistConnFields>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((
+                key,
+                Event::TorConnectionConn(TorConnectionConn::new(time, fields)),
+            ))
+        }
+        EventKind::WindowsThreat => {
+            let Ok(fields) = bincode::deserialize::<WindowsThreat>(v.as_ref()) else {
+                return Err(InvalidEvent::Value(v));
+            };
+            Ok((key, Event::WindowsThreat(fields)))
         }
     }
 }
@@ -2928,45 +3916,49 @@ pub enum TrafficDirection {
     To,
 }
 
-fn eq_ip_country(locator: &ip2location::DB, addr: IpAddr, country: [u8; 2]) -> bool {
-    let country_code = crate::util::find_ip_country(locator, addr);
-    country_code.as_bytes() == country
-}
-
 #[cfg(test)]
 mod tests {
     use std::{
         collections::HashMap,
         net::{IpAddr, Ipv4Addr},
+        num::NonZeroU8,
         str::FromStr,
         sync::Arc,
     };
 
+    use attrievent::attribute::RawEventKind;
+    use bincode::Options;
     use chrono::{TimeZone, Utc};
 
     use crate::{
-        Store,
+        Agent, AgentKind, AgentStatus, AttrCmpKind, CorrelationKey, CorrelationPolicy,
+        CustomAttrSpec, CustomEventSchema, Customer, CustomerNetwork, HostNetworkGroup, LogParser,
+        LogTemplate, Network, PacketAttr, RuleTechniques, Sensor, SensorIngest, SeverityOverride,
+        SeverityScope, Store, ValueKind,
         event::{
-            BlocklistBootp, BlocklistBootpFields, BlocklistConn, BlocklistConnFields,
-            BlocklistDceRpc, BlocklistDceRpcFields, BlocklistDhcp, BlocklistDhcpFields,
-            BlocklistDns, BlocklistDnsFields, BlocklistFtp, BlocklistHttp, BlocklistHttpFields,
-            BlocklistKerberos, BlocklistKerberosFields, BlocklistLdap, BlocklistMalformedDns,
-            BlocklistMalformedDnsFields, BlocklistMqtt, BlocklistMqttFields, BlocklistNfs,
-            BlocklistNfsFields, BlocklistNtlm, BlocklistNtlmFields, BlocklistRadius,
+            Beaconing, BeaconingFields, BlocklistBootp, BlocklistBootpFields, BlocklistConn,
+            BlocklistConnFields, BlocklistDceRpc, BlocklistDceRpcFields, BlocklistDhcp,
+            BlocklistDhcpFields, BlocklistDns, BlocklistDnsFields, BlocklistFtp, BlocklistHttp,
+            BlocklistHttpFields, BlocklistKerberos, BlocklistKerberosFields, BlocklistLdap,
+            BlocklistMalformedDns, BlocklistMalformedDnsFields, BlocklistMqtt, BlocklistMqttFields,
+            BlocklistNfs, BlocklistNfsFields, BlocklistNtlm, BlocklistNtlmFields, BlocklistRadius,
             BlocklistRadiusFields, BlocklistRdp, BlocklistRdpFields, BlocklistSmb,
             BlocklistSmbFields, BlocklistSmtp, BlocklistSmtpFields, BlocklistSsh,
             BlocklistSshFields, BlocklistTls, BlocklistTlsFields, CryptocurrencyMiningPool,
-            CryptocurrencyMiningPoolFields, DgaFields, DnsCovertChannel, DnsEventFields,
-            DomainGenerationAlgorithm, Event, EventFilter, EventKind, EventMessage, ExternalDdos,
-            ExternalDdosFields, ExtraThreat, FtpBruteForce, FtpBruteForceFields, FtpEventFields,
-            FtpPlainText, HttpEventFields, HttpThreat, HttpThreatFields, LOCKY_RANSOMWARE,
-            LdapBruteForce, LdapBruteForceFields, LdapEventFields, LdapPlainText, LockyRansomware,
-            MultiHostPortScan, MultiHostPortScanFields, NetworkThreat, NonBrowser, PortScan,
-            PortScanFields, RdpBruteForce, RdpBruteForceFields, RecordType, RepeatedHttpSessions,
-            RepeatedHttpSessionsFields, SuspiciousTlsTraffic, TorConnection, TriageScore,
-            WindowsThreat,
+            CryptocurrencyMiningPoolFields, CustomEventFields, CustomValue, CustomValueKind,
+            DgaFields, DnsCovertChannel, DnsEventFields, DomainGenerationAlgorithm, Event,
+            EventFilter, EventKind, EventMessage, ExternalDdos, ExternalDdosFields, ExtraThreat,
+            FieldClass, FtpBruteForce, FtpBruteForceFields, FtpEventFields, FtpPlainText,
+            HttpEventFields, HttpThreat, HttpThreatFields, Kerberoasting, KerberoastingFields,
+            LOCKY_RANSOMWARE, LdapBruteForce, LdapBruteForceFields, LdapEventFields, LdapPlainText,
+            LockyRansomware, LogFields, MultiHostPortScan, MultiHostPortScanFields, NetworkThreat,
+            NetworkType, NonBrowser, NtlmPasswordSpray, NtlmPasswordSprayFields, PortScan,
+            PortScanFields, RdpBruteForce, RdpBruteForceFields, RecordType, RedactionAction,
+            RedactionPolicy, RepeatedHttpSessions, RepeatedHttpSessionsFields, SshBruteForce,
+            SshBruteForceFields, SuspiciousTlsTraffic, TorConnection, TriageScore,
+            VolumeExfiltration, VolumeExfiltrationFields, WindowsThreat,
         },
-        types::EventCategory,
+        types::{Endpoint, EventCategory},
     };
 
     fn example_message(kind: EventKind, category: EventCategory) -> EventMessage {
@@ -3034,6 +4026,200 @@ mod tests {
         assert!(iter.next().is_none());
     }
 
+    #[test]
+    fn event_db_put_correlates() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        store
+            .config_map()
+            .set_correlation_policy(&CorrelationPolicy {
+                keys: vec![CorrelationKey::SrcAddr],
+                window: 600,
+            })
+            .unwrap();
+        let db = store.events();
+
+        let first = db
+            .put(&example_message(
+                EventKind::DnsCovertChannel,
+                EventCategory::CommandAndControl,
+            ))
+            .unwrap();
+        let second = db
+            .put(&example_message(
+                EventKind::LockyRansomware,
+                EventCategory::Impact,
+            ))
+            .unwrap();
+
+        let incidents = store
+            .incident_map()
+            .incidents_of("src_addr=127.0.0.1")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].events, vec![first, second]);
+        assert_eq!(
+            incidents[0].kinds,
+            vec![EventKind::DnsCovertChannel, EventKind::LockyRansomware]
+        );
+    }
+
+    #[test]
+    fn event_db_caches_customers() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        store
+            .config_map()
+            .set_correlation_policy(&CorrelationPolicy {
+                keys: vec![CorrelationKey::Customer],
+                window: 600,
+            })
+            .unwrap();
+        let message = example_message(
+            EventKind::DnsCovertChannel,
+            EventCategory::CommandAndControl,
+        );
+        let db = store.events();
+        db.put(&message).unwrap();
+
+        let customer_id = store
+            .customer_map()
+            .put(Customer {
+                id: u32::MAX,
+                name: "customer".to_string(),
+                description: String::new(),
+                networks: vec![CustomerNetwork {
+                    name: "network".to_string(),
+                    description: String::new(),
+                    network_type: NetworkType::Intranet,
+                    network_group: HostNetworkGroup::new(
+                        vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
+                        vec![],
+                        vec![],
+                    ),
+                }],
+                creation_time: Utc::now(),
+            })
+            .unwrap();
+        let group = format!("customer={customer_id}");
+        // The customers were loaded before the new one was added.
+        db.put(&message).unwrap();
+        assert_eq!(store.incident_map().incidents_of(&group).count(), 0);
+
+        let db = store.events();
+        let key = db.put(&message).unwrap();
+        let incidents = store
+            .incident_map()
+            .incidents_of(&group)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].events, vec![key]);
+    }
+
+    #[test]
+    fn event_techniques() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let db = store.events();
+        let msg = example_message(
+            EventKind::DnsCovertChannel,
+            EventCategory::CommandAndControl,
+        );
+        db.put(&msg).unwrap();
+        let (_key, event) = db.iter_forward().next().unwrap().unwrap();
+        let rule_techniques = RuleTechniques::default();
+        assert_eq!(
+            event.techniques(&rule_techniques),
+            vec!["T1071.004", "T1048"]
+        );
+
+        let filter_of = |techniques: &[&str]| {
+            EventFilter::new(
+                None, None, None, None, None, None, None, None, None, None, None, None, None, None,
+            )
+            .with_techniques(
+                techniques.iter().map(ToString::to_string).collect(),
+                RuleTechniques::default(),
+            )
+        };
+        assert!(event.matches(None, &filter_of(&["T1071"])).unwrap().0);
+        assert!(event.matches(None, &filter_of(&["T1048"])).unwrap().0);
+        assert!(!event.matches(None, &filter_of(&["T1071.001"])).unwrap().0);
+        assert!(!event.matches(None, &filter_of(&["T107"])).unwrap().0);
+
+        let mut counter = HashMap::new();
+        event
+            .count_technique(&mut counter, None, &filter_of(&["T1048"]), &rule_techniques)
+            .unwrap();
+        assert_eq!(counter.len(), 2);
+        assert_eq!(counter.get("T1071.004"), Some(&1));
+        event
+            .count_technique(&mut counter, None, &filter_of(&["T1046"]), &rule_techniques)
+            .unwrap();
+        assert_eq!(counter.get("T1048"), Some(&1));
+    }
+
+    #[test]
+    fn severity_overrides() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let db = store.events();
+        let msg = example_message(
+            EventKind::DnsCovertChannel,
+            EventCategory::CommandAndControl,
+        );
+        db.put(&msg).unwrap();
+        let (_key, event) = db.iter_forward().next().unwrap().unwrap();
+        let very_high = NonZeroU8::new(5).unwrap();
+        store
+            .severity_override_map()
+            .set(&SeverityOverride {
+                kind: EventKind::DnsCovertChannel,
+                scope: SeverityScope::Sensor("collector1".to_string()),
+                level: very_high,
+            })
+            .unwrap();
+        let overrides = store.severity_override_map().load().unwrap();
+        assert_eq!(event.level(Some(&overrides)), very_high);
+        assert_eq!(event.level(None), event.detector_level());
+        assert_ne!(event.detector_level(), very_high);
+
+        let filter_of = |severity_overrides| EventFilter {
+            customers: None,
+            endpoints: None,
+            directions: None,
+            source: None,
+            destination: None,
+            countries: None,
+            categories: None,
+            levels: Some(vec![very_high]),
+            kinds: None,
+            learning_methods: None,
+            sensors: None,
+            confidence_min: None,
+            confidence_max: None,
+            triage_policies: None,
+            techniques: None,
+            severity_overrides,
+        };
+        assert!(!event.matches(None, &filter_of(None)).unwrap().0);
+        let filter = filter_of(Some(overrides));
+        assert!(event.matches(None, &filter).unwrap().0);
+        let mut counter = HashMap::new();
+        event.count_level(&mut counter, None, &filter).unwrap();
+        assert_eq!(counter.get(&very_high), Some(&1));
+    }
+
     #[test]
     fn event_message() {
         let db_dir = tempfile::tempdir().unwrap();
@@ -3062,6 +4248,8 @@ mod tests {
             confidence_min: Some(0.5),
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(event.kind(None, &filter).unwrap(), Some(LOCKY_RANSOMWARE));
         let mut counter = HashMap::new();
@@ -3089,6 +4277,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn event_db_put_enriches() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let network_id = store
+            .network_map()
+            .insert(Network::new(
+                "network".to_string(),
+                String::new(),
+                HostNetworkGroup::new(vec![IpAddr::V4(Ipv4Addr::LOCALHOST)], vec![], vec![]),
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+        let db = store.events();
+        let msg = example_message(EventKind::LockyRansomware, EventCategory::Impact);
+        let key = db.put(&msg).unwrap();
+
+        let enrichment = db.enrichment(key).unwrap().unwrap();
+        let src = enrichment.get(IpAddr::V4(Ipv4Addr::LOCALHOST)).unwrap();
+        assert_eq!(src.network_ids, vec![network_id]);
+        assert_eq!(src.country, None);
+
+        let (_key, event) = db.iter_forward().next().unwrap().unwrap();
+        let mut filter = EventFilter {
+            customers: None,
+            endpoints: None,
+            directions: None,
+            source: None,
+            destination: None,
+            countries: None,
+            categories: None,
+            levels: None,
+            kinds: None,
+            learning_methods: None,
+            sensors: None,
+            confidence_min: None,
+            confidence_max: None,
+            triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
+        };
+        let mut counter = HashMap::new();
+        event
+            .count_network(&mut counter, Some(&enrichment), &filter)
+            .unwrap();
+        assert_eq!(counter.get(&network_id), Some(&1));
+        let mut counter = HashMap::new();
+        event
+            .count_country(&mut counter, Some(&enrichment), &filter)
+            .unwrap();
+        assert_eq!(counter.get("ZZ"), Some(&1));
+
+        filter.endpoints = Some(vec![Endpoint {
+            direction: None,
+            network: HostNetworkGroup::default(),
+            network_id: Some(network_id),
+        }]);
+        assert!(event.matches(Some(&enrichment), &filter).unwrap().0);
+        assert!(!event.matches(None, &filter).unwrap().0);
+        filter.endpoints = None;
+        filter.countries = Some(vec![*b"US"]);
+        assert!(!event.matches(Some(&enrichment), &filter).unwrap().0);
+
+        // Networks added later are picked up by re-enrichment.
+        store
+            .network_map()
+            .insert(Network::new(
+                "other".to_string(),
+                String::new(),
+                HostNetworkGroup::new(
+                    vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2))],
+                    vec![],
+                    vec![],
+                ),
+                vec![],
+                vec![],
+            ))
+            .unwrap();
+        assert_eq!(db.reenrich().unwrap(), 1);
+        let enrichment = db.enrichment(key).unwrap().unwrap();
+        let dst = enrichment
+            .get(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)))
+            .unwrap();
+        assert_eq!(dst.network_ids.len(), 1);
+
+        // Rewriting an event into one that cannot be decoded removes its enrichment.
+        let invalid = EventMessage {
+            fields: vec![0xff],
+            ..msg
+        };
+        let stored = db.inner.get(key.to_be_bytes()).unwrap().unwrap();
+        db.update(
+            (&key.to_be_bytes(), stored.as_slice()),
+            (&key.to_be_bytes(), invalid.fields.as_slice()),
+        )
+        .unwrap();
+        assert_eq!(db.enrichment(key).unwrap(), None);
+
+        // An event that cannot be decoded is stored without enrichment.
+        let key = db.put(&invalid).unwrap();
+        assert_eq!(db.enrichment(key).unwrap(), None);
+        assert_eq!(db.iter_forward().filter(Result::is_ok).count(), 0);
+    }
+
     #[test]
     fn syslog_for_dga() {
         let fields = DgaFields {
@@ -3748,6 +5043,8 @@ mod tests {
             confidence_min: None,
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -3991,6 +5288,8 @@ mod tests {
             confidence_min: None,
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -4370,28 +5669,110 @@ mod tests {
     }
 
     #[test]
-    fn syslog_for_blocklist_ftp() {
-        let fields = ftpeventfields();
-
-        let message = EventMessage {
+    fn redact_message() {
+        let policy = RedactionPolicy {
+            username: RedactionAction::Hash,
+            password: RedactionAction::Mask,
+            hmac_key: b"key".to_vec(),
+            ..Default::default()
+        };
+        let mut message = EventMessage {
             time: Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap(),
             kind: EventKind::BlocklistFtp,
-            fields: bincode::serialize(&fields).expect("serializable"),
+            fields: bincode::serialize(&ftpeventfields()).expect("serializable"),
         };
 
-        let message = message.syslog_rfc5424();
-        assert!(message.is_ok());
-        let (_, _, syslog_message) = message.unwrap();
-        assert_eq!(
-            &syslog_message,
-            r#"time="1970-01-01T01:01:01+00:00" event_kind="BlocklistFtp" category="InitialAccess" sensor="collector1" src_addr="127.0.0.1" src_port="10000" dst_addr="127.0.0.2" dst_port="21" proto="6" start_time="1970-01-01T00:01:01+00:00" duration="0" orig_pkts="0" resp_pkts="0" orig_l2_bytes="0" resp_l2_bytes="0" user="user1" password="password" commands="ls:200:OK" confidence="1""#
-        );
-
-        let blocklist_ftp = Event::Blocklist(RecordType::Ftp(BlocklistFtp::new(
-            Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap(),
-            fields,
-        )))
-        .to_string();
+        let (_, _, syslog_message) = message.syslog_rfc5424_redacted(&policy).unwrap();
+        assert!(syslog_message.contains("password=\"********\""));
+        assert!(!syslog_message.contains("user1"));
+
+        message.redact(&policy).unwrap();
+        let fields: FtpEventFields = bincode::deserialize(&message.fields).unwrap();
+        assert_eq!(fields.password, "********");
+        assert_eq!(fields.user.len(), 64);
+        assert_eq!(fields.commands[0].file, "/etc/passwd");
+
+        let mut other = ftpeventfields();
+        other.password = "another".to_string();
+        let mut event = Event::FtpPlainText(FtpPlainText::new(message.time, other));
+        event.redact(&policy).unwrap();
+        let Event::FtpPlainText(event) = event else {
+            unreachable!()
+        };
+        assert_eq!(event.user, fields.user);
+        assert_eq!(event.password, "********");
+    }
+
+    #[test]
+    fn redact_ldap() {
+        let policy = RedactionPolicy {
+            username: RedactionAction::Drop,
+            password: RedactionAction::Mask,
+            ..Default::default()
+        };
+        let mut message = EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap(),
+            kind: EventKind::BlocklistLdap,
+            fields: bincode::serialize(&ldapeventfields()).expect("serializable"),
+        };
+        message.redact(&policy).unwrap();
+        let fields: LdapEventFields = bincode::deserialize(&message.fields).unwrap();
+        assert_eq!(fields.argument, ["********"]);
+        assert_eq!(fields.object, [""]);
+
+        let mut event = Event::LdapPlainText(LdapPlainText::new(message.time, ldapeventfields()));
+        event.redact(&policy).unwrap();
+        let Event::LdapPlainText(event) = event else {
+            unreachable!()
+        };
+        assert_eq!(event.argument, ["********"]);
+
+        let without_key = RedactionPolicy {
+            password: RedactionAction::Hash,
+            ..Default::default()
+        };
+        assert!(message.redact(&without_key).is_err());
+    }
+
+    #[test]
+    fn redact_message_without_sensitive_fields() {
+        let mut message = example_message(
+            EventKind::DnsCovertChannel,
+            EventCategory::CommandAndControl,
+        );
+        let fields = message.fields.clone();
+        message
+            .redact(&RedactionPolicy {
+                username: RedactionAction::Drop,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(message.fields, fields);
+    }
+
+    #[test]
+    fn syslog_for_blocklist_ftp() {
+        let fields = ftpeventfields();
+
+        let message = EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap(),
+            kind: EventKind::BlocklistFtp,
+            fields: bincode::serialize(&fields).expect("serializable"),
+        };
+
+        let message = message.syslog_rfc5424();
+        assert!(message.is_ok());
+        let (_, _, syslog_message) = message.unwrap();
+        assert_eq!(
+            &syslog_message,
+            r#"time="1970-01-01T01:01:01+00:00" event_kind="BlocklistFtp" category="InitialAccess" sensor="collector1" src_addr="127.0.0.1" src_port="10000" dst_addr="127.0.0.2" dst_port="21" proto="6" start_time="1970-01-01T00:01:01+00:00" duration="0" orig_pkts="0" resp_pkts="0" orig_l2_bytes="0" resp_l2_bytes="0" user="user1" password="password" commands="ls:200:OK" confidence="1""#
+        );
+
+        let blocklist_ftp = Event::Blocklist(RecordType::Ftp(BlocklistFtp::new(
+            Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap(),
+            fields,
+        )))
+        .to_string();
 
         assert_eq!(
             &blocklist_ftp,
@@ -4434,6 +5815,8 @@ mod tests {
             confidence_min: Some(0.5),
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -4773,6 +6156,8 @@ mod tests {
             confidence_min: Some(0.5),
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -4875,6 +6260,8 @@ mod tests {
             confidence_min: Some(0.5),
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -4923,6 +6310,7 @@ mod tests {
             attack_kind: "attack_kind".to_string(),
             confidence: 0.9,
             category: Some(EventCategory::Reconnaissance),
+            fields: None,
             triage_scores: None,
         };
 
@@ -4932,6 +6320,14 @@ mod tests {
             fields: bincode::serialize(&fields).expect("serializable"),
         };
 
+        let (_, _, redacted) = message
+            .syslog_rfc5424_redacted(&RedactionPolicy {
+                content: RedactionAction::Mask,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(redacted.contains(r#"content="********""#));
+
         let message = message.syslog_rfc5424();
         assert!(message.is_ok());
         let (_, _, syslog_message) = message.unwrap();
@@ -5244,6 +6640,8 @@ mod tests {
             confidence_min: Some(0.5),
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -5357,6 +6755,619 @@ mod tests {
         );
     }
 
+    #[test]
+    fn syslog_for_sshbruteforce() {
+        let fields = || SshBruteForceFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dst_addrs: vec![
+                IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)),
+                IpAddr::V4(Ipv4Addr::new(127, 0, 0, 3)),
+            ],
+            dst_port: 22,
+            proto: 6,
+            attempts: vec![30, 12],
+            start_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 1, 1)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            end_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 10, 2)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            client: "SSH-2.0-libssh_0.9.6".to_string(),
+            server: "SSH-2.0-OpenSSH_8.9".to_string(),
+            confidence: 0.7,
+            category: Some(EventCategory::CredentialAccess),
+        };
+
+        let message = EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap(),
+            kind: EventKind::SshBruteForce,
+            fields: bincode::serialize(&fields()).expect("serializable"),
+        };
+
+        let (_, _, syslog_message) = message.syslog_rfc5424().unwrap();
+        assert_eq!(
+            &syslog_message,
+            r#"time="1970-01-01T00:01:01+00:00" event_kind="SshBruteForce" category="CredentialAccess" sensor="collector1" src_addr="127.0.0.1" dst_addrs="127.0.0.2,127.0.0.3" dst_port="22" proto="6" attempts="30,12" start_time="1970-01-01T00:01:01+00:00" end_time="1970-01-01T00:10:02+00:00" client="SSH-2.0-libssh_0.9.6" server="SSH-2.0-OpenSSH_8.9" confidence="0.7""#
+        );
+
+        let ssh_brute_force = Event::SshBruteForce(SshBruteForce::new(
+            Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap(),
+            fields(),
+        ))
+        .to_string();
+        assert_eq!(
+            &ssh_brute_force,
+            r#"time="1970-01-01T00:01:01+00:00" event_kind="SshBruteForce" category="CredentialAccess" sensor="collector1" src_addr="127.0.0.1" dst_addrs="127.0.0.2,127.0.0.3" dst_port="22" proto="6" attempts="30,12" start_time="1970-01-01T00:01:01+00:00" end_time="1970-01-01T00:10:02+00:00" client="SSH-2.0-libssh_0.9.6" server="SSH-2.0-OpenSSH_8.9" triage_scores="""#
+        );
+
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let db = store.events();
+        db.put(&message).unwrap();
+        let (_key, event) = db.iter_forward().next().unwrap().unwrap();
+        let Event::SshBruteForce(event) = event else {
+            panic!("unexpected event kind");
+        };
+        assert_eq!(event.attempts, vec![30, 12]);
+        assert_eq!(event.client, "SSH-2.0-libssh_0.9.6");
+    }
+
+    #[test]
+    fn syslog_for_beaconing() {
+        let fields = || BeaconingFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+            dst_addr: IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)),
+            dst_port: 443,
+            proto: 6,
+            start_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 1, 1)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            end_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 1, 1, 1)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            count: 60,
+            mean_interval: 60.5,
+            jitter: 1.25,
+            bytes_per_conn: 512.0,
+            confidence: 0.9,
+            category: Some(EventCategory::CommandAndControl),
+        };
+
+        let message = EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap(),
+            kind: EventKind::Beaconing,
+            fields: bincode::serialize(&fields()).expect("serializable"),
+        };
+
+        let (_, _, syslog_message) = message.syslog_rfc5424().unwrap();
+        assert_eq!(
+            &syslog_message,
+            r#"time="1970-01-01T00:01:01+00:00" event_kind="Beaconing" category="CommandAndControl" sensor="collector1" src_addr="10.0.0.5" dst_addr="203.0.113.7" dst_port="443" proto="6" start_time="1970-01-01T00:01:01+00:00" end_time="1970-01-01T01:01:01+00:00" count="60" mean_interval="60.5" jitter="1.25" bytes_per_conn="512" confidence="0.9""#
+        );
+
+        let event = Event::Beaconing(Beaconing::new(
+            Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap(),
+            fields(),
+        ));
+        assert_eq!(
+            &event.to_string(),
+            r#"time="1970-01-01T00:01:01+00:00" event_kind="Beaconing" category="CommandAndControl" sensor="collector1" src_addr="10.0.0.5" dst_addr="203.0.113.7" dst_port="443" proto="6" start_time="1970-01-01T00:01:01+00:00" end_time="1970-01-01T01:01:01+00:00" count="60" mean_interval="60.5" jitter="1.25" bytes_per_conn="512" triage_scores="""#
+        );
+        assert_eq!(
+            event.primary_addrs(),
+            (
+                Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5))),
+                Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)))
+            )
+        );
+    }
+
+    #[test]
+    fn syslog_for_volume_exfiltration() {
+        let fields = || VolumeExfiltrationFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+            dst_addrs: vec![
+                IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)),
+                IpAddr::V4(Ipv4Addr::new(198, 51, 100, 2)),
+            ],
+            start_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            end_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 1, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            bytes_sent: 5_000_000_000,
+            expected_mean: 20_000_000.0,
+            std_deviation: 5_000_000.0,
+            z_score: 996.0,
+            protocols: vec!["https".to_string(), "dns".to_string()],
+            confidence: 0.95,
+            category: Some(EventCategory::Exfiltration),
+        };
+
+        let message = EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 1, 0, 0).unwrap(),
+            kind: EventKind::VolumeExfiltration,
+            fields: bincode::serialize(&fields()).expect("serializable"),
+        };
+
+        let (_, _, syslog_message) = message.syslog_rfc5424().unwrap();
+        assert_eq!(
+            &syslog_message,
+            r#"time="1970-01-01T01:00:00+00:00" event_kind="VolumeExfiltration" category="Exfiltration" sensor="collector1" src_addr="10.0.0.5" dst_addrs="203.0.113.7,198.51.100.2" start_time="1970-01-01T00:00:00+00:00" end_time="1970-01-01T01:00:00+00:00" bytes_sent="5000000000" expected_mean="20000000" std_deviation="5000000" z_score="996" protocols="https,dns" confidence="0.95""#
+        );
+
+        let event = Event::VolumeExfiltration(VolumeExfiltration::new(
+            Utc.with_ymd_and_hms(1970, 1, 1, 1, 0, 0).unwrap(),
+            fields(),
+        ));
+        assert_eq!(
+            &event.to_string(),
+            r#"time="1970-01-01T01:00:00+00:00" event_kind="VolumeExfiltration" category="Exfiltration" sensor="collector1" src_addr="10.0.0.5" dst_addrs="203.0.113.7,198.51.100.2" start_time="1970-01-01T00:00:00+00:00" end_time="1970-01-01T01:00:00+00:00" bytes_sent="5000000000" expected_mean="20000000" std_deviation="5000000" z_score="996" protocols="https,dns" triage_scores="""#
+        );
+        assert_eq!(event.level(None), NonZeroU8::new(4).unwrap());
+    }
+
+    #[test]
+    fn syslog_for_kerberoasting() {
+        let fields = || KerberoastingFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+            dst_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+            dst_port: 88,
+            proto: 17,
+            start_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            end_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 1, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            client_realm: "EXAMPLE.COM".to_string(),
+            client_name: vec!["user1".to_string()],
+            realm: "EXAMPLE.COM".to_string(),
+            service_names: vec!["MSSQLSvc/db1".to_string(), "HTTP/web1".to_string()],
+            request_count: 40,
+            rc4_request_count: 38,
+            confidence: 0.8,
+            category: Some(EventCategory::CredentialAccess),
+        };
+
+        let message = EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 0).unwrap(),
+            kind: EventKind::Kerberoasting,
+            fields: bincode::serialize(&fields()).expect("serializable"),
+        };
+
+        let (_, _, syslog_message) = message.syslog_rfc5424().unwrap();
+        assert_eq!(
+            &syslog_message,
+            r#"time="1970-01-01T00:01:00+00:00" event_kind="Kerberoasting" category="CredentialAccess" sensor="collector1" src_addr="10.0.0.5" dst_addr="10.0.0.1" dst_port="88" proto="17" start_time="1970-01-01T00:00:00+00:00" end_time="1970-01-01T00:01:00+00:00" client_realm="EXAMPLE.COM" client_name="user1" realm="EXAMPLE.COM" service_names="MSSQLSvc/db1,HTTP/web1" request_count="40" rc4_request_count="38" confidence="0.8""#
+        );
+
+        let event = Event::Kerberoasting(Kerberoasting::new(message.time, fields()));
+        assert_eq!(
+            &event.to_string(),
+            r#"time="1970-01-01T00:01:00+00:00" event_kind="Kerberoasting" category="CredentialAccess" sensor="collector1" src_addr="10.0.0.5" dst_addr="10.0.0.1" dst_port="88" proto="17" start_time="1970-01-01T00:00:00+00:00" end_time="1970-01-01T00:01:00+00:00" client_realm="EXAMPLE.COM" client_name="user1" realm="EXAMPLE.COM" service_names="MSSQLSvc/db1,HTTP/web1" request_count="40" rc4_request_count="38" triage_scores="""#
+        );
+        assert_eq!(event.techniques(&RuleTechniques::default()), ["T1558.003"]);
+
+        let policy = RedactionPolicy {
+            username: RedactionAction::Mask,
+            ..Default::default()
+        };
+        let mut message = message;
+        message.redact(&policy).unwrap();
+        let redacted: KerberoastingFields = bincode::deserialize(&message.fields).unwrap();
+        assert_eq!(redacted.client_name, ["********"]);
+        let mut event = event;
+        event.redact(&policy).unwrap();
+        let Event::Kerberoasting(event) = event else {
+            unreachable!()
+        };
+        assert_eq!(event.client_name, ["********"]);
+    }
+
+    #[test]
+    fn syslog_for_ntlm_password_spray() {
+        let fields = || NtlmPasswordSprayFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+            dst_addrs: vec![
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+            ],
+            dst_port: 445,
+            proto: 6,
+            start_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 0, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            end_time: Utc
+                .with_ymd_and_hms(1970, 1, 1, 0, 10, 0)
+                .unwrap()
+                .timestamp_nanos_opt()
+                .unwrap(),
+            protocol: "smb".to_string(),
+            domainname: "EXAMPLE".to_string(),
+            usernames: vec!["alice".to_string(), "bob".to_string()],
+            attempts: 20,
+            failures: 19,
+            confidence: 0.7,
+            category: Some(EventCategory::CredentialAccess),
+        };
+
+        let mut message = EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 0, 10, 0).unwrap(),
+            kind: EventKind::NtlmPasswordSpray,
+            fields: bincode::serialize(&fields()).expect("serializable"),
+        };
+
+        let (_, _, syslog_message) = message.syslog_rfc5424().unwrap();
+        assert_eq!(
+            &syslog_message,
+            r#"time="1970-01-01T00:10:00+00:00" event_kind="NtlmPasswordSpray" category="CredentialAccess" sensor="collector1" src_addr="10.0.0.5" dst_addrs="10.0.0.1,10.0.0.2" dst_port="445" proto="6" start_time="1970-01-01T00:00:00+00:00" end_time="1970-01-01T00:10:00+00:00" protocol="smb" domainname="EXAMPLE" usernames="alice,bob" attempts="20" failures="19" confidence="0.7""#
+        );
+
+        let event = Event::NtlmPasswordSpray(NtlmPasswordSpray::new(message.time, fields()));
+        assert_eq!(
+            &event.to_string(),
+            r#"time="1970-01-01T00:10:00+00:00" event_kind="NtlmPasswordSpray" category="CredentialAccess" sensor="collector1" src_addr="10.0.0.5" dst_addrs="10.0.0.1,10.0.0.2" dst_port="445" proto="6" start_time="1970-01-01T00:00:00+00:00" end_time="1970-01-01T00:10:00+00:00" protocol="smb" domainname="EXAMPLE" usernames="alice,bob" attempts="20" failures="19" triage_scores="""#
+        );
+
+        message
+            .redact(&RedactionPolicy {
+                username: RedactionAction::Mask,
+                ..Default::default()
+            })
+            .unwrap();
+        let fields: NtlmPasswordSprayFields = bincode::deserialize(&message.fields).unwrap();
+        assert_eq!(fields.usernames, ["********", "********"]);
+    }
+
+    #[test]
+    fn custom_event() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        store
+            .custom_event_schema_map()
+            .register(&CustomEventSchema {
+                name: "partner detector".to_string(),
+                version: 1,
+                description: String::new(),
+                attributes: vec![
+                    CustomAttrSpec {
+                        name: "peer".to_string(),
+                        kind: CustomValueKind::Addr,
+                        description: String::new(),
+                    },
+                    CustomAttrSpec {
+                        name: "rule".to_string(),
+                        kind: CustomValueKind::String,
+                        description: String::new(),
+                    },
+                ],
+            })
+            .unwrap();
+
+        let fields = |attributes| CustomEventFields {
+            sensor: "collector1".to_string(),
+            schema: "partner detector".to_string(),
+            schema_version: 1,
+            src_addrs: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
+            src_port: 10000,
+            dst_addrs: vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2))],
+            dst_port: 443,
+            proto: 6,
+            level: NonZeroU8::new(4).unwrap(),
+            attributes,
+            confidence: 0.6,
+            category: Some(EventCategory::Execution),
+        };
+        let message = |attributes| EventMessage {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap(),
+            kind: EventKind::CustomEvent,
+            fields: bincode::serialize(&fields(attributes)).expect("serializable"),
+        };
+
+        let db = store.events();
+        let invalid = message(vec![("rule".to_string(), CustomValue::UInteger(7))]);
+        assert!(db.put(&invalid).is_err());
+
+        let valid = message(vec![
+            (
+                "peer".to_string(),
+                CustomValue::Addr(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+            ),
+            ("rule".to_string(), CustomValue::String("r1".to_string())),
+        ]);
+        let (_, _, syslog_message) = valid.syslog_rfc5424().unwrap();
+        assert_eq!(
+            &syslog_message,
+            r#"time="1970-01-01T00:01:01+00:00" event_kind="CustomEvent" category="Execution" sensor="collector1" schema="partner detector" schema_version="1" src_addrs="127.0.0.1" src_port="10000" dst_addrs="127.0.0.2" dst_port="443" proto="6" level="4" peer="10.0.0.1" rule="r1" confidence="0.6""#
+        );
+
+        db.put(&valid).unwrap();
+        let (_key, event) = db.iter_forward().next().unwrap().unwrap();
+        assert_eq!(
+            &event.to_string(),
+            r#"time="1970-01-01T00:01:01+00:00" event_kind="CustomEvent" category="Execution" sensor="collector1" schema="partner detector" schema_version="1" src_addrs="127.0.0.1" src_port="10000" dst_addrs="127.0.0.2" dst_port="443" proto="6" level="4" peer="10.0.0.1" rule="r1" triage_scores="""#
+        );
+        assert_eq!(event.level(None), NonZeroU8::new(4).unwrap());
+
+        let filter = EventFilter {
+            customers: None,
+            endpoints: None,
+            directions: None,
+            source: None,
+            destination: None,
+            countries: None,
+            categories: None,
+            levels: None,
+            kinds: Some(vec!["partner detector".to_string()]),
+            learning_methods: None,
+            sensors: None,
+            confidence_min: None,
+            confidence_max: None,
+            triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
+        };
+        let mut counter = HashMap::new();
+        event.count_kind(&mut counter, None, &filter).unwrap();
+        assert_eq!(counter.get("partner detector"), Some(&1));
+
+        let packet_attr = PacketAttr {
+            raw_event_kind: RawEventKind::Conn,
+            attr_name: "partner detector.rule".to_string(),
+            value_kind: ValueKind::String,
+            cmp_kind: AttrCmpKind::Equal,
+            first_value: bincode::DefaultOptions::new().serialize(&"r1").unwrap(),
+            second_value: None,
+            weight: Some(0.4),
+        };
+        let score = event.as_match().score_by_attr(&[packet_attr.clone()]);
+        assert!((score - 0.4).abs() < f64::EPSILON);
+        let mut other_schema = packet_attr.clone();
+        other_schema.attr_name = "other detector.rule".to_string();
+        let mut unqualified = packet_attr;
+        unqualified.attr_name = "rule".to_string();
+        let score = event.as_match().score_by_attr(&[other_schema, unqualified]);
+        assert!(score.abs() < f64::EPSILON);
+
+        let policy = RedactionPolicy {
+            password: RedactionAction::Mask,
+            custom_attributes: HashMap::from([(
+                "partner detector.rule".to_string(),
+                FieldClass::Password,
+            )]),
+            ..Default::default()
+        };
+        let (_, _, syslog_message) = valid.syslog_rfc5424_redacted(&policy).unwrap();
+        assert!(syslog_message.contains(r#"peer="10.0.0.1" rule="********""#));
+        let mut event = event;
+        event.redact(&policy).unwrap();
+        let Event::CustomEvent(event) = event else {
+            unreachable!()
+        };
+        assert_eq!(
+            event.attribute("rule"),
+            Some(&CustomValue::String("********".to_string()))
+        );
+        let mut other_schema = fields(vec![(
+            "rule".to_string(),
+            CustomValue::String("r1".to_string()),
+        )]);
+        other_schema.schema = "other detector".to_string();
+        let mut other_schema = EventMessage {
+            fields: bincode::serialize(&other_schema).expect("serializable"),
+            ..valid
+        };
+        let unredacted = other_schema.fields.clone();
+        other_schema.redact(&policy).unwrap();
+        assert_eq!(other_schema.fields, unredacted);
+    }
+
+    #[test]
+    fn put_idempotent() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let db = store.events();
+
+        let message = example_message(
+            EventKind::DnsCovertChannel,
+            EventCategory::CommandAndControl,
+        );
+        let first = db.put_idempotent(&message, b"collector1:1").unwrap();
+        assert_eq!(db.put_idempotent(&message, b"collector1:1").unwrap(), first);
+        assert_eq!(db.iter_forward().count(), 1);
+
+        let second = db.put_idempotent(&message, b"collector1:2").unwrap();
+        assert_ne!(second, first);
+        let hash = message.content_hash();
+        let third = db.put_idempotent(&message, &hash).unwrap();
+        assert_eq!(db.put_idempotent(&message, &hash).unwrap(), third);
+        assert_eq!(db.iter_forward().count(), 3);
+        assert!(db.put_idempotent(&message, b"").is_err());
+
+        let dedup = store.event_dedup_map();
+        assert_eq!(dedup.count().unwrap(), 3);
+        assert_eq!(
+            dedup.get(b"collector1:1").unwrap().map(|entry| entry.event),
+            Some(first)
+        );
+    }
+
+    #[test]
+    fn put_records_sensor_ingest() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let db = store.events();
+
+        let message = example_message(
+            EventKind::DnsCovertChannel,
+            EventCategory::CommandAndControl,
+        );
+        // Events of a sensor that is not registered are not counted.
+        db.put(&message).unwrap();
+        let ingest = store.sensor_ingest_map();
+        assert!(ingest.last_seen("collector1").unwrap().is_none());
+
+        let customer_id = store
+            .customer_map()
+            .put(Customer {
+                id: u32::MAX,
+                name: "customer".to_string(),
+                description: String::new(),
+                networks: vec![],
+                creation_time: Utc::now(),
+            })
+            .unwrap();
+        let agent = Agent::new(
+            1,
+            "piglet".to_string(),
+            AgentKind::Sensor,
+            AgentStatus::Enabled,
+            None,
+            None,
+        )
+        .unwrap();
+        store.agents_map().insert(&agent).unwrap();
+        store
+            .sensor_map()
+            .register(&Sensor {
+                name: "collector1".to_string(),
+                node: 1,
+                agent_key: "piglet".to_string(),
+                customer_id,
+            })
+            .unwrap();
+
+        db.put(&message).unwrap();
+        db.put_idempotent(&message, b"collector1:1").unwrap();
+        db.put_idempotent(&message, b"collector1:1").unwrap();
+        let message = example_message(EventKind::LockyRansomware, EventCategory::Impact);
+        db.put(&message).unwrap();
+
+        let hours = ingest
+            .ingest_of("collector1")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        let total: u64 = hours.iter().map(SensorIngest::total).sum();
+        assert_eq!(total, 3);
+        assert!(ingest.last_seen("collector1").unwrap().is_some());
+        assert!(ingest.last_seen("collector2").unwrap().is_none());
+    }
+
+    #[test]
+    fn extra_threat_fields_extracted_on_put() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        store
+            .log_template_map()
+            .set(&LogTemplate {
+                service: "firewall".to_string(),
+                parser: LogParser::KeyValue {
+                    pair_delimiter: ' ',
+                    kv_delimiter: '=',
+                },
+                aliases: vec![("src".to_string(), "src_addr".to_string())],
+            })
+            .unwrap();
+
+        let threat = |service: &str| ExtraThreat {
+            time: Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap(),
+            sensor: "collector1".to_string(),
+            service: service.to_string(),
+            content: "src=10.0.0.1 dst_addr=10.0.0.2 dst_port=22 action=deny".to_string(),
+            db_name: "db_name".to_string(),
+            rule_id: 1,
+            matched_to: "matched_to".to_string(),
+            cluster_id: None,
+            attack_kind: "attack_kind".to_string(),
+            confidence: 0.9,
+            category: Some(EventCategory::Reconnaissance),
+            fields: None,
+            triage_scores: None,
+        };
+        let db = store.events();
+        for service in ["firewall", "unknown"] {
+            let message = EventMessage {
+                time: Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap(),
+                kind: EventKind::ExtraThreat,
+                fields: bincode::serialize(&threat(service)).expect("serializable"),
+            };
+            db.put(&message).unwrap();
+        }
+
+        let events: Vec<_> = db.iter_forward().map(|event| event.unwrap().1).collect();
+        assert_eq!(events.len(), 2);
+        let mut extracted = 0;
+        for event in &events {
+            let Event::ExtraThreat(threat) = event else {
+                panic!("unexpected event");
+            };
+            if threat.service == "firewall" {
+                let fields = threat.fields.as_ref().unwrap();
+                assert_eq!(fields.dst_port, Some(22));
+                assert_eq!(fields.action.as_deref(), Some("deny"));
+                assert_eq!(
+                    event.as_match().src_addrs(),
+                    &[IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))]
+                );
+                assert_eq!(event.as_match().dst_port(), 22);
+                extracted += 1;
+            } else {
+                assert!(threat.fields.is_none());
+                assert_eq!(threat.src_addr(), None);
+            }
+        }
+        assert_eq!(extracted, 1);
+
+        let mut threat = threat("firewall");
+        threat.fields = Some(LogFields {
+            user: Some("alice".to_string()),
+            ..LogFields::default()
+        });
+        let mut event = Event::ExtraThreat(threat);
+        event
+            .redact(&RedactionPolicy {
+                username: RedactionAction::Mask,
+                ..Default::default()
+            })
+            .unwrap();
+        let Event::ExtraThreat(threat) = event else {
+            panic!("unexpected event");
+        };
+        let user = threat.fields.and_then(|fields| fields.user);
+        assert_eq!(user.as_deref(), Some("********"));
+        assert!(threat.content.starts_with("src="));
+    }
+
     #[test]
     fn syslog_for_blocklist_rdp() {
         let fields = BlocklistRdpFields {
@@ -5817,6 +7828,8 @@ mod tests {
             confidence_min: Some(0.5),
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -5976,6 +7989,8 @@ mod tests {
             confidence_min: Some(0.5),
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         };
         assert_eq!(
             event.address_pair(None, &filter).unwrap(),
@@ -0,0 +1,374 @@
+//! Archival of events to Arrow IPC and Parquet files.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use arrow::{
+    array::{Array, ArrayRef, Int64Array},
+    datatypes::{DataType, Field, FieldRef, Schema},
+    ipc::{reader::FileReader, writer::FileWriter},
+    record_batch::RecordBatch,
+};
+use chrono::{DateTime, Utc};
+use num_traits::FromPrimitive;
+use parquet::arrow::{ArrowWriter, arrow_reader::ParquetRecordBatchReaderBuilder};
+use rocksdb::{Direction, IteratorMode};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_arrow::schema::{SchemaLike, TracingOptions};
+
+use super::{EventDb, EventKind, EventMessage, FieldsVisitor, ndjson::BATCH_SIZE};
+
+/// The column holding the time of the events, in nanoseconds since the Unix epoch.
+const EVENT_TIME_COLUMN: &str = "event_time";
+/// The schema metadata key holding the name of the event kind.
+const EVENT_KIND_KEY: &str = "event_kind";
+
+/// The file format of event archives.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArchiveFormat {
+    ArrowIpc,
+    Parquet,
+}
+
+impl ArchiveFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::ArrowIpc => "arrow",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// A file written by `EventDb::export_archive`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArchiveFile {
+    pub kind: EventKind,
+    pub path: PathBuf,
+    /// The number of events in the file.
+    pub events: usize,
+}
+
+/// Converts the stored fields of events into a record batch with a column for each field.
+struct ToBatch<'a> {
+    values: &'a [Vec<u8>],
+}
+
+impl FieldsVisitor for ToBatch<'_> {
+    type Output = Result<RecordBatch>;
+
+    fn visit<T: Serialize + DeserializeOwned>(self) -> Self::Output {
+        let records = self
+            .values
+            .iter()
+            .map(|value| bincode::deserialize::<T>(value))
+            .collect::<Result<Vec<_>, _>>()
+            .context("invalid event fields")?;
+        let options = TracingOptions::default()
+            .allow_null_fields(true)
+            .enums_without_data_as_strings(true);
+        let fields = Vec::<FieldRef>::from_type::<T>(options)?;
+        Ok(serde_arrow::to_record_batch(&fields, &records)?)
+    }
+}
+
+/// Converts a record batch back into the stored fields of events.
+struct FromBatch<'a> {
+    batch: &'a RecordBatch,
+}
+
+impl FieldsVisitor for FromBatch<'_> {
+    type Output = Result<Vec<Vec<u8>>>;
+
+    fn visit<T: Serialize + DeserializeOwned>(self) -> Self::Output {
+        let records: Vec<T> = serde_arrow::from_record_batch(self.batch)?;
+        records
+            .iter()
+            .map(|record| bincode::serialize(record).context("cannot serialize event fields"))
+            .collect()
+    }
+}
+
+impl EventDb<'_> {
+    /// Writes the events from `start` until `end` to `dir`, one file per event kind named after
+    /// the kind, e.g., `PortScan.parquet`. Existing files are overwritten.
+    ///
+    /// Each file has a column for the time of the events, `event_time`, followed by a column for
+    /// each field of the fields struct of the kind. The events of a kind are held in memory until
+    /// they are written, so the time range should be sized accordingly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a stored event is invalid, or the database or a file operation fails.
+    pub fn export_archive(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        dir: &Path,
+        format: ArchiveFormat,
+    ) -> Result<Vec<ArchiveFile>> {
+        let from = i128::from(start.timestamp_nanos_opt().unwrap_or(i64::MIN).max(0)) << 64;
+        let end = end.timestamp_nanos_opt().unwrap_or(i64::MAX);
+        let mut kinds: Vec<(EventKind, Vec<i64>, Vec<Vec<u8>>)> = Vec::new();
+        let iter = self
+            .inner
+            .iterator(IteratorMode::From(&from.to_be_bytes(), Direction::Forward));
+        for item in iter {
+            let (key, value) = item.context("cannot read from event database")?;
+            let Ok(key) = <[u8; 16]>::try_from(key.as_ref()) else {
+                continue;
+            };
+            let key = i128::from_be_bytes(key);
+            let time = i64::try_from(key >> 64).expect("valid i64");
+            if time >= end {
+                break;
+            }
+            let Some(kind) = EventKind::from_i128((key & 0xffff_ffff_0000_0000) >> 32) else {
+                continue;
+            };
+            if let Some((_, times, values)) = kinds.iter_mut().find(|(k, _, _)| *k == kind) {
+                times.push(time);
+                values.push(value.to_vec());
+            } else {
+                kinds.push((kind, vec![time], vec![value.to_vec()]));
+            }
+        }
+
+        let mut files = Vec::with_capacity(kinds.len());
+        for (kind, times, values) in kinds {
+            let fields = kind
+                .visit_fields(ToBatch { values: &values })
+                .with_context(|| format!("cannot convert {kind:?} events"))?;
+            let mut schema_fields = vec![Arc::new(Field::new(
+                EVENT_TIME_COLUMN,
+                DataType::Int64,
+                false,
+            ))];
+            schema_fields.extend(fields.schema().fields().iter().cloned());
+            let schema = Schema::new(schema_fields).with_metadata(HashMap::from([(
+                EVENT_KIND_KEY.to_string(),
+                format!("{kind:?}"),
+            )]));
+            let mut columns: Vec<ArrayRef> = vec![Arc::new(Int64Array::from(times))];
+            columns.extend(fields.columns().iter().cloned());
+            let batch = RecordBatch::try_new(Arc::new(schema), columns)?;
+
+            let path = dir.join(format!("{kind:?}.{}", format.extension()));
+            let file =
+                File::create(&path).with_context(|| format!("cannot create {}", path.display()))?;
+            match format {
+                ArchiveFormat::ArrowIpc => {
+                    let mut writer = FileWriter::try_new(file, &batch.schema())?;
+                    writer.write(&batch)?;
+                    writer.finish()?;
+                }
+                ArchiveFormat::Parquet => {
+                    let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+                    writer.write(&batch)?;
+                    writer.close()?;
+                }
+            }
+            files.push(ArchiveFile {
+                kind,
+                path,
+                events: batch.num_rows(),
+            });
+        }
+        Ok(files)
+    }
+
+    /// Stores the events in a file written by `export_archive`. Returns the number of events
+    /// stored.
+    ///
+    /// Each event is checked as `put` does, and stored in batches with a key from its original
+    /// time along with its enrichment. As with `import_ndjson`, the events are neither counted in the ingest statistics of
+    /// their sensors nor correlated into incidents, since they are not new.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not an event archive, an event is
+    /// invalid, or a database operation fails. The batches stored before the error are kept.
+    pub fn import_archive(&self, path: &Path, format: ArchiveFormat) -> Result<usize> {
+        let file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+        let batches = match format {
+            ArchiveFormat::ArrowIpc => {
+                FileReader::try_new(file, None)?.collect::<Result<Vec<_>, _>>()?
+            }
+            ArchiveFormat::Parquet => ParquetRecordBatchReaderBuilder::try_new(file)?
+                .build()?
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        let mut count = 0;
+        let mut events = Vec::with_capacity(BATCH_SIZE);
+        for batch in batches {
+            let schema = batch.schema();
+            let kind = schema
+                .metadata()
+                .get(EVENT_KIND_KEY)
+                .and_then(|name| EventKind::from_name(name))
+                .context("not an event archive")?;
+            let times = batch
+                .column_by_name(EVENT_TIME_COLUMN)
+                .and_then(|column| column.as_any().downcast_ref::<Int64Array>())
+                .filter(|column| column.null_count() == 0)
+                .context("invalid event times")?;
+            let fields = batch.project(&(1..batch.num_columns()).collect::<Vec<_>>())?;
+            let values = kind
+                .visit_fields(FromBatch { batch: &fields })
+                .with_context(|| format!("cannot convert {kind:?} events"))?;
+            for (time, fields) in times.values().iter().zip(values) {
+                let message = EventMessage {
+                    time: DateTime::from_timestamp_nanos(*time),
+                    kind,
+                    fields,
+                };
+                let value = self.prepare(&message)?.into_owned();
+                events.push((Self::base_key(&message)?, value));
+                if events.len() == BATCH_SIZE {
+                    count += self.put_batch(&events)?;
+                    events.clear();
+                }
+            }
+        }
+        count += self.put_batch(&events)?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        sync::Arc,
+    };
+
+    use chrono::{TimeDelta, TimeZone, Utc};
+
+    use super::ArchiveFormat;
+    use crate::{
+        EventKind, EventMessage, Store,
+        event::{DnsEventFields, PortScanFields},
+        types::EventCategory,
+    };
+
+    fn messages() -> Vec<EventMessage> {
+        let time = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let dns = DnsEventFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+            src_port: 10000,
+            dst_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+            dst_port: 53,
+            proto: 17,
+            start_time: time.timestamp_nanos_opt().unwrap(),
+            duration: 0,
+            orig_pkts: 0,
+            resp_pkts: 0,
+            orig_l2_bytes: 0,
+            resp_l2_bytes: 0,
+            query: "foo.com".to_string(),
+            answer: vec!["1.1.1.1".to_string()],
+            trans_id: 1,
+            rtt: 1,
+            qclass: 0,
+            qtype: 0,
+            rcode: 0,
+            aa_flag: false,
+            tc_flag: false,
+            rd_flag: false,
+            ra_flag: false,
+            ttl: vec![1; 5],
+            confidence: 0.8,
+            category: Some(EventCategory::CommandAndControl),
+        };
+        let port_scan = PortScanFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+            dst_addr: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3)),
+            dst_ports: vec![22, 80, 443],
+            start_time: time.timestamp_nanos_opt().unwrap(),
+            end_time: time.timestamp_nanos_opt().unwrap(),
+            proto: 6,
+            confidence: 0.3,
+            category: None,
+        };
+        vec![
+            EventMessage {
+                time,
+                kind: EventKind::DnsCovertChannel,
+                fields: bincode::serialize(&dns).unwrap(),
+            },
+            EventMessage {
+                time: time + TimeDelta::seconds(1),
+                kind: EventKind::PortScan,
+                fields: bincode::serialize(&port_scan).unwrap(),
+            },
+            EventMessage {
+                time: time + TimeDelta::seconds(2),
+                kind: EventKind::DnsCovertChannel,
+                fields: bincode::serialize(&dns).unwrap(),
+            },
+            EventMessage {
+                time: time + TimeDelta::days(1),
+                kind: EventKind::PortScan,
+                fields: bincode::serialize(&port_scan).unwrap(),
+            },
+        ]
+    }
+
+    #[test]
+    fn export_and_import() {
+        for format in [ArchiveFormat::ArrowIpc, ArchiveFormat::Parquet] {
+            let source = setup_store();
+            for message in messages() {
+                source.events().put(&message).unwrap();
+            }
+            let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+            let dir = tempfile::tempdir().unwrap();
+            let files = source
+                .events()
+                .export_archive(start, start + TimeDelta::hours(1), dir.path(), format)
+                .unwrap();
+            assert_eq!(files.len(), 2);
+            assert_eq!(files[0].kind, EventKind::DnsCovertChannel);
+            assert_eq!(files[0].events, 2);
+            assert_eq!(files[1].kind, EventKind::PortScan);
+            assert_eq!(files[1].events, 1);
+
+            let target = setup_store();
+            for file in &files {
+                assert_eq!(
+                    target.events().import_archive(&file.path, format).unwrap(),
+                    file.events
+                );
+            }
+            let expected: Vec<_> = source
+                .events()
+                .iter_forward()
+                .take(3)
+                .map(|event| event.unwrap().1.to_string())
+                .collect();
+            let imported: Vec<_> = target
+                .events()
+                .iter_forward()
+                .map(|event| event.unwrap().1.to_string())
+                .collect();
+            assert_eq!(imported, expected);
+            assert_eq!(
+                target.sensor_ingest_map().last_seen("collector1").unwrap(),
+                None
+            );
+        }
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
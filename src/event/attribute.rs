@@ -0,0 +1,618 @@
+//! The attributes of each event kind that triage rules can refer to.
+
+use attrievent::attribute::{
+    BootpAttr, ConnAttr, DhcpAttr, DnsAttr, FtpAttr, HttpAttr, KerberosAttr, LdapAttr, LogAttr,
+    MqttAttr, NetworkAttr, NfsAttr, NtlmAttr, RawEventAttrKind, RdpAttr, SmbAttr, SmtpAttr,
+    SshAttr, TlsAttr, WindowAttr,
+};
+
+use super::EventKind;
+use crate::ValueKind;
+
+/// An attribute of an event kind that a `PacketAttr` triage rule can refer to.
+#[derive(Clone, Copy, Debug)]
+pub struct EventAttr {
+    pub kind: RawEventAttrKind,
+    pub value_kind: ValueKind,
+    /// Whether the attribute has a list of values. A rule on such an attribute matches if any of
+    /// the values matches, or if all of them match for `NotEqual` and `NotContain`.
+    pub multi_valued: bool,
+}
+
+impl EventAttr {
+    /// Returns whether a rule with the given value type can be applied to the attribute.
+    #[must_use]
+    pub fn accepts(&self, value_kind: ValueKind) -> bool {
+        // Raw bytes can be compared with either bytes or a string.
+        self.value_kind == value_kind
+            || (self.value_kind == ValueKind::Vector && value_kind == ValueKind::String)
+    }
+}
+
+/// Builds a list of attributes of a protocol from `name: ValueKind` pairs. The value type of a
+/// multi-valued attribute is enclosed in brackets.
+macro_rules! attrs {
+    ($proto:ident, $attr:ident { $($name:ident: $value_kind:tt),* $(,)? }) => {
+        &[$(attr!($proto, $attr, $name, $value_kind)),*]
+    };
+}
+
+macro_rules! attr {
+    ($proto:ident, $attr:ident, $name:ident, [$value_kind:ident]) => {
+        EventAttr {
+            kind: RawEventAttrKind::$proto($attr::$name),
+            value_kind: ValueKind::$value_kind,
+            multi_valued: true,
+        }
+    };
+    ($proto:ident, $attr:ident, $name:ident, $value_kind:ident) => {
+        EventAttr {
+            kind: RawEventAttrKind::$proto($attr::$name),
+            value_kind: ValueKind::$value_kind,
+            multi_valued: false,
+        }
+    };
+}
+
+const BOOTP: &[EventAttr] = attrs!(
+    Bootp,
+    BootpAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Op: UInteger,
+        Htype: UInteger,
+        Hops: UInteger,
+        Xid: UInteger,
+        CiAddr: IpAddr,
+        YiAddr: IpAddr,
+        SiAddr: IpAddr,
+        GiAddr: IpAddr,
+        ChAddr: Vector,
+        SName: String,
+        File: String,
+    }
+);
+
+const CONN: &[EventAttr] = attrs!(
+    Conn,
+    ConnAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        ConnState: String,
+        Duration: Integer,
+        Service: String,
+        OrigBytes: UInteger,
+        RespBytes: UInteger,
+        OrigPkts: UInteger,
+        RespPkts: UInteger,
+        OrigL2Bytes: UInteger,
+        RespL2Bytes: UInteger,
+    }
+);
+
+const PORT_SCAN: &[EventAttr] = attrs!(
+    Conn,
+    ConnAttr {
+        SrcAddr: IpAddr,
+        DstAddr: IpAddr,
+        DstPort: [UInteger],
+        Proto: UInteger,
+    }
+);
+
+const MULTI_HOST_PORT_SCAN: &[EventAttr] = attrs!(
+    Conn,
+    ConnAttr {
+        SrcAddr: IpAddr,
+        DstPort: UInteger,
+        DstAddr: [IpAddr],
+        Proto: UInteger,
+    }
+);
+
+const EXTERNAL_DDOS: &[EventAttr] = attrs!(
+    Conn,
+    ConnAttr {
+        SrcAddr: [IpAddr],
+        DstAddr: IpAddr,
+        Proto: UInteger,
+    }
+);
+
+const BEACONING: &[EventAttr] = attrs!(
+    Conn,
+    ConnAttr {
+        SrcAddr: IpAddr,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+    }
+);
+
+const VOLUME_EXFILTRATION: &[EventAttr] = attrs!(
+    Conn,
+    ConnAttr {
+        SrcAddr: IpAddr,
+        DstAddr: [IpAddr],
+        Service: [String],
+        OrigBytes: UInteger,
+    }
+);
+
+const DHCP: &[EventAttr] = attrs!(
+    Dhcp,
+    DhcpAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        MgsType: UInteger,
+        CiAddr: IpAddr,
+        YiAddr: IpAddr,
+        SiAddr: IpAddr,
+        GiAddr: IpAddr,
+        SubNetMask: IpAddr,
+        Router: [IpAddr],
+        DomainNameServer: [IpAddr],
+        ReqIpAddr: IpAddr,
+        LeaseTime: UInteger,
+        ServerId: IpAddr,
+        ParamReqList: [UInteger],
+        Message: String,
+        RenewalTime: UInteger,
+        RebindingTime: UInteger,
+        ClassId: Vector,
+        ClientIdType: UInteger,
+        ClientId: Vector,
+    }
+);
+
+const DNS: &[EventAttr] = attrs!(
+    Dns,
+    DnsAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Query: String,
+        Answer: [String],
+        TransId: UInteger,
+        Rtt: Integer,
+        QClass: UInteger,
+        QType: UInteger,
+        RCode: UInteger,
+        AA: Bool,
+        TC: Bool,
+        RD: Bool,
+        RA: Bool,
+        Ttl: [Integer],
+    }
+);
+
+const FTP: &[EventAttr] = attrs!(
+    Ftp,
+    FtpAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        User: String,
+        Password: String,
+        Command: String,
+        ReplyCode: String,
+        ReplyMsg: String,
+        DataPassive: Bool,
+        DataOrigAddr: IpAddr,
+        DataRespAddr: IpAddr,
+        DataRespPort: UInteger,
+        File: String,
+        FileSize: UInteger,
+        FileId: String,
+    }
+);
+
+const FTP_BRUTE_FORCE: &[EventAttr] = attrs!(
+    Ftp,
+    FtpAttr {
+        SrcAddr: IpAddr,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        User: [String],
+    }
+);
+
+const HTTP: &[EventAttr] = attrs!(
+    Http,
+    HttpAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Method: String,
+        Host: String,
+        Uri: String,
+        Referer: String,
+        Version: String,
+        UserAgent: String,
+        RequestLen: UInteger,
+        ResponseLen: UInteger,
+        StatusCode: UInteger,
+        StatusMsg: String,
+        Username: String,
+        Password: String,
+        Cookie: String,
+        ContentEncoding: String,
+        ContentType: String,
+        CacheControl: String,
+        OrigFilenames: [String],
+        OrigMimeTypes: [String],
+        RespFilenames: [String],
+        RespMimeTypes: [String],
+        PostBody: Vector,
+        State: String,
+    }
+);
+
+const REPEATED_HTTP_SESSIONS: &[EventAttr] = attrs!(
+    Http,
+    HttpAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+    }
+);
+
+const KERBEROS: &[EventAttr] = attrs!(
+    Kerberos,
+    KerberosAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        ClientTime: Integer,
+        ServerTime: Integer,
+        ErrorCode: UInteger,
+        ClientRealm: String,
+        CnameType: UInteger,
+        ClientName: [String],
+        Realm: String,
+        SnameType: UInteger,
+        ServiceName: [String],
+    }
+);
+
+const KERBEROASTING: &[EventAttr] = attrs!(
+    Kerberos,
+    KerberosAttr {
+        SrcAddr: IpAddr,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        ClientRealm: String,
+        ClientName: [String],
+        Realm: String,
+        ServiceName: [String],
+    }
+);
+
+const AS_REP_ROASTING: &[EventAttr] = attrs!(
+    Kerberos,
+    KerberosAttr {
+        SrcAddr: IpAddr,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        ClientName: [String],
+        Realm: String,
+    }
+);
+
+const LDAP: &[EventAttr] = attrs!(
+    Ldap,
+    LdapAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        MessageId: UInteger,
+        Version: UInteger,
+        Opcode: [String],
+        Result: [String],
+        DiagnosticMessage: [String],
+        Object: [String],
+        Argument: [String],
+    }
+);
+
+const LDAP_BRUTE_FORCE: &[EventAttr] = attrs!(
+    Ldap,
+    LdapAttr {
+        SrcAddr: IpAddr,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+    }
+);
+
+const EXTRA_THREAT: &[EventAttr] = attrs!(Log, LogAttr { Content: String });
+
+const MQTT: &[EventAttr] = attrs!(
+    Mqtt,
+    MqttAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Protocol: String,
+        Version: UInteger,
+        ClientId: String,
+        ConnackReason: UInteger,
+        Subscribe: [String],
+        SubackReason: [UInteger],
+    }
+);
+
+const NETWORK: &[EventAttr] = attrs!(
+    Network,
+    NetworkAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Content: String,
+    }
+);
+
+const NFS: &[EventAttr] = attrs!(
+    Nfs,
+    NfsAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        ReadFiles: [String],
+        WriteFiles: [String],
+    }
+);
+
+const NTLM: &[EventAttr] = attrs!(
+    Ntlm,
+    NtlmAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Protocol: String,
+        Username: String,
+        Hostname: String,
+        Domainname: String,
+        Success: String,
+    }
+);
+
+const NTLM_PASSWORD_SPRAY: &[EventAttr] = attrs!(
+    Ntlm,
+    NtlmAttr {
+        SrcAddr: IpAddr,
+        DstAddr: [IpAddr],
+        DstPort: UInteger,
+        Proto: UInteger,
+        Protocol: String,
+        Username: [String],
+        Domainname: String,
+    }
+);
+
+const RDP: &[EventAttr] = attrs!(
+    Rdp,
+    RdpAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Cookie: String,
+    }
+);
+
+const RDP_BRUTE_FORCE: &[EventAttr] = attrs!(
+    Rdp,
+    RdpAttr {
+        SrcAddr: IpAddr,
+        DstAddr: [IpAddr],
+        Proto: UInteger,
+    }
+);
+
+const SMB: &[EventAttr] = attrs!(
+    Smb,
+    SmbAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Command: UInteger,
+        Path: String,
+        Service: String,
+        FileName: String,
+        FileSize: UInteger,
+        ResourceType: UInteger,
+        Fid: UInteger,
+        CreateTime: Integer,
+        AccessTime: Integer,
+        WriteTime: Integer,
+        ChangeTime: Integer,
+    }
+);
+
+const SMTP: &[EventAttr] = attrs!(
+    Smtp,
+    SmtpAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        MailFrom: String,
+        Date: String,
+        From: String,
+        To: String,
+        Subject: String,
+        Agent: String,
+        State: String,
+    }
+);
+
+const SSH: &[EventAttr] = attrs!(
+    Ssh,
+    SshAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        Client: String,
+        Server: String,
+        CipherAlg: String,
+        MacAlg: String,
+        CompressionAlg: String,
+        KexAlg: String,
+        HostKeyAlg: String,
+        HasshAlgorithms: String,
+        Hassh: String,
+        HasshServerAlgorithms: String,
+        HasshServer: String,
+        ClientShka: String,
+        ServerShka: String,
+    }
+);
+
+const SSH_BRUTE_FORCE: &[EventAttr] = attrs!(
+    Ssh,
+    SshAttr {
+        SrcAddr: IpAddr,
+        DstAddr: [IpAddr],
+        DstPort: UInteger,
+        Proto: UInteger,
+        Client: String,
+        Server: String,
+    }
+);
+
+const TLS: &[EventAttr] = attrs!(
+    Tls,
+    TlsAttr {
+        SrcAddr: IpAddr,
+        SrcPort: UInteger,
+        DstAddr: IpAddr,
+        DstPort: UInteger,
+        Proto: UInteger,
+        ServerName: String,
+        AlpnProtocol: String,
+        Ja3: String,
+        Version: String,
+        ClientCipherSuites: [UInteger],
+        ClientExtensions: [UInteger],
+        Cipher: UInteger,
+        Extensions: [UInteger],
+        Ja3s: String,
+        Serial: String,
+        SubjectCountry: String,
+        SubjectOrgName: String,
+        SubjectCommonName: String,
+        ValidityNotBefore: Integer,
+        ValidityNotAfter: Integer,
+        SubjectAltName: String,
+        IssuerCountry: String,
+        IssuerOrgName: String,
+        IssuerOrgUnitName: String,
+        IssuerCommonName: String,
+        LastAlert: UInteger,
+    }
+);
+
+const WINDOW: &[EventAttr] = attrs!(
+    Window,
+    WindowAttr {
+        Service: String,
+        AgentName: String,
+        AgentId: String,
+        ProcessGuid: String,
+        ProcessId: UInteger,
+        Image: String,
+        User: String,
+        Content: String,
+    }
+);
+
+/// Returns the attributes of the given event kind that triage rules can refer to. The list must be
+/// kept in sync with the `find_attr_by_kind` implementation of the event kind.
+pub(super) fn attributes(kind: EventKind) -> &'static [EventAttr] {
+    match kind {
+        EventKind::DnsCovertChannel
+        | EventKind::LockyRansomware
+        | EventKind::CryptocurrencyMiningPool
+        | EventKind::BlocklistDns => DNS,
+        EventKind::HttpThreat
+        | EventKind::DomainGenerationAlgorithm
+        | EventKind::NonBrowser
+        | EventKind::BlocklistHttp
+        | EventKind::TorConnection => HTTP,
+        EventKind::RepeatedHttpSessions => REPEATED_HTTP_SESSIONS,
+        EventKind::RdpBruteForce => RDP_BRUTE_FORCE,
+        EventKind::BlocklistRdp => RDP,
+        EventKind::ExtraThreat => EXTRA_THREAT,
+        EventKind::FtpBruteForce => FTP_BRUTE_FORCE,
+        EventKind::FtpPlainText | EventKind::BlocklistFtp => FTP,
+        EventKind::PortScan => PORT_SCAN,
+        EventKind::MultiHostPortScan => MULTI_HOST_PORT_SCAN,
+        EventKind::ExternalDdos => EXTERNAL_DDOS,
+        EventKind::BlocklistConn | EventKind::TorConnectionConn => CONN,
+        EventKind::Beaconing => BEACONING,
+        EventKind::VolumeExfiltration => VOLUME_EXFILTRATION,
+        EventKind::LdapBruteForce => LDAP_BRUTE_FORCE,
+        EventKind::LdapPlainText | EventKind::BlocklistLdap => LDAP,
+        EventKind::BlocklistKerberos => KERBEROS,
+        EventKind::Kerberoasting => KERBEROASTING,
+        EventKind::AsRepRoasting => AS_REP_ROASTING,
+        EventKind::BlocklistMqtt => MQTT,
+        EventKind::BlocklistNfs => NFS,
+        EventKind::BlocklistNtlm => NTLM,
+        EventKind::NtlmPasswordSpray => NTLM_PASSWORD_SPRAY,
+        EventKind::BlocklistSmb => SMB,
+        EventKind::BlocklistSmtp => SMTP,
+        EventKind::BlocklistSsh => SSH,
+        EventKind::SshBruteForce => SSH_BRUTE_FORCE,
+        EventKind::BlocklistTls | EventKind::SuspiciousTlsTraffic => TLS,
+        EventKind::BlocklistBootp => BOOTP,
+        EventKind::BlocklistDhcp => DHCP,
+        EventKind::NetworkThreat => NETWORK,
+        EventKind::WindowsThreat => WINDOW,
+        // The attributes of custom events are described by their registered schemas instead.
+        EventKind::BlocklistDceRpc
+        | EventKind::BlocklistRadius
+        | EventKind::BlocklistMalformedDns
+        | EventKind::UnusualDestinationPattern
+        | EventKind::CustomEvent => &[],
+    }
+}
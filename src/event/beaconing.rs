@@ -0,0 +1,187 @@
+use std::{fmt, net::IpAddr, num::NonZeroU8};
+
+use attrievent::attribute::{ConnAttr, RawEventAttrKind};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
+use crate::event::common::{AttrValue, triage_scores_to_string};
+
+pub type BeaconingFields = BeaconingFieldsV0_43;
+
+/// The fields of periodic outbound connections from a host to a single destination, such as the
+/// heartbeat of a command-and-control implant.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Serialize, Deserialize)]
+pub struct BeaconingFieldsV0_43 {
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addr: IpAddr,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub start_time: i64,
+    pub end_time: i64,
+    /// The number of connections in the observation window.
+    pub count: usize,
+    /// The mean interval between connections, in seconds.
+    pub mean_interval: f64,
+    /// The standard deviation of the intervals between connections, in seconds.
+    pub jitter: f64,
+    /// The mean number of bytes sent and received per connection.
+    pub bytes_per_conn: f64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+}
+
+impl BeaconingFields {
+    #[must_use]
+    pub fn syslog_rfc5424(&self) -> String {
+        let start_time_dt = DateTime::from_timestamp_nanos(self.start_time);
+        let end_time_dt = DateTime::from_timestamp_nanos(self.end_time);
+        format!(
+            "category={:?} sensor={:?} src_addr={:?} dst_addr={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} count={:?} mean_interval={:?} jitter={:?} bytes_per_conn={:?} confidence={:?}",
+            self.category.as_ref().map_or_else(
+                || "Unspecified".to_string(),
+                std::string::ToString::to_string
+            ),
+            self.sensor,
+            self.src_addr.to_string(),
+            self.dst_addr.to_string(),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            start_time_dt.to_rfc3339(),
+            end_time_dt.to_rfc3339(),
+            self.count.to_string(),
+            self.mean_interval.to_string(),
+            self.jitter.to_string(),
+            self.bytes_per_conn.to_string(),
+            self.confidence.to_string(),
+        )
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct Beaconing {
+    pub time: DateTime<Utc>,
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addr: IpAddr,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub count: usize,
+    pub mean_interval: f64,
+    pub jitter: f64,
+    pub bytes_per_conn: f64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+    pub triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl fmt::Display for Beaconing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor={:?} src_addr={:?} dst_addr={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} count={:?} mean_interval={:?} jitter={:?} bytes_per_conn={:?} triage_scores={:?}",
+            self.sensor,
+            self.src_addr.to_string(),
+            self.dst_addr.to_string(),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            self.start_time.to_rfc3339(),
+            self.end_time.to_rfc3339(),
+            self.count.to_string(),
+            self.mean_interval.to_string(),
+            self.jitter.to_string(),
+            self.bytes_per_conn.to_string(),
+            triage_scores_to_string(self.triage_scores.as_ref()),
+        )
+    }
+}
+
+impl Beaconing {
+    pub(super) fn new(time: DateTime<Utc>, fields: BeaconingFields) -> Self {
+        Self {
+            time,
+            sensor: fields.sensor,
+            src_addr: fields.src_addr,
+            dst_addr: fields.dst_addr,
+            dst_port: fields.dst_port,
+            proto: fields.proto,
+            start_time: DateTime::from_timestamp_nanos(fields.start_time),
+            end_time: DateTime::from_timestamp_nanos(fields.end_time),
+            count: fields.count,
+            mean_interval: fields.mean_interval,
+            jitter: fields.jitter,
+            bytes_per_conn: fields.bytes_per_conn,
+            confidence: fields.confidence,
+            category: fields.category,
+            triage_scores: None,
+        }
+    }
+}
+
+impl Match for Beaconing {
+    fn src_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.src_addr)
+    }
+
+    fn src_port(&self) -> u16 {
+        0
+    }
+
+    fn dst_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.dst_addr)
+    }
+
+    fn dst_port(&self) -> u16 {
+        self.dst_port
+    }
+
+    fn proto(&self) -> u8 {
+        self.proto
+    }
+
+    fn category(&self) -> Option<EventCategory> {
+        self.category
+    }
+
+    fn detector_level(&self) -> NonZeroU8 {
+        MEDIUM
+    }
+
+    fn kind(&self) -> &'static str {
+        "beaconing"
+    }
+
+    fn event_kind(&self) -> EventKind {
+        EventKind::Beaconing
+    }
+
+    fn sensor(&self) -> &str {
+        self.sensor.as_str()
+    }
+
+    fn confidence(&self) -> Option<f32> {
+        Some(self.confidence)
+    }
+
+    fn learning_method(&self) -> LearningMethod {
+        LearningMethod::SemiSupervised
+    }
+
+    fn find_attr_by_kind(&self, raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>> {
+        if let RawEventAttrKind::Conn(attr) = raw_event_attr {
+            match attr {
+                ConnAttr::SrcAddr => Some(AttrValue::Addr(self.src_addr)),
+                ConnAttr::DstAddr => Some(AttrValue::Addr(self.dst_addr)),
+                ConnAttr::DstPort => Some(AttrValue::UInt(self.dst_port.into())),
+                ConnAttr::Proto => Some(AttrValue::UInt(self.proto.into())),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
@@ -4,7 +4,7 @@ use attrievent::attribute::{BootpAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, to_hardware_address, triage_scores_to_string};
 
 macro_rules! find_bootp_attr_by_kind {
@@ -227,7 +227,7 @@ impl Match for BlocklistBootp {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -235,6 +235,10 @@ impl Match for BlocklistBootp {
         "blocklist bootp"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistBootp
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
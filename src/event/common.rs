@@ -10,10 +10,11 @@ use bincode::Options;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 
-use super::{
-    EventCategory, EventFilter, FlowKind, LearningMethod, TrafficDirection, eq_ip_country,
+use super::{EventCategory, EventFilter, EventKind, FlowKind, LearningMethod, TrafficDirection};
+use crate::{
+    AttrCmpKind, Confidence, Customer, EventEnrichment, PacketAttr, RuleTechniques,
+    SeverityOverrides, TriageExclusion, ValueKind, types::Endpoint,
 };
-use crate::{AttrCmpKind, Confidence, PacketAttr, TriageExclusion, ValueKind};
 
 /// Epsilon value for inclusive confidence comparisons
 const CONFIDENCE_EPSILON: f32 = 1e-6;
@@ -30,21 +31,28 @@ pub(super) trait Match {
     #[allow(dead_code)] // for future use
     fn proto(&self) -> u8;
     fn category(&self) -> Option<EventCategory>;
-    fn level(&self) -> NonZeroU8;
+    /// Returns the level assigned by the detector, regardless of severity overrides.
+    fn detector_level(&self) -> NonZeroU8;
     fn kind(&self) -> &str;
+    fn event_kind(&self) -> EventKind;
     fn sensor(&self) -> &str;
     fn confidence(&self) -> Option<f32>;
     fn learning_method(&self) -> LearningMethod;
     fn find_attr_by_kind(&self, raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>>;
+
+    /// Returns the value of the attribute that the triage rule refers to.
+    fn find_attr(&self, packet_attr: &PacketAttr) -> Option<AttrValue<'_>> {
+        let kind = RawEventAttrKind::from_kind_and_attr_name(
+            &packet_attr.raw_event_kind,
+            &packet_attr.attr_name,
+        )
+        .ok()?;
+        self.find_attr_by_kind(kind)
+    }
+
     fn score_by_attr(&self, attr_triage: &[PacketAttr]) -> f64 {
         let total_score = attr_triage.iter().fold(0.0, |score_acc, item| {
-            let Ok(kind) =
-                RawEventAttrKind::from_kind_and_attr_name(&item.raw_event_kind, &item.attr_name)
-            else {
-                return score_acc;
-            };
-
-            let Some(value) = self.find_attr_by_kind(kind) else {
+            let Some(value) = self.find_attr(item) else {
                 return score_acc;
             };
 
@@ -57,22 +65,54 @@ pub(super) trait Match {
         (total_score * 100.0).trunc() / 100.0
     }
 
+    /// Returns the level of the event, which is the level of the applicable severity override, if
+    /// any, or the detector level.
+    fn level(&self, overrides: Option<&SeverityOverrides>) -> NonZeroU8 {
+        overrides
+            .and_then(|overrides| {
+                overrides.level(
+                    self.event_kind(),
+                    self.sensor(),
+                    self.src_addrs(),
+                    self.dst_addrs(),
+                )
+            })
+            .unwrap_or_else(|| self.detector_level())
+    }
+
+    /// Returns the name of the TI database and the ID of the rule that the event matched, if the
+    /// event was detected by a TI rule.
+    fn rule(&self) -> Option<(&str, u32)> {
+        None
+    }
+
+    /// Returns the MITRE ATT&CK technique IDs of the event, taking those of the matched TI rule
+    /// over those of the event kind.
+    fn techniques<'a>(&'a self, rule_techniques: &'a RuleTechniques) -> Vec<&'a str> {
+        if let Some(techniques) = self
+            .rule()
+            .and_then(|(db_name, rule_id)| rule_techniques.get(db_name, rule_id))
+        {
+            return techniques.iter().map(String::as_str).collect();
+        }
+        self.event_kind().techniques().to_vec()
+    }
+
     /// Returns whether the event matches the filter and the triage scores. The triage scores are
     /// only returned if the event matches the filter.
     ///
     /// # Errors
     ///
-    /// Returns an error if the filter contains a country filter but the ip2location database is
-    /// not available.
+    /// Returns an error if matching the event against the filter fails.
     fn matches(
         &self,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
         filter: &EventFilter,
     ) -> Result<(bool, Option<Vec<TriageScore>>)> {
         if !self.kind_matches(filter) {
             return Ok((false, None));
         }
-        self.other_matches(filter, locator)
+        self.other_matches(filter, enrichment)
     }
 
     fn kind_matches(&self, filter: &EventFilter) -> bool {
@@ -88,25 +128,28 @@ pub(super) trait Match {
     /// Returns whether the event matches the filter (excluding `kinds`) and the triage scores. The
     /// triage scores are only returned if the event matches the filter.
     ///
+    /// The countries, customers, and networks of the addresses are taken from `enrichment`. Without
+    /// it, the event matches no country filter, and the customer and endpoint filters are checked
+    /// against the addresses.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the filter contains a country filter but the ip2location database is
-    /// not available.
+    /// Returns an error if matching the event against the filter fails.
     #[allow(clippy::too_many_lines)]
     fn other_matches(
         &self,
         filter: &EventFilter,
-        locator: Option<&ip2location::DB>,
+        enrichment: Option<&EventEnrichment>,
     ) -> Result<(bool, Option<Vec<TriageScore>>)> {
         if let Some(customers) = &filter.customers
             && customers.iter().all(|customer| {
                 self.src_addrs()
                     .iter()
-                    .all(|&src_addr| !customer.contains(src_addr))
+                    .all(|&src_addr| !in_customer(enrichment, src_addr, customer))
                     && self
                         .dst_addrs()
                         .iter()
-                        .all(|&dst_addr| !customer.contains(dst_addr))
+                        .all(|&dst_addr| !in_customer(enrichment, dst_addr, customer))
             })
         {
             return Ok((false, None));
@@ -117,19 +160,19 @@ pub(super) trait Match {
                 Some(TrafficDirection::From) => self
                     .src_addrs()
                     .iter()
-                    .all(|&src_addr| !endpoint.network.contains(src_addr)),
+                    .all(|&src_addr| !in_endpoint(enrichment, src_addr, endpoint)),
                 Some(TrafficDirection::To) => self
                     .dst_addrs()
                     .iter()
-                    .all(|&dst_addr| !endpoint.network.contains(dst_addr)),
+                    .all(|&dst_addr| !in_endpoint(enrichment, dst_addr, endpoint)),
                 None => {
                     self.src_addrs()
                         .iter()
-                        .all(|&src_addr| !endpoint.network.contains(src_addr))
+                        .all(|&src_addr| !in_endpoint(enrichment, src_addr, endpoint))
                         && self
                             .dst_addrs()
                             .iter()
-                            .all(|&dst_addr| !endpoint.network.contains(dst_addr))
+                            .all(|&dst_addr| !in_endpoint(enrichment, dst_addr, endpoint))
                 }
             })
         {
@@ -179,16 +222,16 @@ pub(super) trait Match {
             }
         }
 
-        if let Some(countries) = &filter.countries {
-            if let Some(locator) = locator {
-                if countries.iter().all(|country| {
+        if let Some(filter_countries) = &filter.countries {
+            if let Some(enrichment) = enrichment {
+                if filter_countries.iter().all(|country| {
                     self.src_addrs()
                         .iter()
-                        .all(|&src_addr| !eq_ip_country(locator, src_addr, *country))
+                        .all(|&src_addr| !eq_country(enrichment, src_addr, *country))
                         && self
                             .dst_addrs()
                             .iter()
-                            .all(|&dst_addr| !eq_ip_country(locator, dst_addr, *country))
+                            .all(|&dst_addr| !eq_country(enrichment, dst_addr, *country))
                 }) {
                     return Ok((false, None));
                 }
@@ -206,7 +249,9 @@ pub(super) trait Match {
         }
 
         if let Some(levels) = &filter.levels
-            && levels.iter().all(|level| *level != self.level())
+            && levels
+                .iter()
+                .all(|level| *level != self.level(filter.severity_overrides.as_ref()))
         {
             return Ok((false, None));
         }
@@ -225,6 +270,21 @@ pub(super) trait Match {
             return Ok((false, None));
         }
 
+        if let Some((techniques, rule_techniques)) = &filter.techniques {
+            let event_techniques = self.techniques(rule_techniques);
+            // A technique also matches its sub-techniques.
+            if techniques.iter().all(|t| {
+                event_techniques.iter().all(|e| {
+                    *e != t
+                        && !e
+                            .strip_prefix(t.as_str())
+                            .is_some_and(|r| r.starts_with('.'))
+                })
+            }) {
+                return Ok((false, None));
+            }
+        }
+
         if let Some(event_confidence) = self.confidence() {
             if let Some(confidence_min) = filter.confidence_min
                 && event_confidence < confidence_min - CONFIDENCE_EPSILON
@@ -294,6 +354,36 @@ pub(super) trait Match {
     }
 }
 
+/// Returns `true` if the address belongs to the customer, according to the stored enrichment if
+/// any.
+fn in_customer(enrichment: Option<&EventEnrichment>, addr: IpAddr, customer: &Customer) -> bool {
+    match enrichment {
+        Some(enrichment) => enrichment
+            .get(addr)
+            .is_some_and(|info| info.customer_id == Some(customer.id)),
+        None => customer.contains(addr),
+    }
+}
+
+/// Returns `true` if the address is in the endpoint. The networks in the stored enrichment, if
+/// any, are used for an endpoint of a predefined network.
+fn in_endpoint(enrichment: Option<&EventEnrichment>, addr: IpAddr, endpoint: &Endpoint) -> bool {
+    match (enrichment, endpoint.network_id) {
+        (Some(enrichment), Some(id)) => enrichment
+            .get(addr)
+            .is_some_and(|info| info.network_ids.binary_search(&id).is_ok()),
+        _ => endpoint.network.contains(addr),
+    }
+}
+
+/// Returns `true` if the stored country of the address is `country`.
+fn eq_country(enrichment: &EventEnrichment, addr: IpAddr, country: [u8; 2]) -> bool {
+    enrichment
+        .get(addr)
+        .and_then(|info| info.country.as_deref())
+        .is_some_and(|code| code.as_bytes() == country)
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct TriageScore {
     pub policy_id: u32,
@@ -350,7 +440,6 @@ pub fn to_hardware_address(chaddr: &[u8]) -> String {
 pub enum AttrValue<'a> {
     Addr(IpAddr),
     Bool(bool),
-    #[allow(dead_code)]
     Float(f64),
     SInt(i64),
     UInt(u64),
@@ -551,33 +640,37 @@ mod tests {
         net::{IpAddr, Ipv4Addr},
     };
 
-    use attrievent::attribute::{DhcpAttr, DnsAttr, HttpAttr, RawEventKind};
+    use attrievent::attribute::{DhcpAttr, DnsAttr, HttpAttr, RawEventAttrKind, RawEventKind};
     use bincode::Options;
     use chrono::{TimeZone, Utc};
     use serde::Serialize;
+    use strum::IntoEnumIterator;
 
-    use super::Match;
+    use super::{AttrValue, Match};
     use crate::{
         AttrCmpKind, Customer, CustomerNetwork, EventCategory, HostNetworkGroup, PacketAttr,
         ValueKind,
         event::{
-            BlocklistBootp, BlocklistBootpFields, BlocklistConn, BlocklistConnFields,
-            BlocklistDceRpc, BlocklistDceRpcFields, BlocklistDhcp, BlocklistDhcpFields,
-            BlocklistDns, BlocklistDnsFields, BlocklistFtp, BlocklistHttp, BlocklistHttpFields,
+            AsRepRoasting, AsRepRoastingFields, Beaconing, BeaconingFields, BlocklistBootp,
+            BlocklistBootpFields, BlocklistConn, BlocklistConnFields, BlocklistDceRpc,
+            BlocklistDceRpcFields, BlocklistDhcp, BlocklistDhcpFields, BlocklistDns,
+            BlocklistDnsFields, BlocklistFtp, BlocklistHttp, BlocklistHttpFields,
             BlocklistKerberos, BlocklistKerberosFields, BlocklistLdap, BlocklistMqtt,
             BlocklistMqttFields, BlocklistNfs, BlocklistNfsFields, BlocklistNtlm,
             BlocklistNtlmFields, BlocklistRdp, BlocklistRdpFields, BlocklistSmb,
             BlocklistSmbFields, BlocklistSmtp, BlocklistSmtpFields, BlocklistSsh,
             BlocklistSshFields, BlocklistTls, BlocklistTlsFields, CryptocurrencyMiningPool,
             CryptocurrencyMiningPoolFields, DgaFields, DnsCovertChannel, DnsEventFields,
-            DomainGenerationAlgorithm, Event, EventFilter, ExternalDdos, ExternalDdosFields,
-            ExtraThreat, FlowKind, FtpBruteForce, FtpBruteForceFields, FtpEventFields,
-            FtpPlainText, HttpEventFields, HttpThreat, HttpThreatFields, LdapBruteForce,
-            LdapBruteForceFields, LdapEventFields, LdapPlainText, LearningMethod, LockyRansomware,
-            MultiHostPortScan, MultiHostPortScanFields, NetworkThreat, NetworkType, NonBrowser,
-            PortScan, PortScanFields, RdpBruteForce, RdpBruteForceFields, RecordType,
-            RepeatedHttpSessions, RepeatedHttpSessionsFields, SuspiciousTlsTraffic, TorConnection,
-            WindowsThreat,
+            DomainGenerationAlgorithm, Event, EventFilter, EventKind, ExternalDdos,
+            ExternalDdosFields, ExtraThreat, FlowKind, FtpBruteForce, FtpBruteForceFields,
+            FtpEventFields, FtpPlainText, HttpEventFields, HttpThreat, HttpThreatFields,
+            Kerberoasting, KerberoastingFields, LdapBruteForce, LdapBruteForceFields,
+            LdapEventFields, LdapPlainText, LearningMethod, LockyRansomware, MultiHostPortScan,
+            MultiHostPortScanFields, NetworkThreat, NetworkType, NonBrowser, NtlmPasswordSpray,
+            NtlmPasswordSprayFields, PortScan, PortScanFields, RdpBruteForce, RdpBruteForceFields,
+            RecordType, RepeatedHttpSessions, RepeatedHttpSessionsFields, SshBruteForce,
+            SshBruteForceFields, SuspiciousTlsTraffic, TorConnection, TorConnectionConn,
+            VolumeExfiltration, VolumeExfiltrationFields, WindowsThreat,
         },
         types::Endpoint,
     };
@@ -1186,6 +1279,208 @@ mod tests {
         );
     }
 
+    /// Returns the value type of the attribute value and whether it has a list of values.
+    fn attr_value_kind(value: &AttrValue) -> (ValueKind, bool) {
+        match value {
+            AttrValue::Addr(_) => (ValueKind::IpAddr, false),
+            AttrValue::Bool(_) => (ValueKind::Bool, false),
+            AttrValue::Float(_) => (ValueKind::Float, false),
+            AttrValue::SInt(_) => (ValueKind::Integer, false),
+            AttrValue::UInt(_) => (ValueKind::UInteger, false),
+            AttrValue::String(_) => (ValueKind::String, false),
+            AttrValue::VecAddr(_) => (ValueKind::IpAddr, true),
+            AttrValue::VecFloat(_) => (ValueKind::Float, true),
+            AttrValue::VecSInt(_) => (ValueKind::Integer, true),
+            AttrValue::VecUInt(_) => (ValueKind::UInteger, true),
+            AttrValue::VecString(_) => (ValueKind::String, true),
+            AttrValue::VecRaw(_) => (ValueKind::Vector, false),
+        }
+    }
+
+    /// Checks that each attribute listed for the kind of the event is found with the listed type.
+    fn assert_attributes(event: &dyn Match) {
+        let attributes = event.event_kind().attributes();
+        assert!(!attributes.is_empty());
+        for attr in attributes {
+            let value = event
+                .find_attr_by_kind(attr.kind)
+                .expect("listed attribute should be found");
+            assert_eq!(
+                attr_value_kind(&value),
+                (attr.value_kind, attr.multi_valued)
+            );
+        }
+    }
+
+    /// Returns an event of the given kind, or `None` if the kind has no attributes.
+    fn sample_event(kind: EventKind) -> Option<Event> {
+        let time = Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap();
+        let event = match kind {
+            EventKind::DnsCovertChannel => {
+                Event::DnsCovertChannel(DnsCovertChannel::new(time, dns_event_fields()))
+            }
+            EventKind::HttpThreat => Event::HttpThreat(HttpThreat::new(time, http_threat_fields())),
+            EventKind::RdpBruteForce => {
+                Event::RdpBruteForce(RdpBruteForce::new(time, &rdp_brute_force_fields()))
+            }
+            EventKind::SshBruteForce => {
+                Event::SshBruteForce(SshBruteForce::new(time, ssh_brute_force_fields()))
+            }
+            EventKind::Beaconing => Event::Beaconing(Beaconing::new(time, beaconing_fields())),
+            EventKind::VolumeExfiltration => Event::VolumeExfiltration(VolumeExfiltration::new(
+                time,
+                volume_exfiltration_fields(),
+            )),
+            EventKind::Kerberoasting => {
+                Event::Kerberoasting(Kerberoasting::new(time, kerberoasting_fields()))
+            }
+            EventKind::AsRepRoasting => {
+                Event::AsRepRoasting(AsRepRoasting::new(time, as_rep_roasting_fields()))
+            }
+            EventKind::NtlmPasswordSpray => {
+                Event::NtlmPasswordSpray(NtlmPasswordSpray::new(time, ntlm_password_spray_fields()))
+            }
+            EventKind::RepeatedHttpSessions => Event::RepeatedHttpSessions(
+                RepeatedHttpSessions::new(time, &repeated_http_sessions_fiedls()),
+            ),
+            EventKind::TorConnection => {
+                Event::TorConnection(TorConnection::new(time, &http_event_fields()))
+            }
+            EventKind::TorConnectionConn => {
+                Event::TorConnectionConn(TorConnectionConn::new(time, blocklist_conn_fields()))
+            }
+            EventKind::DomainGenerationAlgorithm => {
+                Event::DomainGenerationAlgorithm(DomainGenerationAlgorithm::new(time, dga_fields()))
+            }
+            EventKind::FtpBruteForce => {
+                Event::FtpBruteForce(FtpBruteForce::new(time, &ftp_brute_force_fields()))
+            }
+            EventKind::FtpPlainText => {
+                Event::FtpPlainText(FtpPlainText::new(time, ftp_event_fields()))
+            }
+            EventKind::PortScan => Event::PortScan(PortScan::new(time, &port_scan_fields())),
+            EventKind::MultiHostPortScan => Event::MultiHostPortScan(MultiHostPortScan::new(
+                time,
+                &multi_host_port_scan_fields(),
+            )),
+            EventKind::NonBrowser => Event::NonBrowser(NonBrowser::new(time, &http_event_fields())),
+            EventKind::LdapBruteForce => {
+                Event::LdapBruteForce(LdapBruteForce::new(time, &ldap_brute_force_fields()))
+            }
+            EventKind::LdapPlainText => {
+                Event::LdapPlainText(LdapPlainText::new(time, ldap_event_fields()))
+            }
+            EventKind::ExternalDdos => {
+                Event::ExternalDdos(ExternalDdos::new(time, &external_ddos_fields()))
+            }
+            EventKind::CryptocurrencyMiningPool => Event::CryptocurrencyMiningPool(
+                CryptocurrencyMiningPool::new(time, crypto_miining_pool_fields()),
+            ),
+            EventKind::BlocklistBootp => Event::Blocklist(RecordType::Bootp(BlocklistBootp::new(
+                time,
+                blocklist_bootp_fields(),
+            ))),
+            EventKind::BlocklistConn => Event::Blocklist(RecordType::Conn(BlocklistConn::new(
+                time,
+                blocklist_conn_fields(),
+            ))),
+            EventKind::BlocklistDhcp => Event::Blocklist(RecordType::Dhcp(BlocklistDhcp::new(
+                time,
+                blocklist_dhcp_fields(),
+            ))),
+            EventKind::BlocklistDns => Event::Blocklist(RecordType::Dns(BlocklistDns::new(
+                time,
+                blocklist_dns_fields(),
+            ))),
+            EventKind::BlocklistFtp => {
+                Event::Blocklist(RecordType::Ftp(BlocklistFtp::new(time, ftp_event_fields())))
+            }
+            EventKind::BlocklistHttp => Event::Blocklist(RecordType::Http(BlocklistHttp::new(
+                time,
+                blocklist_http_fields(),
+            ))),
+            EventKind::BlocklistKerberos => Event::Blocklist(RecordType::Kerberos(
+                BlocklistKerberos::new(time, blocklist_kerberos_fields()),
+            )),
+            EventKind::BlocklistLdap => Event::Blocklist(RecordType::Ldap(BlocklistLdap::new(
+                time,
+                ldap_event_fields(),
+            ))),
+            EventKind::BlocklistMqtt => Event::Blocklist(RecordType::Mqtt(BlocklistMqtt::new(
+                time,
+                blocklist_mqtt_fields(),
+            ))),
+            EventKind::BlocklistNfs => Event::Blocklist(RecordType::Nfs(BlocklistNfs::new(
+                time,
+                blocklist_nfs_fields(),
+            ))),
+            EventKind::BlocklistNtlm => Event::Blocklist(RecordType::Ntlm(BlocklistNtlm::new(
+                time,
+                blocklist_ntlm_fields(),
+            ))),
+            EventKind::BlocklistRdp => Event::Blocklist(RecordType::Rdp(BlocklistRdp::new(
+                time,
+                blocklist_rdp_fields(),
+            ))),
+            EventKind::BlocklistSmb => Event::Blocklist(RecordType::Smb(BlocklistSmb::new(
+                time,
+                blocklist_smb_fields(),
+            ))),
+            EventKind::BlocklistSmtp => Event::Blocklist(RecordType::Smtp(BlocklistSmtp::new(
+                time,
+                blocklist_smtp_fields(),
+            ))),
+            EventKind::BlocklistSsh => Event::Blocklist(RecordType::Ssh(BlocklistSsh::new(
+                time,
+                blocklist_ssh_fields(),
+            ))),
+            EventKind::BlocklistTls => Event::Blocklist(RecordType::Tls(BlocklistTls::new(
+                time,
+                blocklist_tls_fields(),
+            ))),
+            EventKind::WindowsThreat => Event::WindowsThreat(windows_threat()),
+            EventKind::NetworkThreat => Event::NetworkThreat(network_threat()),
+            EventKind::ExtraThreat => Event::ExtraThreat(extra_threat()),
+            EventKind::LockyRansomware => {
+                Event::LockyRansomware(LockyRansomware::new(time, dns_event_fields()))
+            }
+            EventKind::SuspiciousTlsTraffic => {
+                Event::SuspiciousTlsTraffic(SuspiciousTlsTraffic::new(time, blocklist_tls_fields()))
+            }
+            EventKind::BlocklistDceRpc
+            | EventKind::BlocklistRadius
+            | EventKind::BlocklistMalformedDns
+            | EventKind::UnusualDestinationPattern
+            | EventKind::CustomEvent => return None,
+        };
+        Some(event)
+    }
+
+    #[test]
+    fn attributes_of_event_kinds() {
+        for kind in EventKind::iter() {
+            let Some(event) = sample_event(kind) else {
+                assert!(kind.attributes().is_empty(), "{kind:?}");
+                continue;
+            };
+            let event = event.as_match();
+            assert_eq!(event.event_kind(), kind);
+            assert_attributes(event);
+        }
+
+        let uri = RawEventAttrKind::from_kind_and_attr_name(
+            &RawEventKind::Http,
+            &HttpAttr::Uri.to_string(),
+        )
+        .unwrap();
+        let kinds: Vec<_> = EventKind::with_attribute(uri)
+            .map(|(kind, _)| kind)
+            .collect();
+        assert!(kinds.contains(&EventKind::HttpThreat));
+        assert!(!kinds.contains(&EventKind::RepeatedHttpSessions));
+        assert!(!kinds.contains(&EventKind::BlocklistDns));
+    }
+
     #[test]
     fn compare_attribute() {
         let time = Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap();
@@ -1328,6 +1623,7 @@ mod tests {
         Endpoint {
             direction: None,
             network: create_host_network_group(addr),
+            network_id: None,
         }
     }
 
@@ -1370,6 +1666,8 @@ mod tests {
             confidence_min: None,
             confidence_max: None,
             triage_policies: None,
+            techniques: None,
+            severity_overrides: None,
         }
     }
 
@@ -2293,6 +2591,7 @@ mod tests {
             attack_kind: "attack_kind".to_string(),
             confidence: 0.9,
             category: Some(EventCategory::Reconnaissance),
+            fields: None,
             triage_scores: None,
         }
     }
@@ -2320,6 +2619,114 @@ mod tests {
         }
     }
 
+    fn ssh_brute_force_fields() -> SshBruteForceFields {
+        SshBruteForceFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dst_addrs: vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2))],
+            dst_port: 22,
+            proto: 6,
+            attempts: vec![30],
+            start_time: 0,
+            end_time: 1,
+            client: "SSH-2.0-OpenSSH_8.9".to_string(),
+            server: "SSH-2.0-OpenSSH_9.0".to_string(),
+            confidence: 0.3,
+            category: Some(EventCategory::CredentialAccess),
+        }
+    }
+
+    fn beaconing_fields() -> BeaconingFields {
+        BeaconingFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dst_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)),
+            dst_port: 443,
+            proto: 6,
+            start_time: 0,
+            end_time: 1,
+            count: 20,
+            mean_interval: 60.0,
+            jitter: 0.5,
+            bytes_per_conn: 512.0,
+            confidence: 0.3,
+            category: Some(EventCategory::CommandAndControl),
+        }
+    }
+
+    fn volume_exfiltration_fields() -> VolumeExfiltrationFields {
+        VolumeExfiltrationFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dst_addrs: vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2))],
+            start_time: 0,
+            end_time: 1,
+            bytes_sent: 1_000_000,
+            expected_mean: 1000.0,
+            std_deviation: 100.0,
+            z_score: 9990.0,
+            protocols: vec!["http".to_string()],
+            confidence: 0.3,
+            category: Some(EventCategory::Exfiltration),
+        }
+    }
+
+    fn kerberoasting_fields() -> KerberoastingFields {
+        KerberoastingFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dst_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)),
+            dst_port: 88,
+            proto: 17,
+            start_time: 0,
+            end_time: 1,
+            client_realm: "EXAMPLE.COM".to_string(),
+            client_name: vec!["user1".to_string()],
+            realm: "EXAMPLE.COM".to_string(),
+            service_names: vec!["http/web.example.com".to_string()],
+            request_count: 10,
+            rc4_request_count: 10,
+            confidence: 0.3,
+            category: Some(EventCategory::CredentialAccess),
+        }
+    }
+
+    fn as_rep_roasting_fields() -> AsRepRoastingFields {
+        AsRepRoastingFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dst_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)),
+            dst_port: 88,
+            proto: 17,
+            start_time: 0,
+            end_time: 1,
+            realm: "EXAMPLE.COM".to_string(),
+            accounts: vec!["user1".to_string()],
+            request_count: 10,
+            confidence: 0.3,
+            category: Some(EventCategory::CredentialAccess),
+        }
+    }
+
+    fn ntlm_password_spray_fields() -> NtlmPasswordSprayFields {
+        NtlmPasswordSprayFields {
+            sensor: "collector1".to_string(),
+            src_addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            dst_addrs: vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2))],
+            dst_port: 445,
+            proto: 6,
+            start_time: 0,
+            end_time: 1,
+            protocol: "smb".to_string(),
+            domainname: "EXAMPLE".to_string(),
+            usernames: vec!["user1".to_string(), "user2".to_string()],
+            attempts: 20,
+            failures: 19,
+            confidence: 0.3,
+            category: Some(EventCategory::CredentialAccess),
+        }
+    }
+
     fn http_threat_fields() -> HttpThreatFields {
         HttpThreatFields {
             time: Utc.with_ymd_and_hms(1970, 1, 1, 0, 1, 1).unwrap(),
@@ -4,7 +4,7 @@ use attrievent::attribute::{ConnAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string, vector_to_string};
 
 #[macro_export]
@@ -150,7 +150,7 @@ impl Match for PortScan {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -158,6 +158,10 @@ impl Match for PortScan {
         "port scan"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::PortScan
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -301,7 +305,7 @@ impl Match for MultiHostPortScan {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -309,6 +313,10 @@ impl Match for MultiHostPortScan {
         "multi host port scan"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::MultiHostPortScan
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -446,7 +454,7 @@ impl Match for ExternalDdos {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -454,6 +462,10 @@ impl Match for ExternalDdos {
         "external ddos"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::ExternalDdos
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -639,7 +651,7 @@ impl Match for BlocklistConn {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -647,6 +659,10 @@ impl Match for BlocklistConn {
         "blocklist conn"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistConn
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
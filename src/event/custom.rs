@@ -0,0 +1,278 @@
+#![allow(clippy::module_name_repetitions)]
+
+use std::{fmt, net::IpAddr, num::NonZeroU8};
+
+use attrievent::attribute::RawEventAttrKind;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{EventCategory, EventKind, LearningMethod, TriageScore, common::Match};
+use crate::{
+    PacketAttr, ValueKind,
+    event::common::{AttrValue, triage_scores_to_string, vector_to_string},
+};
+
+/// The type of a value in a custom event.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CustomValueKind {
+    Addr,
+    Port,
+    String,
+    Integer,
+    UInteger,
+    Float,
+    Bool,
+}
+
+impl CustomValueKind {
+    /// Returns the value type of triage rules on attributes of this type.
+    #[must_use]
+    pub fn value_kind(self) -> ValueKind {
+        match self {
+            Self::Addr => ValueKind::IpAddr,
+            Self::Port | Self::UInteger => ValueKind::UInteger,
+            Self::String => ValueKind::String,
+            Self::Integer => ValueKind::Integer,
+            Self::Float => ValueKind::Float,
+            Self::Bool => ValueKind::Bool,
+        }
+    }
+}
+
+/// A typed value in a custom event.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum CustomValue {
+    Addr(IpAddr),
+    Port(u16),
+    String(String),
+    Integer(i64),
+    UInteger(u64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl CustomValue {
+    #[must_use]
+    pub fn kind(&self) -> CustomValueKind {
+        match self {
+            Self::Addr(_) => CustomValueKind::Addr,
+            Self::Port(_) => CustomValueKind::Port,
+            Self::String(_) => CustomValueKind::String,
+            Self::Integer(_) => CustomValueKind::Integer,
+            Self::UInteger(_) => CustomValueKind::UInteger,
+            Self::Float(_) => CustomValueKind::Float,
+            Self::Bool(_) => CustomValueKind::Bool,
+        }
+    }
+
+    fn as_attr_value(&self) -> AttrValue<'_> {
+        match self {
+            Self::Addr(addr) => AttrValue::Addr(*addr),
+            Self::Port(port) => AttrValue::UInt((*port).into()),
+            Self::String(s) => AttrValue::String(s),
+            Self::Integer(i) => AttrValue::SInt(*i),
+            Self::UInteger(u) => AttrValue::UInt(*u),
+            Self::Float(f) => AttrValue::Float(*f),
+            Self::Bool(b) => AttrValue::Bool(*b),
+        }
+    }
+}
+
+impl fmt::Display for CustomValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Addr(addr) => addr.fmt(f),
+            Self::Port(port) => port.fmt(f),
+            Self::String(s) => s.fmt(f),
+            Self::Integer(i) => i.fmt(f),
+            Self::UInteger(u) => u.fmt(f),
+            Self::Float(v) => v.fmt(f),
+            Self::Bool(b) => b.fmt(f),
+        }
+    }
+}
+
+/// The fields of an event reported by a detector without a kind of its own. The attributes are
+/// described by the registered `CustomEventSchema` with the given name and version.
+pub type CustomEventFields = CustomEventFieldsV0_43;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CustomEventFieldsV0_43 {
+    pub sensor: String,
+    pub schema: String,
+    pub schema_version: u32,
+    pub src_addrs: Vec<IpAddr>,
+    pub src_port: u16,
+    pub dst_addrs: Vec<IpAddr>,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub level: NonZeroU8,
+    /// The names and values of the attributes defined by the schema.
+    pub attributes: Vec<(String, CustomValue)>,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+}
+
+impl CustomEventFields {
+    #[must_use]
+    pub fn syslog_rfc5424(&self) -> String {
+        format!(
+            "category={:?} sensor={:?} schema={:?} schema_version={:?} src_addrs={:?} src_port={:?} dst_addrs={:?} dst_port={:?} proto={:?} level={:?}{} confidence={:?}",
+            self.category.as_ref().map_or_else(
+                || "Unspecified".to_string(),
+                std::string::ToString::to_string
+            ),
+            self.sensor,
+            self.schema,
+            self.schema_version.to_string(),
+            vector_to_string(&self.src_addrs),
+            self.src_port.to_string(),
+            vector_to_string(&self.dst_addrs),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            self.level.to_string(),
+            attributes_to_string(&self.attributes),
+            self.confidence.to_string()
+        )
+    }
+}
+
+/// Formats the attributes as space-separated `name="value"` pairs, each preceded by a space.
+fn attributes_to_string(attributes: &[(String, CustomValue)]) -> String {
+    attributes
+        .iter()
+        .map(|(name, value)| format!(" {name}={:?}", value.to_string()))
+        .collect()
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CustomEvent {
+    pub time: DateTime<Utc>,
+    pub sensor: String,
+    pub schema: String,
+    pub schema_version: u32,
+    pub src_addrs: Vec<IpAddr>,
+    pub src_port: u16,
+    pub dst_addrs: Vec<IpAddr>,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub level: NonZeroU8,
+    pub attributes: Vec<(String, CustomValue)>,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+    pub triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl fmt::Display for CustomEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor={:?} schema={:?} schema_version={:?} src_addrs={:?} src_port={:?} dst_addrs={:?} dst_port={:?} proto={:?} level={:?}{} triage_scores={:?}",
+            self.sensor,
+            self.schema,
+            self.schema_version.to_string(),
+            vector_to_string(&self.src_addrs),
+            self.src_port.to_string(),
+            vector_to_string(&self.dst_addrs),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            self.level.to_string(),
+            attributes_to_string(&self.attributes),
+            triage_scores_to_string(self.triage_scores.as_ref())
+        )
+    }
+}
+
+impl CustomEvent {
+    pub(super) fn new(time: DateTime<Utc>, fields: CustomEventFields) -> Self {
+        Self {
+            time,
+            sensor: fields.sensor,
+            schema: fields.schema,
+            schema_version: fields.schema_version,
+            src_addrs: fields.src_addrs,
+            src_port: fields.src_port,
+            dst_addrs: fields.dst_addrs,
+            dst_port: fields.dst_port,
+            proto: fields.proto,
+            level: fields.level,
+            attributes: fields.attributes,
+            confidence: fields.confidence,
+            category: fields.category,
+            triage_scores: None,
+        }
+    }
+
+    /// Returns the value of the attribute with the given name.
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&CustomValue> {
+        self.attributes
+            .iter()
+            .find_map(|(n, value)| (n == name).then_some(value))
+    }
+}
+
+impl Match for CustomEvent {
+    fn src_addrs(&self) -> &[IpAddr] {
+        &self.src_addrs
+    }
+
+    fn src_port(&self) -> u16 {
+        self.src_port
+    }
+
+    fn dst_addrs(&self) -> &[IpAddr] {
+        &self.dst_addrs
+    }
+
+    fn dst_port(&self) -> u16 {
+        self.dst_port
+    }
+
+    fn proto(&self) -> u8 {
+        self.proto
+    }
+
+    fn category(&self) -> Option<EventCategory> {
+        self.category
+    }
+
+    fn detector_level(&self) -> NonZeroU8 {
+        self.level
+    }
+
+    /// Returns the name of the schema, so that custom events can be filtered by schema.
+    fn kind(&self) -> &str {
+        &self.schema
+    }
+
+    fn event_kind(&self) -> EventKind {
+        EventKind::CustomEvent
+    }
+
+    fn sensor(&self) -> &str {
+        self.sensor.as_str()
+    }
+
+    fn confidence(&self) -> Option<f32> {
+        Some(self.confidence)
+    }
+
+    fn learning_method(&self) -> LearningMethod {
+        LearningMethod::SemiSupervised
+    }
+
+    fn find_attr_by_kind(&self, _raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>> {
+        None
+    }
+
+    /// Looks up the attribute that the triage rule refers to as `<schema>.<attribute>`, if the
+    /// schema is the one of this event.
+    fn find_attr(&self, packet_attr: &PacketAttr) -> Option<AttrValue<'_>> {
+        let (schema, name) = packet_attr.custom_attr()?;
+        if schema != self.schema {
+            return None;
+        }
+        self.attribute(name).map(CustomValue::as_attr_value)
+    }
+}
@@ -4,7 +4,7 @@ use attrievent::attribute::RawEventAttrKind;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 pub type BlocklistDceRpcFields = BlocklistDceRpcFieldsV0_42;
@@ -164,7 +164,7 @@ impl Match for BlocklistDceRpc {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -172,6 +172,10 @@ impl Match for BlocklistDceRpc {
         "blocklist dcerpc"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistDceRpc
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
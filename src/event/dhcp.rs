@@ -5,7 +5,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    EventCategory, LearningMethod, MEDIUM, TriageScore,
+    EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore,
     common::{AttrValue, Match},
 };
 use crate::event::common::{to_hardware_address, triage_scores_to_string, vector_to_string};
@@ -287,7 +287,7 @@ impl Match for BlocklistDhcp {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -295,6 +295,10 @@ impl Match for BlocklistDhcp {
         "blocklist dhcp"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistDhcp
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -5,7 +5,7 @@ use attrievent::attribute::{DnsAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, HIGH, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, HIGH, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::{
     TriageExclusion,
     event::common::{AttrValue, triage_scores_to_string, vector_to_string},
@@ -241,7 +241,7 @@ impl Match for DnsCovertChannel {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -249,6 +249,10 @@ impl Match for DnsCovertChannel {
         "dns covert channel"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::DnsCovertChannel
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -413,7 +417,7 @@ impl Match for LockyRansomware {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         HIGH
     }
 
@@ -421,6 +425,10 @@ impl Match for LockyRansomware {
         "locky ransomware"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::LockyRansomware
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -661,7 +669,7 @@ impl Match for CryptocurrencyMiningPool {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -669,6 +677,10 @@ impl Match for CryptocurrencyMiningPool {
         "cryptocurrency mining pool"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::CryptocurrencyMiningPool
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -904,7 +916,7 @@ impl Match for BlocklistDns {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -912,6 +924,10 @@ impl Match for BlocklistDns {
         "blocklist dns"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistDns
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
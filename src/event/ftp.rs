@@ -5,7 +5,7 @@ use attrievent::attribute::{FtpAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::{
     event::common::{AttrValue, triage_scores_to_string},
     types::EventCategoryV0_41,
@@ -270,7 +270,7 @@ impl Match for FtpBruteForce {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -278,6 +278,10 @@ impl Match for FtpBruteForce {
         "ftp brute force"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::FtpBruteForce
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -473,7 +477,7 @@ impl Match for FtpPlainText {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -481,6 +485,10 @@ impl Match for FtpPlainText {
         "ftp plain text"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::FtpPlainText
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -604,7 +612,7 @@ impl Match for BlocklistFtp {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -612,6 +620,10 @@ impl Match for BlocklistFtp {
         "blocklist ftp"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistFtp
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -5,7 +5,9 @@ use attrievent::attribute::{HttpAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc, serde::ts_nanoseconds};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, EventFilter, LOW, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{
+    EventCategory, EventFilter, EventKind, LOW, LearningMethod, MEDIUM, TriageScore, common::Match,
+};
 use crate::{
     TriageExclusion,
     event::common::{AttrValue, triage_scores_to_string},
@@ -268,6 +270,10 @@ impl Match for RepeatedHttpSessions {
         "repeated http sessions"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::RepeatedHttpSessions
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -580,7 +586,7 @@ impl Match for HttpThreat {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         LOW
     }
 
@@ -588,6 +594,14 @@ impl Match for HttpThreat {
         "http threat"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::HttpThreat
+    }
+
+    fn rule(&self) -> Option<(&str, u32)> {
+        Some((&self.db_name, self.rule_id))
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -885,7 +899,7 @@ impl Match for DomainGenerationAlgorithm {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         LOW
     }
 
@@ -893,6 +907,10 @@ impl Match for DomainGenerationAlgorithm {
         "dga"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::DomainGenerationAlgorithm
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -1075,7 +1093,7 @@ impl Match for NonBrowser {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -1083,6 +1101,10 @@ impl Match for NonBrowser {
         "non browser"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::NonBrowser
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -1269,7 +1291,7 @@ impl Match for BlocklistHttp {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -1277,6 +1299,10 @@ impl Match for BlocklistHttp {
         "blocklist http"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistHttp
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
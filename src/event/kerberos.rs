@@ -4,7 +4,7 @@ use attrievent::attribute::{KerberosAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 macro_rules! find_kerberos_attr_by_kind {
@@ -216,7 +216,7 @@ impl Match for BlocklistKerberos {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -224,6 +224,10 @@ impl Match for BlocklistKerberos {
         "blocklist kerberos"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistKerberos
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -240,3 +244,369 @@ impl Match for BlocklistKerberos {
         find_kerberos_attr_by_kind!(self, raw_event_attr)
     }
 }
+
+pub type KerberoastingFields = KerberoastingFieldsV0_43;
+
+/// The fields of a burst of TGS requests from a host for many service tickets, most of them with
+/// RC4 encryption, which are requested to crack the passwords of service accounts offline.
+#[derive(Serialize, Deserialize)]
+pub struct KerberoastingFieldsV0_43 {
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addr: IpAddr,
+    pub dst_port: u16,
+    pub proto: u8,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub start_time: i64,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub end_time: i64,
+    pub client_realm: String,
+    pub client_name: Vec<String>,
+    pub realm: String,
+    /// The service principal names whose tickets were requested.
+    pub service_names: Vec<String>,
+    /// The number of TGS requests in the time window.
+    pub request_count: u64,
+    /// The number of TGS requests for tickets with RC4 encryption.
+    pub rc4_request_count: u64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+}
+
+impl KerberoastingFields {
+    #[must_use]
+    pub fn syslog_rfc5424(&self) -> String {
+        let start_time_dt = DateTime::from_timestamp_nanos(self.start_time);
+        let end_time_dt = DateTime::from_timestamp_nanos(self.end_time);
+        format!(
+            "category={:?} sensor={:?} src_addr={:?} dst_addr={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} client_realm={:?} client_name={:?} realm={:?} service_names={:?} request_count={:?} rc4_request_count={:?} confidence={:?}",
+            self.category.as_ref().map_or_else(
+                || "Unspecified".to_string(),
+                std::string::ToString::to_string
+            ),
+            self.sensor,
+            self.src_addr.to_string(),
+            self.dst_addr.to_string(),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            start_time_dt.to_rfc3339(),
+            end_time_dt.to_rfc3339(),
+            self.client_realm,
+            self.client_name.join(","),
+            self.realm,
+            self.service_names.join(","),
+            self.request_count.to_string(),
+            self.rc4_request_count.to_string(),
+            self.confidence.to_string()
+        )
+    }
+}
+
+pub struct Kerberoasting {
+    pub time: DateTime<Utc>,
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addr: IpAddr,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub client_realm: String,
+    pub client_name: Vec<String>,
+    pub realm: String,
+    pub service_names: Vec<String>,
+    pub request_count: u64,
+    pub rc4_request_count: u64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+    pub triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl fmt::Display for Kerberoasting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor={:?} src_addr={:?} dst_addr={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} client_realm={:?} client_name={:?} realm={:?} service_names={:?} request_count={:?} rc4_request_count={:?} triage_scores={:?}",
+            self.sensor,
+            self.src_addr.to_string(),
+            self.dst_addr.to_string(),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            self.start_time.to_rfc3339(),
+            self.end_time.to_rfc3339(),
+            self.client_realm,
+            self.client_name.join(","),
+            self.realm,
+            self.service_names.join(","),
+            self.request_count.to_string(),
+            self.rc4_request_count.to_string(),
+            triage_scores_to_string(self.triage_scores.as_ref())
+        )
+    }
+}
+
+impl Kerberoasting {
+    pub(super) fn new(time: DateTime<Utc>, fields: KerberoastingFields) -> Self {
+        Self {
+            time,
+            sensor: fields.sensor,
+            src_addr: fields.src_addr,
+            dst_addr: fields.dst_addr,
+            dst_port: fields.dst_port,
+            proto: fields.proto,
+            start_time: DateTime::from_timestamp_nanos(fields.start_time),
+            end_time: DateTime::from_timestamp_nanos(fields.end_time),
+            client_realm: fields.client_realm,
+            client_name: fields.client_name,
+            realm: fields.realm,
+            service_names: fields.service_names,
+            request_count: fields.request_count,
+            rc4_request_count: fields.rc4_request_count,
+            confidence: fields.confidence,
+            category: fields.category,
+            triage_scores: None,
+        }
+    }
+}
+
+impl Match for Kerberoasting {
+    fn src_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.src_addr)
+    }
+
+    fn src_port(&self) -> u16 {
+        0
+    }
+
+    fn dst_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.dst_addr)
+    }
+
+    fn dst_port(&self) -> u16 {
+        self.dst_port
+    }
+
+    fn proto(&self) -> u8 {
+        self.proto
+    }
+
+    fn category(&self) -> Option<EventCategory> {
+        self.category
+    }
+
+    fn detector_level(&self) -> NonZeroU8 {
+        MEDIUM
+    }
+
+    fn kind(&self) -> &'static str {
+        "kerberoasting"
+    }
+
+    fn event_kind(&self) -> EventKind {
+        EventKind::Kerberoasting
+    }
+
+    fn sensor(&self) -> &str {
+        self.sensor.as_str()
+    }
+
+    fn confidence(&self) -> Option<f32> {
+        Some(self.confidence)
+    }
+
+    fn learning_method(&self) -> LearningMethod {
+        LearningMethod::SemiSupervised
+    }
+
+    fn find_attr_by_kind(&self, raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>> {
+        if let RawEventAttrKind::Kerberos(attr) = raw_event_attr {
+            match attr {
+                KerberosAttr::SrcAddr => Some(AttrValue::Addr(self.src_addr)),
+                KerberosAttr::DstAddr => Some(AttrValue::Addr(self.dst_addr)),
+                KerberosAttr::DstPort => Some(AttrValue::UInt(self.dst_port.into())),
+                KerberosAttr::Proto => Some(AttrValue::UInt(self.proto.into())),
+                KerberosAttr::ClientRealm => Some(AttrValue::String(&self.client_realm)),
+                KerberosAttr::ClientName => Some(AttrValue::VecString(&self.client_name)),
+                KerberosAttr::Realm => Some(AttrValue::String(&self.realm)),
+                KerberosAttr::ServiceName => Some(AttrValue::VecString(&self.service_names)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+pub type AsRepRoastingFields = AsRepRoastingFieldsV0_43;
+
+/// The fields of AS requests from a host for accounts that do not require Kerberos
+/// pre-authentication, whose AS replies can be cracked offline.
+#[derive(Serialize, Deserialize)]
+pub struct AsRepRoastingFieldsV0_43 {
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addr: IpAddr,
+    pub dst_port: u16,
+    pub proto: u8,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub start_time: i64,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub end_time: i64,
+    pub realm: String,
+    /// The accounts whose AS replies were returned without pre-authentication.
+    pub accounts: Vec<String>,
+    /// The number of AS requests in the time window.
+    pub request_count: u64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+}
+
+impl AsRepRoastingFields {
+    #[must_use]
+    pub fn syslog_rfc5424(&self) -> String {
+        let start_time_dt = DateTime::from_timestamp_nanos(self.start_time);
+        let end_time_dt = DateTime::from_timestamp_nanos(self.end_time);
+        format!(
+            "category={:?} sensor={:?} src_addr={:?} dst_addr={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} realm={:?} accounts={:?} request_count={:?} confidence={:?}",
+            self.category.as_ref().map_or_else(
+                || "Unspecified".to_string(),
+                std::string::ToString::to_string
+            ),
+            self.sensor,
+            self.src_addr.to_string(),
+            self.dst_addr.to_string(),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            start_time_dt.to_rfc3339(),
+            end_time_dt.to_rfc3339(),
+            self.realm,
+            self.accounts.join(","),
+            self.request_count.to_string(),
+            self.confidence.to_string()
+        )
+    }
+}
+
+pub struct AsRepRoasting {
+    pub time: DateTime<Utc>,
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addr: IpAddr,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub realm: String,
+    pub accounts: Vec<String>,
+    pub request_count: u64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+    pub triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl fmt::Display for AsRepRoasting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor={:?} src_addr={:?} dst_addr={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} realm={:?} accounts={:?} request_count={:?} triage_scores={:?}",
+            self.sensor,
+            self.src_addr.to_string(),
+            self.dst_addr.to_string(),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            self.start_time.to_rfc3339(),
+            self.end_time.to_rfc3339(),
+            self.realm,
+            self.accounts.join(","),
+            self.request_count.to_string(),
+            triage_scores_to_string(self.triage_scores.as_ref())
+        )
+    }
+}
+
+impl AsRepRoasting {
+    pub(super) fn new(time: DateTime<Utc>, fields: AsRepRoastingFields) -> Self {
+        Self {
+            time,
+            sensor: fields.sensor,
+            src_addr: fields.src_addr,
+            dst_addr: fields.dst_addr,
+            dst_port: fields.dst_port,
+            proto: fields.proto,
+            start_time: DateTime::from_timestamp_nanos(fields.start_time),
+            end_time: DateTime::from_timestamp_nanos(fields.end_time),
+            realm: fields.realm,
+            accounts: fields.accounts,
+            request_count: fields.request_count,
+            confidence: fields.confidence,
+            category: fields.category,
+            triage_scores: None,
+        }
+    }
+}
+
+impl Match for AsRepRoasting {
+    fn src_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.src_addr)
+    }
+
+    fn src_port(&self) -> u16 {
+        0
+    }
+
+    fn dst_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.dst_addr)
+    }
+
+    fn dst_port(&self) -> u16 {
+        self.dst_port
+    }
+
+    fn proto(&self) -> u8 {
+        self.proto
+    }
+
+    fn category(&self) -> Option<EventCategory> {
+        self.category
+    }
+
+    fn detector_level(&self) -> NonZeroU8 {
+        MEDIUM
+    }
+
+    fn kind(&self) -> &'static str {
+        "as-rep roasting"
+    }
+
+    fn event_kind(&self) -> EventKind {
+        EventKind::AsRepRoasting
+    }
+
+    fn sensor(&self) -> &str {
+        self.sensor.as_str()
+    }
+
+    fn confidence(&self) -> Option<f32> {
+        Some(self.confidence)
+    }
+
+    fn learning_method(&self) -> LearningMethod {
+        LearningMethod::SemiSupervised
+    }
+
+    fn find_attr_by_kind(&self, raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>> {
+        if let RawEventAttrKind::Kerberos(attr) = raw_event_attr {
+            match attr {
+                KerberosAttr::SrcAddr => Some(AttrValue::Addr(self.src_addr)),
+                KerberosAttr::DstAddr => Some(AttrValue::Addr(self.dst_addr)),
+                KerberosAttr::DstPort => Some(AttrValue::UInt(self.dst_port.into())),
+                KerberosAttr::Proto => Some(AttrValue::UInt(self.proto.into())),
+                KerberosAttr::ClientName => Some(AttrValue::VecString(&self.accounts)),
+                KerberosAttr::Realm => Some(AttrValue::String(&self.realm)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
@@ -5,7 +5,7 @@ use attrievent::attribute::{LdapAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 macro_rules! find_ldap_attr_by_kind {
@@ -163,7 +163,7 @@ impl Match for LdapBruteForce {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -171,6 +171,10 @@ impl Match for LdapBruteForce {
         "ldap brute force"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::LdapBruteForce
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -371,7 +375,7 @@ impl Match for LdapPlainText {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -379,6 +383,10 @@ impl Match for LdapPlainText {
         "ldap plain text"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::LdapPlainText
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -507,7 +515,7 @@ impl Match for BlocklistLdap {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -515,6 +523,10 @@ impl Match for BlocklistLdap {
         "blocklist ldap"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistLdap
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -9,9 +9,22 @@ use attrievent::attribute::{LogAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc, serde::ts_nanoseconds};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
+/// The fields extracted from the content of a log by the extraction template for its service.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct LogFields {
+    pub src_addr: Option<IpAddr>,
+    pub src_port: Option<u16>,
+    pub dst_addr: Option<IpAddr>,
+    pub dst_port: Option<u16>,
+    pub user: Option<String>,
+    pub action: Option<String>,
+    /// The names and values of the fields other than the above.
+    pub others: Vec<(String, String)>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ExtraThreat {
     #[serde(with = "ts_nanoseconds")]
@@ -26,6 +39,8 @@ pub struct ExtraThreat {
     pub attack_kind: String,
     pub confidence: f32,
     pub category: Option<EventCategory>,
+    /// The fields extracted from `content`, if there is an extraction template for `service`.
+    pub fields: Option<LogFields>,
     pub triage_scores: Option<Vec<TriageScore>>,
 }
 
@@ -70,21 +85,55 @@ impl fmt::Display for ExtraThreat {
     }
 }
 
+impl ExtraThreat {
+    /// Returns the source address extracted from the content, if any.
+    #[must_use]
+    pub fn src_addr(&self) -> Option<IpAddr> {
+        self.fields.as_ref().and_then(|fields| fields.src_addr)
+    }
+
+    /// Returns the destination address extracted from the content, if any.
+    #[must_use]
+    pub fn dst_addr(&self) -> Option<IpAddr> {
+        self.fields.as_ref().and_then(|fields| fields.dst_addr)
+    }
+}
+
 impl Match for ExtraThreat {
     fn src_addrs(&self) -> &[IpAddr] {
-        std::slice::from_ref(&IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+        match self
+            .fields
+            .as_ref()
+            .and_then(|fields| fields.src_addr.as_ref())
+        {
+            Some(addr) => std::slice::from_ref(addr),
+            None => std::slice::from_ref(&IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        }
     }
 
     fn src_port(&self) -> u16 {
-        0
+        self.fields
+            .as_ref()
+            .and_then(|fields| fields.src_port)
+            .unwrap_or_default()
     }
 
     fn dst_addrs(&self) -> &[IpAddr] {
-        std::slice::from_ref(&IpAddr::V4(Ipv4Addr::UNSPECIFIED))
+        match self
+            .fields
+            .as_ref()
+            .and_then(|fields| fields.dst_addr.as_ref())
+        {
+            Some(addr) => std::slice::from_ref(addr),
+            None => std::slice::from_ref(&IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        }
     }
 
     fn dst_port(&self) -> u16 {
-        0
+        self.fields
+            .as_ref()
+            .and_then(|fields| fields.dst_port)
+            .unwrap_or_default()
     }
 
     fn proto(&self) -> u8 {
@@ -95,7 +144,7 @@ impl Match for ExtraThreat {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -103,6 +152,14 @@ impl Match for ExtraThreat {
         "extra threat"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::ExtraThreat
+    }
+
+    fn rule(&self) -> Option<(&str, u32)> {
+        Some((&self.db_name, self.rule_id))
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -4,7 +4,7 @@ use attrievent::attribute::{MqttAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 macro_rules! find_mqtt_attr_by_kind {
@@ -205,7 +205,7 @@ impl Match for BlocklistMqtt {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -213,6 +213,10 @@ impl Match for BlocklistMqtt {
         "blocklist mqtt"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistMqtt
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
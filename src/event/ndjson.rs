@@ -0,0 +1,207 @@
+//! Bulk import of events in newline-delimited JSON.
+
+use std::io::BufRead;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use super::{EventDb, EventKind, EventMessage, FieldsVisitor, decode};
+use crate::{EventEnrichment, Table};
+
+/// The number of events written in a transaction.
+pub(super) const BATCH_SIZE: usize = 1000;
+
+/// A line rejected by `EventDb::import_ndjson`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RejectedLine {
+    /// The line number, starting from 1.
+    pub line: usize,
+    pub reason: String,
+}
+
+/// The result of `EventDb::import_ndjson`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ImportReport {
+    /// The number of events stored.
+    pub accepted: usize,
+    pub rejected: Vec<RejectedLine>,
+}
+
+/// An event as represented in JSON, with the fields as an object whose keys are the names of the
+/// fields of the kind's fields struct.
+#[derive(Deserialize)]
+struct JsonEvent {
+    kind: EventKind,
+    time: DateTime<Utc>,
+    fields: Value,
+}
+
+/// Converts the fields in JSON into the stored fields, rejecting fields not in the fields struct.
+struct FromJson(Value);
+
+impl FieldsVisitor for FromJson {
+    type Output = Result<Vec<u8>>;
+
+    fn visit<T: Serialize + DeserializeOwned>(self) -> Self::Output {
+        let fields: T = serde_json::from_value(self.0.clone())?;
+        if let (Value::Object(given), Value::Object(known)) =
+            (&self.0, serde_json::to_value(&fields)?)
+            && let Some(name) = given.keys().find(|name| !known.contains_key(*name))
+        {
+            bail!("unknown field `{name}`");
+        }
+        Ok(bincode::serialize(&fields)?)
+    }
+}
+
+impl EventDb<'_> {
+    /// Stores the events in newline-delimited JSON, one event per line in the form of
+    /// `{"kind": "PortScan", "time": "2025-01-01T00:00:00Z", "fields": {...}}`. Blank lines are
+    /// skipped.
+    ///
+    /// Each event is checked against the fields struct of its kind as `put` does, and stored with
+    /// a key from its original time along with its enrichment. Lines that fail the check are
+    /// reported as rejected, and the others are stored in batches. Unlike `put`, the events are neither counted in the ingest
+    /// statistics of their sensors nor correlated into incidents, since they are not new.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading from `reader` or a database operation fails. The batches
+    /// stored before the error are kept.
+    pub fn import_ndjson<R: BufRead>(&self, reader: R) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        for (index, line) in reader.lines().enumerate() {
+            let line = line.context("cannot read event")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match self.parse_json(&line) {
+                Ok(event) => batch.push(event),
+                Err(e) => report.rejected.push(RejectedLine {
+                    line: index + 1,
+                    reason: format!("{e:#}"),
+                }),
+            }
+            if batch.len() == BATCH_SIZE {
+                report.accepted += self.put_batch(&batch)?;
+                batch.clear();
+            }
+        }
+        report.accepted += self.put_batch(&batch)?;
+        Ok(report)
+    }
+
+    /// Parses an event in JSON, and returns the base of its key and the fields to store.
+    fn parse_json(&self, line: &str) -> Result<(i128, Vec<u8>)> {
+        let event: JsonEvent = serde_json::from_str(line).context("invalid event")?;
+        let fields = event
+            .kind
+            .visit_fields(FromJson(event.fields))
+            .with_context(|| format!("invalid {:?} fields", event.kind))?;
+        let message = EventMessage {
+            time: event.time,
+            kind: event.kind,
+            fields,
+        };
+        let value = self.prepare(&message)?.into_owned();
+        Ok((Self::base_key(&message)?, value))
+    }
+
+    /// Stores the events along with their enrichments in a transaction. Returns the number of
+    /// events stored.
+    pub(super) fn put_batch(&self, events: &[(i128, Vec<u8>)]) -> Result<usize> {
+        if events.is_empty() {
+            return Ok(0);
+        }
+        let enrichments =
+            Table::<EventEnrichment>::open(self.inner).context("no event enrichments table")?;
+        let mut enriched = events
+            .iter()
+            .map(|(base, value)| {
+                decode(base.to_be_bytes().into(), value.as_slice().into())
+                    .ok()
+                    .map(|(_, event)| self.enrichment_of(*base, &event))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        loop {
+            let txn = self.inner.transaction();
+            for ((base, value), enrichment) in events.iter().zip(&mut enriched) {
+                let key = Self::free_key(&txn, *base)?;
+                txn.put(key.to_be_bytes(), value)
+                    .context("cannot write event")?;
+                if let Some(enrichment) = enrichment {
+                    enrichment.key = key;
+                    enrichments.put_with_transaction(enrichment, &txn)?;
+                }
+            }
+            match txn.commit() {
+                Ok(()) => return Ok(events.len()),
+                Err(e) => {
+                    if !e.as_ref().starts_with("Resource busy:") {
+                        return Err(e).context("failed to store events");
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::Store;
+
+    #[test]
+    fn import_ndjson() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+
+        let fields = r#"{"sensor":"collector1","src_addr":"10.0.0.1","dst_addr":"10.0.0.2","dst_ports":[22,80],"start_time":0,"end_time":0,"proto":6,"confidence":0.3,"category":"Reconnaissance"}"#;
+        let event = |fields: &str| {
+            format!(r#"{{"kind":"PortScan","time":"2025-01-01T00:00:00Z","fields":{fields}}}"#)
+        };
+        let input = [
+            event(fields),
+            String::new(),
+            "not json".to_string(),
+            r#"{"kind":"NoSuchKind","time":"2025-01-01T00:00:00Z","fields":{}}"#.to_string(),
+            event(&fields.replace(r#""proto":6"#, r#""proto":"tcp""#)),
+            event(&fields.replace(r#""proto":6"#, r#""proto":6,"extra":1"#)),
+            event(fields),
+        ]
+        .join("\n");
+
+        let db = store.events();
+        let report = db.import_ndjson(input.as_bytes()).unwrap();
+        assert_eq!(report.accepted, 2);
+        assert_eq!(
+            report
+                .rejected
+                .iter()
+                .map(|rejected| rejected.line)
+                .collect::<Vec<_>>(),
+            vec![3, 4, 5, 6]
+        );
+        assert!(report.rejected[3].reason.contains("unknown field `extra`"));
+
+        let time = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let events: Vec<_> = db.iter_forward().map(|event| event.unwrap()).collect();
+        assert_eq!(events.len(), 2);
+        assert_ne!(events[0].0, events[1].0);
+        for (key, event) in &events {
+            assert_eq!(key >> 64, i128::from(time.timestamp_nanos_opt().unwrap()));
+            assert!(event.to_string().contains(r#"dst_ports="22,80""#));
+            let enrichment = db.enrichment(*key).unwrap().unwrap();
+            assert_eq!(enrichment.src.len(), 1);
+            assert_eq!(enrichment.dst.len(), 1);
+        }
+    }
+}
@@ -5,7 +5,7 @@ use attrievent::attribute::{NetworkAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc, serde::ts_nanoseconds};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 // TODO: We plan to implement the triage feature after detection events from other network
@@ -145,7 +145,7 @@ impl Match for NetworkThreat {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -153,6 +153,14 @@ impl Match for NetworkThreat {
         "network threat"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::NetworkThreat
+    }
+
+    fn rule(&self) -> Option<(&str, u32)> {
+        Some((&self.db_name, self.rule_id))
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
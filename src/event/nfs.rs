@@ -4,7 +4,7 @@ use attrievent::attribute::{NfsAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 macro_rules! find_nfs_attr_by_kind {
@@ -173,7 +173,7 @@ impl Match for BlocklistNfs {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -181,6 +181,10 @@ impl Match for BlocklistNfs {
         "blocklist nfs"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistNfs
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
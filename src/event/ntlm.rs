@@ -4,8 +4,8 @@ use attrievent::attribute::{NtlmAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
-use crate::event::common::{AttrValue, triage_scores_to_string};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
+use crate::event::common::{AttrValue, triage_scores_to_string, vector_to_string};
 
 macro_rules! find_ntlm_attr_by_kind {
     ($event: expr, $raw_event_attr: expr) => {{
@@ -190,7 +190,7 @@ impl Match for BlocklistNtlm {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -198,6 +198,10 @@ impl Match for BlocklistNtlm {
         "blocklist ntlm"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistNtlm
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -214,3 +218,191 @@ impl Match for BlocklistNtlm {
         find_ntlm_attr_by_kind!(self, raw_event_attr)
     }
 }
+
+pub type NtlmPasswordSprayFields = NtlmPasswordSprayFieldsV0_43;
+
+/// The fields of NTLM authentication attempts from a host against many accounts, each with a few
+/// common passwords, to avoid locking out any single account.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Serialize, Deserialize)]
+pub struct NtlmPasswordSprayFieldsV0_43 {
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addrs: Vec<IpAddr>,
+    pub dst_port: u16,
+    pub proto: u8,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub start_time: i64,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub end_time: i64,
+    pub protocol: String,
+    pub domainname: String,
+    /// The accounts attempted.
+    pub usernames: Vec<String>,
+    /// The number of authentication attempts in the time window.
+    pub attempts: u64,
+    /// The number of failed authentication attempts.
+    pub failures: u64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+}
+
+impl NtlmPasswordSprayFields {
+    #[must_use]
+    pub fn syslog_rfc5424(&self) -> String {
+        let start_time_dt = DateTime::from_timestamp_nanos(self.start_time);
+        let end_time_dt = DateTime::from_timestamp_nanos(self.end_time);
+        format!(
+            "category={:?} sensor={:?} src_addr={:?} dst_addrs={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} protocol={:?} domainname={:?} usernames={:?} attempts={:?} failures={:?} confidence={:?}",
+            self.category.as_ref().map_or_else(
+                || "Unspecified".to_string(),
+                std::string::ToString::to_string
+            ),
+            self.sensor,
+            self.src_addr.to_string(),
+            vector_to_string(&self.dst_addrs),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            start_time_dt.to_rfc3339(),
+            end_time_dt.to_rfc3339(),
+            self.protocol,
+            self.domainname,
+            self.usernames.join(","),
+            self.attempts.to_string(),
+            self.failures.to_string(),
+            self.confidence.to_string()
+        )
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct NtlmPasswordSpray {
+    pub time: DateTime<Utc>,
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addrs: Vec<IpAddr>,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub protocol: String,
+    pub domainname: String,
+    pub usernames: Vec<String>,
+    pub attempts: u64,
+    pub failures: u64,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+    pub triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl fmt::Display for NtlmPasswordSpray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor={:?} src_addr={:?} dst_addrs={:?} dst_port={:?} proto={:?} start_time={:?} end_time={:?} protocol={:?} domainname={:?} usernames={:?} attempts={:?} failures={:?} triage_scores={:?}",
+            self.sensor,
+            self.src_addr.to_string(),
+            vector_to_string(&self.dst_addrs),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            self.start_time.to_rfc3339(),
+            self.end_time.to_rfc3339(),
+            self.protocol,
+            self.domainname,
+            self.usernames.join(","),
+            self.attempts.to_string(),
+            self.failures.to_string(),
+            triage_scores_to_string(self.triage_scores.as_ref())
+        )
+    }
+}
+
+impl NtlmPasswordSpray {
+    pub(super) fn new(time: DateTime<Utc>, fields: NtlmPasswordSprayFields) -> Self {
+        Self {
+            time,
+            sensor: fields.sensor,
+            src_addr: fields.src_addr,
+            dst_addrs: fields.dst_addrs,
+            dst_port: fields.dst_port,
+            proto: fields.proto,
+            start_time: DateTime::from_timestamp_nanos(fields.start_time),
+            end_time: DateTime::from_timestamp_nanos(fields.end_time),
+            protocol: fields.protocol,
+            domainname: fields.domainname,
+            usernames: fields.usernames,
+            attempts: fields.attempts,
+            failures: fields.failures,
+            confidence: fields.confidence,
+            category: fields.category,
+            triage_scores: None,
+        }
+    }
+}
+
+impl Match for NtlmPasswordSpray {
+    fn src_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.src_addr)
+    }
+
+    fn src_port(&self) -> u16 {
+        0
+    }
+
+    fn dst_addrs(&self) -> &[IpAddr] {
+        &self.dst_addrs
+    }
+
+    fn dst_port(&self) -> u16 {
+        self.dst_port
+    }
+
+    fn proto(&self) -> u8 {
+        self.proto
+    }
+
+    fn category(&self) -> Option<EventCategory> {
+        self.category
+    }
+
+    fn detector_level(&self) -> NonZeroU8 {
+        MEDIUM
+    }
+
+    fn kind(&self) -> &'static str {
+        "ntlm password spray"
+    }
+
+    fn event_kind(&self) -> EventKind {
+        EventKind::NtlmPasswordSpray
+    }
+
+    fn sensor(&self) -> &str {
+        self.sensor.as_str()
+    }
+
+    fn confidence(&self) -> Option<f32> {
+        Some(self.confidence)
+    }
+
+    fn learning_method(&self) -> LearningMethod {
+        LearningMethod::SemiSupervised
+    }
+
+    fn find_attr_by_kind(&self, raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>> {
+        if let RawEventAttrKind::Ntlm(attr) = raw_event_attr {
+            match attr {
+                NtlmAttr::SrcAddr => Some(AttrValue::Addr(self.src_addr)),
+                NtlmAttr::DstAddr => Some(AttrValue::VecAddr(&self.dst_addrs)),
+                NtlmAttr::DstPort => Some(AttrValue::UInt(self.dst_port.into())),
+                NtlmAttr::Proto => Some(AttrValue::UInt(self.proto.into())),
+                NtlmAttr::Protocol => Some(AttrValue::String(&self.protocol)),
+                NtlmAttr::Username => Some(AttrValue::VecString(&self.usernames)),
+                NtlmAttr::Domainname => Some(AttrValue::String(&self.domainname)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
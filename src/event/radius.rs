@@ -3,7 +3,7 @@ use std::{fmt, net::IpAddr, num::NonZeroU8};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 #[derive(Serialize, Deserialize)]
@@ -212,7 +212,7 @@ impl Match for BlocklistRadius {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -220,6 +220,10 @@ impl Match for BlocklistRadius {
         "blocklist radius"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistRadius
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
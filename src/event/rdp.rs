@@ -7,7 +7,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::{
-    EventCategory, LearningMethod, MEDIUM, TriageScore,
+    EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore,
     common::{Match, vector_to_string},
 };
 use crate::event::common::{AttrValue, triage_scores_to_string};
@@ -139,7 +139,7 @@ impl Match for RdpBruteForce {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -147,6 +147,10 @@ impl Match for RdpBruteForce {
         "rdp brute force"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::RdpBruteForce
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -314,7 +318,7 @@ impl Match for BlocklistRdp {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -322,6 +326,10 @@ impl Match for BlocklistRdp {
         "blocklist rdp"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistRdp
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
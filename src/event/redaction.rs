@@ -0,0 +1,376 @@
+//! Redaction of personal information and credentials in events.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use anyhow::{Result, bail};
+use data_encoding::HEXLOWER;
+use ring::hmac;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use super::{
+    AsRepRoasting, AsRepRoastingFields, BlocklistFtp, BlocklistHttp, BlocklistLdap, BlocklistNtlm,
+    BlocklistNtlmFields, BlocklistRadius, BlocklistRadiusFields, BlocklistRdp, BlocklistRdpFields,
+    CustomEvent, CustomEventFields, CustomValue, DgaFields, DomainGenerationAlgorithm, ExtraThreat,
+    FtpBruteForce, FtpBruteForceFields, FtpEventFields, FtpPlainText, HttpEventFields, HttpThreat,
+    HttpThreatFields, Kerberoasting, KerberoastingFields, LdapBruteForce, LdapBruteForceFields,
+    LdapEventFields, LdapPlainText, LogFields, NonBrowser, NtlmPasswordSpray,
+    NtlmPasswordSprayFields, TorConnection,
+};
+
+/// The value that replaces a masked field.
+const MASK: &str = "********";
+
+/// What to do with the value of a sensitive field.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RedactionAction {
+    /// Leaves the value as it is.
+    #[default]
+    Keep,
+    /// Replaces the value with a fixed mask.
+    Mask,
+    /// Replaces the value with its keyed HMAC-SHA256 digest in hexadecimal, so that equal values
+    /// can still be correlated.
+    Hash,
+    /// Removes the value.
+    Drop,
+}
+
+/// The class of a sensitive field, which determines the action taken on it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum FieldClass {
+    /// User names and account names.
+    Username,
+    /// Passwords, including RADIUS CHAP passwords.
+    Password,
+    /// HTTP cookies and RDP cookies.
+    Cookie,
+    /// HTTP bodies.
+    Body,
+    /// The raw content of logs.
+    Content,
+}
+
+/// The actions to take on each class of sensitive fields: user names, passwords (including
+/// RADIUS CHAP passwords), cookies, HTTP bodies, and the raw content of logs.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RedactionPolicy {
+    pub username: RedactionAction,
+    pub password: RedactionAction,
+    pub cookie: RedactionAction,
+    pub body: RedactionAction,
+    /// The action on the raw content of logs, such as that of `ExtraThreat`, which may hold any
+    /// of the other classes.
+    #[serde(default)]
+    pub content: RedactionAction,
+    /// The classes of the string attributes of custom events, keyed by `<schema>.<attribute>`
+    /// as in triage rules. Attributes not listed here are kept.
+    #[serde(default)]
+    pub custom_attributes: HashMap<String, FieldClass>,
+    /// The key for `RedactionAction::Hash`. It is prepared for hashing when the policy is first
+    /// applied, so changing it afterwards has no effect.
+    pub hmac_key: Vec<u8>,
+    #[serde(skip)]
+    key: OnceLock<hmac::Key>,
+}
+
+impl PartialEq for RedactionPolicy {
+    fn eq(&self, other: &Self) -> bool {
+        self.username == other.username
+            && self.password == other.password
+            && self.cookie == other.cookie
+            && self.body == other.body
+            && self.content == other.content
+            && self.custom_attributes == other.custom_attributes
+            && self.hmac_key == other.hmac_key
+    }
+}
+
+impl RedactionPolicy {
+    /// Checks that the policy can be applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a class is to be hashed but `hmac_key` is empty.
+    pub fn verify(&self) -> Result<()> {
+        let hashed = [
+            self.username,
+            self.password,
+            self.cookie,
+            self.body,
+            self.content,
+        ]
+        .contains(&RedactionAction::Hash);
+        if hashed && self.hmac_key.is_empty() {
+            bail!("HMAC key must not be empty to hash sensitive fields");
+        }
+        Ok(())
+    }
+
+    fn action(&self, class: FieldClass) -> RedactionAction {
+        match class {
+            FieldClass::Username => self.username,
+            FieldClass::Password => self.password,
+            FieldClass::Cookie => self.cookie,
+            FieldClass::Body => self.body,
+            FieldClass::Content => self.content,
+        }
+    }
+
+    pub(super) fn apply<T: Redactable>(&self, class: FieldClass, value: &mut T) {
+        let action = self.action(class);
+        if action != RedactionAction::Keep {
+            let key = self
+                .key
+                .get_or_init(|| hmac::Key::new(hmac::HMAC_SHA256, &self.hmac_key));
+            value.redact_with(action, key);
+        }
+    }
+}
+
+/// A value that can be redacted.
+pub(super) trait Redactable {
+    fn redact_with(&mut self, action: RedactionAction, key: &hmac::Key);
+}
+
+impl Redactable for String {
+    fn redact_with(&mut self, action: RedactionAction, key: &hmac::Key) {
+        if self.is_empty() {
+            return;
+        }
+        match action {
+            RedactionAction::Keep => {}
+            RedactionAction::Mask => *self = MASK.to_string(),
+            RedactionAction::Hash => {
+                *self = HEXLOWER.encode(hmac::sign(key, self.as_bytes()).as_ref())
+            }
+            RedactionAction::Drop => self.clear(),
+        }
+    }
+}
+
+impl Redactable for Vec<u8> {
+    fn redact_with(&mut self, action: RedactionAction, key: &hmac::Key) {
+        if self.is_empty() {
+            return;
+        }
+        match action {
+            RedactionAction::Keep => {}
+            RedactionAction::Mask => *self = MASK.as_bytes().to_vec(),
+            RedactionAction::Hash => {
+                *self = HEXLOWER.encode(hmac::sign(key, self).as_ref()).into_bytes();
+            }
+            RedactionAction::Drop => self.clear(),
+        }
+    }
+}
+
+impl<T: Redactable> Redactable for Option<T> {
+    fn redact_with(&mut self, action: RedactionAction, key: &hmac::Key) {
+        if let Some(value) = self {
+            value.redact_with(action, key);
+        }
+    }
+}
+
+impl Redactable for Vec<String> {
+    fn redact_with(&mut self, action: RedactionAction, key: &hmac::Key) {
+        for value in self.iter_mut() {
+            value.redact_with(action, key);
+        }
+    }
+}
+
+/// An event, or the fields of an event, holding sensitive values.
+pub(super) trait Redact {
+    fn redact(&mut self, policy: &RedactionPolicy);
+}
+
+/// Implements `Redact` for types whose sensitive fields are listed with their classes.
+macro_rules! impl_redact {
+    ($($ty:ty),+ => { $($field:ident: $class:ident),+ $(,)? }) => {
+        $(
+            impl Redact for $ty {
+                fn redact(&mut self, policy: &RedactionPolicy) {
+                    $(policy.apply(FieldClass::$class, &mut self.$field);)+
+                }
+            }
+        )+
+    };
+}
+
+impl_redact!(
+    HttpEventFields, HttpThreatFields, DgaFields, HttpThreat, DomainGenerationAlgorithm,
+    NonBrowser, BlocklistHttp, TorConnection => {
+        username: Username,
+        password: Password,
+        cookie: Cookie,
+        body: Body,
+    }
+);
+impl_redact!(FtpEventFields, FtpPlainText, BlocklistFtp => { user: Username, password: Password });
+impl_redact!(FtpBruteForceFields, FtpBruteForce => { user_list: Username });
+impl_redact!(BlocklistNtlmFields, BlocklistNtlm => { username: Username });
+impl_redact!(NtlmPasswordSprayFields, NtlmPasswordSpray => { usernames: Username });
+impl_redact!(KerberoastingFields, Kerberoasting => { client_name: Username });
+impl_redact!(AsRepRoastingFields, AsRepRoasting => { accounts: Username });
+impl_redact!(BlocklistRadiusFields, BlocklistRadius => {
+    user_name: Username,
+    user_passwd: Password,
+    chap_passwd: Password,
+});
+impl_redact!(BlocklistRdpFields, BlocklistRdp => { cookie: Cookie });
+impl_redact!(LogFields => { user: Username });
+impl_redact!(LdapEventFields, LdapPlainText, BlocklistLdap => {
+    argument: Password,
+    object: Username,
+});
+
+impl Redact for [(String, String)] {
+    fn redact(&mut self, policy: &RedactionPolicy) {
+        for (user, password) in self {
+            policy.apply(FieldClass::Username, user);
+            policy.apply(FieldClass::Password, password);
+        }
+    }
+}
+
+impl Redact for ExtraThreat {
+    fn redact(&mut self, policy: &RedactionPolicy) {
+        policy.apply(FieldClass::Content, &mut self.content);
+        if let Some(fields) = &mut self.fields {
+            fields.redact(policy);
+        }
+    }
+}
+
+impl Redact for CustomEventFields {
+    fn redact(&mut self, policy: &RedactionPolicy) {
+        redact_custom_attributes(&self.schema, &mut self.attributes, policy);
+    }
+}
+
+impl Redact for CustomEvent {
+    fn redact(&mut self, policy: &RedactionPolicy) {
+        redact_custom_attributes(&self.schema, &mut self.attributes, policy);
+    }
+}
+
+/// Redacts the string attributes of a custom event whose classes are given in the policy.
+fn redact_custom_attributes(
+    schema: &str,
+    attributes: &mut [(String, CustomValue)],
+    policy: &RedactionPolicy,
+) {
+    if policy.custom_attributes.is_empty() {
+        return;
+    }
+    for (name, value) in attributes {
+        let CustomValue::String(value) = value else {
+            continue;
+        };
+        if let Some(class) = policy.custom_attributes.get(&format!("{schema}.{name}")) {
+            policy.apply(*class, value);
+        }
+    }
+}
+
+impl Redact for LdapBruteForceFields {
+    fn redact(&mut self, policy: &RedactionPolicy) {
+        self.user_pw_list.redact(policy);
+    }
+}
+
+impl Redact for LdapBruteForce {
+    fn redact(&mut self, policy: &RedactionPolicy) {
+        self.user_pw_list.redact(policy);
+    }
+}
+
+/// Redacts the serialized fields of an event in place.
+///
+/// # Errors
+///
+/// Returns an error if the fields cannot be deserialized as `T`.
+pub(super) fn redact_fields<T>(fields: &mut Vec<u8>, policy: &RedactionPolicy) -> Result<()>
+where
+    T: Redact + Serialize + DeserializeOwned,
+{
+    let mut value: T = bincode::deserialize(fields)?;
+    value.redact(policy);
+    *fields = bincode::serialize(&value)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FieldClass, RedactionAction, RedactionPolicy};
+
+    fn policy(action: RedactionAction) -> RedactionPolicy {
+        RedactionPolicy {
+            username: action,
+            password: action,
+            cookie: action,
+            body: action,
+            content: action,
+            hmac_key: b"secret".to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn redact_string() {
+        let mut value = "alice".to_string();
+        policy(RedactionAction::Keep).apply(FieldClass::Username, &mut value);
+        assert_eq!(value, "alice");
+
+        policy(RedactionAction::Mask).apply(FieldClass::Username, &mut value);
+        assert_eq!(value, "********");
+
+        let mut value = String::new();
+        policy(RedactionAction::Mask).apply(FieldClass::Password, &mut value);
+        assert!(value.is_empty());
+
+        let mut value = "alice".to_string();
+        policy(RedactionAction::Drop).apply(FieldClass::Cookie, &mut value);
+        assert!(value.is_empty());
+    }
+
+    #[test]
+    fn hash_is_keyed_and_stable() {
+        let mut first = "alice".to_string();
+        let mut second = "alice".to_string();
+        let mut other_key = "alice".to_string();
+        policy(RedactionAction::Hash).apply(FieldClass::Username, &mut first);
+        policy(RedactionAction::Hash).apply(FieldClass::Username, &mut second);
+        let mut different = policy(RedactionAction::Hash);
+        different.hmac_key = b"another".to_vec();
+        different.apply(FieldClass::Username, &mut other_key);
+
+        assert_eq!(first, second);
+        assert_ne!(first, "alice");
+        assert_ne!(first, other_key);
+        assert_eq!(first.len(), 64);
+    }
+
+    #[test]
+    fn verify() {
+        assert!(policy(RedactionAction::Hash).verify().is_ok());
+        let mut without_key = policy(RedactionAction::Hash);
+        without_key.hmac_key.clear();
+        assert!(without_key.verify().is_err());
+        without_key.username = RedactionAction::Mask;
+        without_key.password = RedactionAction::Mask;
+        without_key.cookie = RedactionAction::Mask;
+        without_key.body = RedactionAction::Mask;
+        assert!(without_key.verify().is_err());
+        without_key.content = RedactionAction::Mask;
+        assert!(without_key.verify().is_ok());
+    }
+
+    #[test]
+    fn redact_bytes() {
+        let mut body = b"user=alice&password=secret".to_vec();
+        policy(RedactionAction::Mask).apply(FieldClass::Body, &mut body);
+        assert_eq!(body, b"********");
+    }
+}
@@ -4,7 +4,7 @@ use attrievent::attribute::{RawEventAttrKind, SmbAttr};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 macro_rules! find_smb_attr_by_kind {
@@ -226,7 +226,7 @@ impl Match for BlocklistSmb {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -234,6 +234,10 @@ impl Match for BlocklistSmb {
         "blocklist smb"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistSmb
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -4,7 +4,7 @@ use attrievent::attribute::{RawEventAttrKind, SmtpAttr};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 macro_rules! find_smtp_attr_by_kind {
@@ -203,7 +203,7 @@ impl Match for BlocklistSmtp {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -211,6 +211,10 @@ impl Match for BlocklistSmtp {
         "blocklist smtp"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistSmtp
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
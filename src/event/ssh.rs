@@ -4,7 +4,10 @@ use attrievent::attribute::{RawEventAttrKind, SshAttr};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{
+    EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore,
+    common::{Match, vector_to_string},
+};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 macro_rules! find_ssh_attr_by_kind {
@@ -241,7 +244,7 @@ impl Match for BlocklistSsh {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -249,6 +252,10 @@ impl Match for BlocklistSsh {
         "blocklist ssh"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistSsh
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -265,3 +272,178 @@ impl Match for BlocklistSsh {
         find_ssh_attr_by_kind!(self, raw_event_attr)
     }
 }
+
+pub type SshBruteForceFields = SshBruteForceFieldsV0_43;
+
+#[allow(clippy::module_name_repetitions)]
+#[derive(Serialize, Deserialize)]
+pub struct SshBruteForceFieldsV0_43 {
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addrs: Vec<IpAddr>,
+    pub dst_port: u16,
+    pub proto: u8,
+    /// The number of login attempts against each destination, in the order of `dst_addrs`.
+    pub attempts: Vec<u64>,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub start_time: i64,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub end_time: i64,
+    /// The software version string of the client.
+    pub client: String,
+    /// The software version string of the server.
+    pub server: String,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+}
+
+impl SshBruteForceFields {
+    #[must_use]
+    pub fn syslog_rfc5424(&self) -> String {
+        let start_time_dt = DateTime::from_timestamp_nanos(self.start_time);
+        let end_time_dt = DateTime::from_timestamp_nanos(self.end_time);
+        format!(
+            "category={:?} sensor={:?} src_addr={:?} dst_addrs={:?} dst_port={:?} proto={:?} attempts={:?} start_time={:?} end_time={:?} client={:?} server={:?} confidence={:?}",
+            self.category.as_ref().map_or_else(
+                || "Unspecified".to_string(),
+                std::string::ToString::to_string
+            ),
+            self.sensor,
+            self.src_addr.to_string(),
+            vector_to_string(&self.dst_addrs),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            vector_to_string(&self.attempts),
+            start_time_dt.to_rfc3339(),
+            end_time_dt.to_rfc3339(),
+            self.client,
+            self.server,
+            self.confidence.to_string()
+        )
+    }
+}
+
+#[allow(clippy::module_name_repetitions)]
+pub struct SshBruteForce {
+    pub time: DateTime<Utc>,
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addrs: Vec<IpAddr>,
+    pub dst_port: u16,
+    pub proto: u8,
+    pub attempts: Vec<u64>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub client: String,
+    pub server: String,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+    pub triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl fmt::Display for SshBruteForce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor={:?} src_addr={:?} dst_addrs={:?} dst_port={:?} proto={:?} attempts={:?} start_time={:?} end_time={:?} client={:?} server={:?} triage_scores={:?}",
+            self.sensor,
+            self.src_addr.to_string(),
+            vector_to_string(&self.dst_addrs),
+            self.dst_port.to_string(),
+            self.proto.to_string(),
+            vector_to_string(&self.attempts),
+            self.start_time.to_rfc3339(),
+            self.end_time.to_rfc3339(),
+            self.client,
+            self.server,
+            triage_scores_to_string(self.triage_scores.as_ref())
+        )
+    }
+}
+
+impl SshBruteForce {
+    pub(super) fn new(time: DateTime<Utc>, fields: SshBruteForceFields) -> Self {
+        Self {
+            time,
+            sensor: fields.sensor,
+            src_addr: fields.src_addr,
+            dst_addrs: fields.dst_addrs,
+            dst_port: fields.dst_port,
+            proto: fields.proto,
+            attempts: fields.attempts,
+            start_time: DateTime::from_timestamp_nanos(fields.start_time),
+            end_time: DateTime::from_timestamp_nanos(fields.end_time),
+            client: fields.client,
+            server: fields.server,
+            confidence: fields.confidence,
+            category: fields.category,
+            triage_scores: None,
+        }
+    }
+}
+
+impl Match for SshBruteForce {
+    fn src_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.src_addr)
+    }
+
+    fn src_port(&self) -> u16 {
+        0
+    }
+
+    fn dst_addrs(&self) -> &[IpAddr] {
+        &self.dst_addrs
+    }
+
+    fn dst_port(&self) -> u16 {
+        self.dst_port
+    }
+
+    fn proto(&self) -> u8 {
+        self.proto
+    }
+
+    fn category(&self) -> Option<EventCategory> {
+        self.category
+    }
+
+    fn detector_level(&self) -> NonZeroU8 {
+        MEDIUM
+    }
+
+    fn kind(&self) -> &'static str {
+        "ssh brute force"
+    }
+
+    fn event_kind(&self) -> EventKind {
+        EventKind::SshBruteForce
+    }
+
+    fn sensor(&self) -> &str {
+        self.sensor.as_str()
+    }
+
+    fn confidence(&self) -> Option<f32> {
+        Some(self.confidence)
+    }
+
+    fn learning_method(&self) -> LearningMethod {
+        LearningMethod::SemiSupervised
+    }
+
+    fn find_attr_by_kind(&self, raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>> {
+        if let RawEventAttrKind::Ssh(attr) = raw_event_attr {
+            match attr {
+                SshAttr::SrcAddr => Some(AttrValue::Addr(self.src_addr)),
+                SshAttr::DstAddr => Some(AttrValue::VecAddr(&self.dst_addrs)),
+                SshAttr::DstPort => Some(AttrValue::UInt(self.dst_port.into())),
+                SshAttr::Proto => Some(AttrValue::UInt(self.proto.into())),
+                SshAttr::Client => Some(AttrValue::String(&self.client)),
+                SshAttr::Server => Some(AttrValue::String(&self.server)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
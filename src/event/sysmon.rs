@@ -9,7 +9,7 @@ use attrievent::attribute::{RawEventAttrKind, WindowAttr};
 use chrono::{DateTime, Utc, serde::ts_nanoseconds};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string};
 
 // TODO: We plan to implement the triage feature only after we have cleaned up the range of
@@ -141,7 +141,7 @@ impl Match for WindowsThreat {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -149,6 +149,14 @@ impl Match for WindowsThreat {
         "windows threat"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::WindowsThreat
+    }
+
+    fn rule(&self) -> Option<(&str, u32)> {
+        Some((&self.db_name, self.rule_id))
+    }
+
     fn confidence(&self) -> Option<f32> {
         Some(self.confidence)
     }
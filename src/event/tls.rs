@@ -4,7 +4,7 @@ use attrievent::attribute::{RawEventAttrKind, TlsAttr};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::event::common::{AttrValue, triage_scores_to_string, vector_to_string};
 
 macro_rules! find_tls_attr_by_kind {
@@ -307,7 +307,7 @@ impl Match for BlocklistTls {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -315,6 +315,10 @@ impl Match for BlocklistTls {
         "blocklist tls"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::BlocklistTls
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -485,7 +489,7 @@ impl Match for SuspiciousTlsTraffic {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -493,6 +497,10 @@ impl Match for SuspiciousTlsTraffic {
         "suspicious tls traffic"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::SuspiciousTlsTraffic
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -4,7 +4,7 @@ use attrievent::attribute::{ConnAttr, HttpAttr, RawEventAttrKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{EventCategory, LearningMethod, MEDIUM, TriageScore, common::Match};
+use super::{EventCategory, EventKind, LearningMethod, MEDIUM, TriageScore, common::Match};
 use crate::TriageExclusion;
 use crate::event::{
     common::{AttrValue, triage_scores_to_string},
@@ -163,7 +163,7 @@ impl Match for TorConnection {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -171,6 +171,10 @@ impl Match for TorConnection {
         "tor exit nodes"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::TorConnection
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -304,7 +308,7 @@ impl Match for TorConnectionConn {
         self.category
     }
 
-    fn level(&self) -> NonZeroU8 {
+    fn detector_level(&self) -> NonZeroU8 {
         MEDIUM
     }
 
@@ -312,6 +316,10 @@ impl Match for TorConnectionConn {
         "tor exit nodes"
     }
 
+    fn event_kind(&self) -> EventKind {
+        EventKind::TorConnectionConn
+    }
+
     fn sensor(&self) -> &str {
         self.sensor.as_str()
     }
@@ -427,7 +435,8 @@ mod tests {
         assert_eq!(event.dst_port(), 443);
         assert_eq!(event.proto(), 6);
         assert_eq!(event.category(), Some(EventCategory::CommandAndControl));
-        assert_eq!(event.level(), MEDIUM);
+        assert_eq!(event.detector_level(), MEDIUM);
+        assert_eq!(event.level(None), MEDIUM);
         assert_eq!(event.kind(), "tor exit nodes");
         assert_eq!(event.sensor(), "test-sensor");
         assert_eq!(event.confidence(), Some(0.95));
@@ -0,0 +1,183 @@
+use std::{fmt, net::IpAddr, num::NonZeroU8};
+
+use attrievent::attribute::{ConnAttr, RawEventAttrKind};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{EventCategory, EventKind, HIGH, LearningMethod, TriageScore, common::Match};
+use crate::event::common::{AttrValue, triage_scores_to_string, vector_to_string};
+
+pub type VolumeExfiltrationFields = VolumeExfiltrationFieldsV0_43;
+
+/// The fields of an internal host sending far more data to external destinations than its
+/// baseline, regardless of the protocol used.
+#[derive(Serialize, Deserialize)]
+pub struct VolumeExfiltrationFieldsV0_43 {
+    pub sensor: String,
+    /// The internal host that sent the data.
+    pub src_addr: IpAddr,
+    /// The external destinations the data was sent to.
+    pub dst_addrs: Vec<IpAddr>,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub start_time: i64,
+    /// Timestamp in nanoseconds since the Unix epoch (UTC).
+    pub end_time: i64,
+    /// The number of bytes sent in the time window.
+    pub bytes_sent: u64,
+    /// The number of bytes the host is expected to send in a time window of the same length.
+    pub expected_mean: f64,
+    pub std_deviation: f64,
+    pub z_score: f64,
+    /// The application protocols the data was sent over, e.g., "http" or "dns".
+    pub protocols: Vec<String>,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+}
+
+impl VolumeExfiltrationFields {
+    #[must_use]
+    pub fn syslog_rfc5424(&self) -> String {
+        let start_time_dt = DateTime::from_timestamp_nanos(self.start_time);
+        let end_time_dt = DateTime::from_timestamp_nanos(self.end_time);
+        format!(
+            "category={:?} sensor={:?} src_addr={:?} dst_addrs={:?} start_time={:?} end_time={:?} bytes_sent={:?} expected_mean={:?} std_deviation={:?} z_score={:?} protocols={:?} confidence={:?}",
+            self.category.as_ref().map_or_else(
+                || "Unspecified".to_string(),
+                std::string::ToString::to_string
+            ),
+            self.sensor,
+            self.src_addr.to_string(),
+            vector_to_string(&self.dst_addrs),
+            start_time_dt.to_rfc3339(),
+            end_time_dt.to_rfc3339(),
+            self.bytes_sent.to_string(),
+            self.expected_mean.to_string(),
+            self.std_deviation.to_string(),
+            self.z_score.to_string(),
+            self.protocols.join(","),
+            self.confidence.to_string(),
+        )
+    }
+}
+
+pub struct VolumeExfiltration {
+    pub time: DateTime<Utc>,
+    pub sensor: String,
+    pub src_addr: IpAddr,
+    pub dst_addrs: Vec<IpAddr>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    pub bytes_sent: u64,
+    pub expected_mean: f64,
+    pub std_deviation: f64,
+    pub z_score: f64,
+    pub protocols: Vec<String>,
+    pub confidence: f32,
+    pub category: Option<EventCategory>,
+    pub triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl fmt::Display for VolumeExfiltration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sensor={:?} src_addr={:?} dst_addrs={:?} start_time={:?} end_time={:?} bytes_sent={:?} expected_mean={:?} std_deviation={:?} z_score={:?} protocols={:?} triage_scores={:?}",
+            self.sensor,
+            self.src_addr.to_string(),
+            vector_to_string(&self.dst_addrs),
+            self.start_time.to_rfc3339(),
+            self.end_time.to_rfc3339(),
+            self.bytes_sent.to_string(),
+            self.expected_mean.to_string(),
+            self.std_deviation.to_string(),
+            self.z_score.to_string(),
+            self.protocols.join(","),
+            triage_scores_to_string(self.triage_scores.as_ref()),
+        )
+    }
+}
+
+impl VolumeExfiltration {
+    pub(super) fn new(time: DateTime<Utc>, fields: VolumeExfiltrationFields) -> Self {
+        Self {
+            time,
+            sensor: fields.sensor,
+            src_addr: fields.src_addr,
+            dst_addrs: fields.dst_addrs,
+            start_time: DateTime::from_timestamp_nanos(fields.start_time),
+            end_time: DateTime::from_timestamp_nanos(fields.end_time),
+            bytes_sent: fields.bytes_sent,
+            expected_mean: fields.expected_mean,
+            std_deviation: fields.std_deviation,
+            z_score: fields.z_score,
+            protocols: fields.protocols,
+            confidence: fields.confidence,
+            category: fields.category,
+            triage_scores: None,
+        }
+    }
+}
+
+impl Match for VolumeExfiltration {
+    fn src_addrs(&self) -> &[IpAddr] {
+        std::slice::from_ref(&self.src_addr)
+    }
+
+    fn src_port(&self) -> u16 {
+        0
+    }
+
+    fn dst_addrs(&self) -> &[IpAddr] {
+        &self.dst_addrs
+    }
+
+    fn dst_port(&self) -> u16 {
+        0
+    }
+
+    fn proto(&self) -> u8 {
+        0
+    }
+
+    fn category(&self) -> Option<EventCategory> {
+        self.category
+    }
+
+    fn detector_level(&self) -> NonZeroU8 {
+        HIGH
+    }
+
+    fn kind(&self) -> &'static str {
+        "volume exfiltration"
+    }
+
+    fn event_kind(&self) -> EventKind {
+        EventKind::VolumeExfiltration
+    }
+
+    fn sensor(&self) -> &str {
+        self.sensor.as_str()
+    }
+
+    fn confidence(&self) -> Option<f32> {
+        Some(self.confidence)
+    }
+
+    fn learning_method(&self) -> LearningMethod {
+        LearningMethod::SemiSupervised
+    }
+
+    fn find_attr_by_kind(&self, raw_event_attr: RawEventAttrKind) -> Option<AttrValue<'_>> {
+        if let RawEventAttrKind::Conn(attr) = raw_event_attr {
+            match attr {
+                ConnAttr::SrcAddr => Some(AttrValue::Addr(self.src_addr)),
+                ConnAttr::DstAddr => Some(AttrValue::VecAddr(&self.dst_addrs)),
+                ConnAttr::Service => Some(AttrValue::VecString(&self.protocols)),
+                ConnAttr::OrigBytes => Some(AttrValue::UInt(self.bytes_sent)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
@@ -9,6 +9,7 @@ mod column_statistics;
 pub mod event;
 mod migration;
 mod model;
+mod scoped;
 mod scores;
 mod tables;
 mod tags;
@@ -28,7 +29,10 @@ pub use tags::TagSet;
 use tags::{EventTagId, NetworkTagId, WorkflowTagId};
 use thiserror::Error;
 
-pub use self::account::Role;
+pub use self::account::{
+    Action, LockoutPolicy, LockoutState, PasswordPolicy, PasswordPolicyError, PasswordStatus,
+    PasswordViolation, Permission, Resource, Role, RoleId, TotpEnrollment,
+};
 pub use self::batch_info::BatchInfo;
 pub use self::category::Category;
 pub use self::cluster::*;
@@ -38,18 +42,22 @@ pub use self::column_statistics::*;
 pub use self::event::{Event, EventDb, EventKind, EventMessage};
 pub use self::migration::migrate_data_dir;
 pub use self::model::{Digest, Model};
+pub use self::scoped::{ScopedEventDb, ScopedEventIterator, ScopedStore};
 pub use self::scores::Scores;
 use self::tables::StateDb;
 pub use self::tables::{
-    AccessToken, Agent, AgentConfig, AgentKind, AgentStatus, AllowNetwork, AllowNetworkUpdate,
-    AttrCmpKind, BlockNetwork, BlockNetworkUpdate, Cluster, ClusterTimeSeries, ColumnStats,
-    ColumnTimeSeries, Confidence, CsvColumnExtra as CsvColumnExtraConfig, Customer,
-    CustomerNetwork, CustomerUpdate, DataSource, DataSourceUpdate, DataType, ExternalService,
-    ExternalServiceConfig, ExternalServiceKind, ExternalServiceStatus, Filter, FilterValue, Host,
-    IndexedTable, Iterable, Model as ModelDigest, ModelIndicator, Network, NetworkFilter,
-    NetworkUpdate, Node, NodeProfile, NodeTable, NodeUpdate, OutlierInfo, OutlierInfoKey,
-    OutlierInfoValue, PacketAttr, PeriodForSearch, ProtocolPorts, Response, ResponseKind,
-    SamplingInterval, SamplingKind, SamplingPeriod, SamplingPolicy, SamplingPolicyUpdate,
+    AccessToken, AddrEnrichment, Agent, AgentConfig, AgentKind, AgentStatus, AllowNetwork,
+    AllowNetworkUpdate, AttrCmpKind, BlockNetwork, BlockNetworkUpdate, Cluster, ClusterTimeSeries,
+    ColumnStats, ColumnTimeSeries, Confidence, CorrelationKey, CorrelationPolicy,
+    CsvColumnExtra as CsvColumnExtraConfig, CustomAttrSpec, CustomEventSchema, CustomRole,
+    Customer, CustomerNetwork, CustomerUpdate, DataSource, DataSourceUpdate, DataType, DedupEntry,
+    DedupPolicy, EventEnrichment, ExternalService, ExternalServiceConfig, ExternalServiceKind,
+    ExternalServiceStatus, Filter, FilterValue, Host, Incident, IndexedTable, Iterable, LogParser,
+    LogTemplate, Model as ModelDigest, ModelIndicator, Network, NetworkFilter, NetworkUpdate, Node,
+    NodeProfile, NodeTable, NodeUpdate, OutlierInfo, OutlierInfoKey, OutlierInfoValue, PacketAttr,
+    PeriodForSearch, ProtocolPorts, Response, ResponseKind, RoleUpdate, RuleTechniques,
+    SamplingInterval, SamplingKind, SamplingPeriod, SamplingPolicy, SamplingPolicyUpdate, Sensor,
+    SensorIngest, Session, SessionLimitAction, SeverityOverride, SeverityOverrides, SeverityScope,
     Structured, StructuredClusteringAlgorithm, Table, Template, Tidb, TidbKind, TidbRule,
     TidbRuleKind, TimeSeries, TopColumnsOfCluster, TopMultimaps, TorExitNode, TrafficFilter,
     TriageExclusion, TriageExclusionReason, TriagePolicy, TriagePolicyInput, TriagePolicyUpdate,
@@ -172,6 +180,12 @@ impl Store {
         self.states.csv_column_extras()
     }
 
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn custom_event_schema_map(&self) -> Table<'_, CustomEventSchema> {
+        self.states.custom_event_schemas()
+    }
+
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     pub fn customer_map(&self) -> IndexedTable<'_, Customer> {
@@ -184,6 +198,18 @@ impl Store {
         self.states.data_sources()
     }
 
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn event_dedup_map(&self) -> Table<'_, DedupEntry> {
+        self.states.event_dedup_keys()
+    }
+
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn event_enrichment_map(&self) -> Table<'_, EventEnrichment> {
+        self.states.event_enrichments()
+    }
+
     /// Returns the tag set for event.
     ///
     /// # Errors
@@ -198,6 +224,18 @@ impl Store {
         TagSet::new(set)
     }
 
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn incident_map(&self) -> Table<'_, tables::Incident> {
+        self.states.incidents()
+    }
+
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn log_template_map(&self) -> Table<'_, LogTemplate> {
+        self.states.log_templates()
+    }
+
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     pub fn filter_map(&self) -> Table<'_, Filter> {
@@ -266,12 +304,42 @@ impl Store {
         self.states.qualifiers()
     }
 
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn role_map(&self) -> IndexedTable<'_, CustomRole> {
+        self.states.roles()
+    }
+
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     pub fn scores_map(&self) -> Table<'_, scores::Scores> {
         self.states.scores()
     }
 
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn sensor_map(&self) -> Table<'_, Sensor> {
+        self.states.sensors()
+    }
+
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn sensor_ingest_map(&self) -> Table<'_, SensorIngest> {
+        self.states.sensor_ingest()
+    }
+
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn session_map(&self) -> Table<'_, Session> {
+        self.states.sessions()
+    }
+
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn severity_override_map(&self) -> Table<'_, SeverityOverride> {
+        self.states.severity_overrides()
+    }
+
     #[must_use]
     #[allow(clippy::missing_panics_doc)]
     pub fn status_map(&self) -> IndexedTable<'_, types::Status> {
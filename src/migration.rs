@@ -10,7 +10,7 @@ use std::{
 
 use anyhow::{Context, Result, anyhow};
 use semver::{Version, VersionReq};
-use tracing::info;
+use tracing::{info, warn};
 
 /// The range of versions that use the current database format.
 ///
@@ -93,7 +93,7 @@ use tracing::info;
 /// // release that involves database format change) to 3.5.0, including
 /// // all alpha changes finalized in 3.5.0.
 /// ```
-const COMPATIBLE_VERSION_REQ: &str = ">=0.43.0-alpha.1,<0.43.0-alpha.2";
+const COMPATIBLE_VERSION_REQ: &str = ">=0.43.0-alpha.2,<0.43.0-alpha.3";
 
 /// Migrates the data directory to the up-to-date format if necessary.
 ///
@@ -141,8 +141,8 @@ pub fn migrate_data_dir<P: AsRef<Path>>(data_dir: P, backup_dir: P) -> Result<()
     //   the first version (major.minor) in the "version requirement" and B is the "to version"
     //   (major.minor). (NOTE: Once we release 1.0.0, A and B will contain the major version only.)
     let migration: Vec<Migration> = vec![(
-        VersionReq::parse(">=0.42.0-alpha.5,<0.43.0-alpha.1")?,
-        Version::parse("0.43.0-alpha.1")?,
+        VersionReq::parse(">=0.42.0-alpha.5,<0.43.0-alpha.2")?,
+        Version::parse("0.43.0-alpha.2")?,
         migrate_0_42_to_0_43,
     )];
 
@@ -203,40 +203,141 @@ const MAP_NAMES_V0_42: [&str; 36] = [
 ];
 
 fn migrate_0_42_to_0_43(data_dir: &Path, backup_dir: &Path) -> Result<()> {
-    // Open the database with the old column family list (including "account policy")
     let db_path = data_dir.join("states.db");
     let backup_path = backup_dir.join("states.db");
 
-    info!("Opening database with legacy column families to drop 'account policy'");
+    drop_account_policy(&db_path)?;
+    drop_account_policy(&backup_path).context("backup database")?;
 
-    // Open the database with the old column family names
+    migrate_extra_threats_0_42(&db_path)?;
+    migrate_extra_threats_0_42(&backup_path).context("backup database")?;
+
+    migrate_accounts_0_42(&db_path)?;
+    migrate_accounts_0_42(&backup_path).context("backup database")?;
+
+    invalidate_access_tokens(&db_path)?;
+    invalidate_access_tokens(&backup_path).context("backup database")?;
+    Ok(())
+}
+
+/// Drops the deprecated "account policy" column family, if it has not been dropped already.
+fn drop_account_policy(db_path: &Path) -> Result<()> {
     let mut opts = rocksdb::Options::default();
     opts.create_if_missing(false);
     opts.create_missing_column_families(false);
 
-    let db = rocksdb::OptimisticTransactionDB::open_cf(&opts, &db_path, MAP_NAMES_V0_42)
-        .context("Failed to open database with legacy column families")?;
+    let names = rocksdb::DB::list_cf(&opts, db_path).context("Failed to list column families")?;
+    if !names.iter().any(|name| name == "account policy") {
+        return Ok(());
+    }
 
-    // Drop the "account policy" column family
     info!("Dropping 'account policy' column family");
+    let db = rocksdb::OptimisticTransactionDB::open_cf(&opts, db_path, MAP_NAMES_V0_42)
+        .context("Failed to open database with legacy column families")?;
     db.drop_cf("account policy")
         .context("Failed to drop 'account policy' column family")?;
+    info!("Successfully removed 'account policy' column family");
+    Ok(())
+}
 
-    // Close the database by dropping it
-    drop(db);
+/// Adds the extracted log fields, absent before 0.43, to the stored `ExtraThreat` events. Events
+/// that cannot be deserialized are skipped.
+fn migrate_extra_threats_0_42(db_path: &Path) -> Result<()> {
+    use num_traits::FromPrimitive;
 
-    // Also drop from backup database
-    let backup_db = rocksdb::OptimisticTransactionDB::open_cf(&opts, &backup_path, MAP_NAMES_V0_42)
-        .context("Failed to open backup database with legacy column families")?;
+    use self::migration_structures::ExtraThreatV0_42;
+    use crate::{EventKind, event::ExtraThreat};
 
-    info!("Dropping 'account policy' column family from backup");
-    backup_db
-        .drop_cf("account policy")
-        .context("Failed to drop 'account policy' column family from backup")?;
+    let mut opts = rocksdb::Options::default();
+    opts.create_if_missing(false);
+    opts.create_missing_column_families(false);
 
-    drop(backup_db);
+    let names = rocksdb::DB::list_cf(&opts, db_path).context("Failed to list column families")?;
+    let db = rocksdb::OptimisticTransactionDB::open_cf(&opts, db_path, names)
+        .context("Failed to open database")?;
 
-    info!("Successfully removed 'account policy' column family");
+    info!("Migrating 'ExtraThreat' events");
+    for item in db.iterator(rocksdb::IteratorMode::Start) {
+        let (key, value) = item.context("Failed to read event")?;
+        let Ok(event_key) = <[u8; 16]>::try_from(key.as_ref()) else {
+            continue;
+        };
+        let kind = (i128::from_be_bytes(event_key) & 0xffff_ffff_0000_0000) >> 32;
+        if EventKind::from_i128(kind) != Some(EventKind::ExtraThreat) {
+            continue;
+        }
+        // An event that cannot be decoded is left as it is rather than failing the migration,
+        // which has already dropped the "account policy" column family.
+        let Ok(old) = bincode::deserialize::<ExtraThreatV0_42>(&value) else {
+            warn!(
+                "Skipping 'ExtraThreat' event that cannot be deserialized: {}",
+                i128::from_be_bytes(event_key)
+            );
+            continue;
+        };
+        let new = bincode::serialize(&ExtraThreat::from(old))?;
+        db.put(&key, new)
+            .context("Failed to store 'ExtraThreat' event")?;
+    }
+    Ok(())
+}
+
+/// Adds the password history, the requirement to change the password, and the
+/// lockout state, absent before 0.43, to the stored accounts, maps their roles
+/// to the built-in roles, and turns the addresses they can sign in from into a
+/// `HostNetworkGroup`.
+fn migrate_accounts_0_42(db_path: &Path) -> Result<()> {
+    use bincode::Options;
+
+    use self::migration_structures::AccountV0_42;
+    use crate::types::Account;
+
+    let mut opts = rocksdb::Options::default();
+    opts.create_if_missing(false);
+    opts.create_missing_column_families(false);
+
+    let names = rocksdb::DB::list_cf(&opts, db_path).context("Failed to list column families")?;
+    let db = rocksdb::OptimisticTransactionDB::open_cf(&opts, db_path, names)
+        .context("Failed to open database")?;
+    let Some(cf) = db.cf_handle("accounts") else {
+        return Ok(());
+    };
+
+    info!("Migrating accounts");
+    let options = bincode::DefaultOptions::new();
+    for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+        let (key, value) = item.context("Failed to read account")?;
+        let old: AccountV0_42 = options
+            .deserialize(&value)
+            .context("Failed to deserialize account")?;
+        let new = options.serialize(&Account::from(old))?;
+        db.put_cf(cf, &key, new)
+            .context("Failed to store account")?;
+    }
+    Ok(())
+}
+
+/// Removes the access tokens stored in plain text before 0.43. They cannot be
+/// rehashed since the key to hash them with is not in the database, so their
+/// users need to sign in again.
+fn invalidate_access_tokens(db_path: &Path) -> Result<()> {
+    let mut opts = rocksdb::Options::default();
+    opts.create_if_missing(false);
+    opts.create_missing_column_families(false);
+
+    let names = rocksdb::DB::list_cf(&opts, db_path).context("Failed to list column families")?;
+    let db = rocksdb::OptimisticTransactionDB::open_cf(&opts, db_path, names)
+        .context("Failed to open database")?;
+    let Some(cf) = db.cf_handle("access_tokens") else {
+        return Ok(());
+    };
+
+    info!("Invalidating access tokens");
+    for item in db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+        let (key, _) = item.context("Failed to read access token")?;
+        db.delete_cf(cf, &key)
+            .context("Failed to delete access token")?;
+    }
     Ok(())
 }
 
@@ -443,4 +544,178 @@ mod tests {
             .unwrap();
         assert!(backup_db.cf_handle("account policy").is_none());
     }
+
+    #[test]
+    fn migrate_0_42_to_0_43_extra_threat() {
+        use chrono::{TimeZone, Utc};
+        use num_traits::ToPrimitive;
+
+        use crate::{
+            EventKind, event::ExtraThreat, migration::migration_structures::ExtraThreatV0_42,
+        };
+
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let time = Utc.with_ymd_and_hms(1970, 1, 1, 1, 1, 1).unwrap();
+        let key = (i128::from(time.timestamp_nanos_opt().unwrap()) << 64)
+            | (EventKind::ExtraThreat.to_i128().unwrap() << 32);
+        let old = ExtraThreatV0_42 {
+            time,
+            sensor: "sensor".to_string(),
+            service: "sshd".to_string(),
+            content: "content".to_string(),
+            db_name: "db_name".to_string(),
+            rule_id: 1,
+            matched_to: "matched_to".to_string(),
+            cluster_id: None,
+            attack_kind: "attack_kind".to_string(),
+            confidence: 0.9,
+            category: None,
+            triage_scores: None,
+        };
+        let invalid = key + (1 << 64);
+        for dir in [&db_dir, &backup_dir] {
+            let db: rocksdb::OptimisticTransactionDB = rocksdb::OptimisticTransactionDB::open_cf(
+                &opts,
+                dir.path().join("states.db"),
+                super::MAP_NAMES_V0_42,
+            )
+            .unwrap();
+            db.put(key.to_be_bytes(), bincode::serialize(&old).unwrap())
+                .unwrap();
+            db.put(invalid.to_be_bytes(), [0xff]).unwrap();
+        }
+
+        super::migrate_0_42_to_0_43(db_dir.path(), backup_dir.path()).unwrap();
+
+        let db: rocksdb::OptimisticTransactionDB = rocksdb::OptimisticTransactionDB::open_cf(
+            &opts,
+            db_dir.path().join("states.db"),
+            crate::tables::MAP_NAMES,
+        )
+        .unwrap();
+        let value = db.get(key.to_be_bytes()).unwrap().unwrap();
+        let new: ExtraThreat = bincode::deserialize(&value).unwrap();
+        assert_eq!(new.service, "sshd");
+        assert!(new.fields.is_none());
+        assert_eq!(
+            db.get(invalid.to_be_bytes()).unwrap().as_deref(),
+            Some([0xff].as_slice())
+        );
+    }
+
+    #[test]
+    fn migrate_0_42_to_0_43_accounts() {
+        use bincode::Options;
+        use chrono::Utc;
+
+        use crate::{
+            Role, RoleId,
+            account::{PasswordHashAlgorithm, SaltedPassword},
+            migration::migration_structures::AccountV0_42,
+            types::Account,
+        };
+
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let old = AccountV0_42 {
+            username: "admin".to_string(),
+            password: SaltedPassword::new_with_hash_algorithm(
+                "password",
+                &PasswordHashAlgorithm::Argon2id,
+            )
+            .unwrap(),
+            role: Role::SystemAdministrator,
+            name: "Admin".to_string(),
+            department: String::new(),
+            language: None,
+            theme: None,
+            creation_time: Utc::now(),
+            last_signin_time: None,
+            allow_access_from: Some(vec!["10.0.0.1".parse().unwrap()]),
+            max_parallel_sessions: None,
+            password_hash_algorithm: PasswordHashAlgorithm::Argon2id,
+            password_last_modified_at: Utc::now(),
+            customer_ids: None,
+            failed_login_attempts: 2,
+            locked_out_until: None,
+            is_suspended: false,
+        };
+        let value = bincode::DefaultOptions::new().serialize(&old).unwrap();
+        for dir in [&db_dir, &backup_dir] {
+            let db: rocksdb::OptimisticTransactionDB = rocksdb::OptimisticTransactionDB::open_cf(
+                &opts,
+                dir.path().join("states.db"),
+                super::MAP_NAMES_V0_42,
+            )
+            .unwrap();
+            let cf = db.cf_handle("accounts").unwrap();
+            db.put_cf(cf, b"admin", &value).unwrap();
+        }
+
+        super::migrate_0_42_to_0_43(db_dir.path(), backup_dir.path()).unwrap();
+
+        let db: rocksdb::OptimisticTransactionDB = rocksdb::OptimisticTransactionDB::open_cf(
+            &opts,
+            db_dir.path().join("states.db"),
+            crate::tables::MAP_NAMES,
+        )
+        .unwrap();
+        let cf = db.cf_handle("accounts").unwrap();
+        let value = db.get_cf(cf, b"admin").unwrap().unwrap();
+        let new: Account = bincode::DefaultOptions::new().deserialize(&value).unwrap();
+        assert_eq!(new.username, "admin");
+        assert_eq!(new.role, RoleId::BuiltIn(Role::SystemAdministrator));
+        assert!(new.is_access_allowed("10.0.0.1".parse().unwrap()));
+        assert!(!new.is_access_allowed("10.0.0.2".parse().unwrap()));
+        assert_eq!(new.failed_login_attempts, 2);
+        assert!(new.verify_password("password"));
+        assert!(new.password_history.is_empty());
+        assert!(new.must_change_password());
+    }
+
+    #[test]
+    fn migrate_0_42_to_0_43_invalidates_access_tokens() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        for dir in [&db_dir, &backup_dir] {
+            let db: rocksdb::OptimisticTransactionDB = rocksdb::OptimisticTransactionDB::open_cf(
+                &opts,
+                dir.path().join("states.db"),
+                super::MAP_NAMES_V0_42,
+            )
+            .unwrap();
+            let cf = db.cf_handle("access_tokens").unwrap();
+            db.put_cf(cf, b"admin\0token", []).unwrap();
+        }
+
+        super::migrate_0_42_to_0_43(db_dir.path(), backup_dir.path()).unwrap();
+
+        for dir in [&db_dir, &backup_dir] {
+            let db: rocksdb::OptimisticTransactionDB = rocksdb::OptimisticTransactionDB::open_cf(
+                &opts,
+                dir.path().join("states.db"),
+                crate::tables::MAP_NAMES,
+            )
+            .unwrap();
+            let cf = db.cf_handle("access_tokens").unwrap();
+            assert!(
+                db.iterator_cf(cf, rocksdb::IteratorMode::Start)
+                    .next()
+                    .is_none()
+            );
+        }
+    }
 }
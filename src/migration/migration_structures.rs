@@ -4,10 +4,14 @@
 //! and must not be modified. They are used to migrate data from
 //! old formats to new formats.
 
+use std::net::IpAddr;
+
+use chrono::{DateTime, Utc, serde::ts_nanoseconds};
 use serde::{Deserialize, Serialize};
 
-use crate::PeriodForSearch;
-use crate::event::{FilterEndpoint, FlowKind, LearningMethod};
+use crate::account::{PasswordHashAlgorithm, SaltedPassword};
+use crate::event::{FilterEndpoint, FlowKind, LearningMethod, TriageScore};
+use crate::{EventCategory, HostNetworkGroup, PeriodForSearch, Role};
 
 /// Filter value structure from version 0.41.x
 ///
@@ -63,3 +67,106 @@ impl From<FilterValueV0_41> for crate::FilterValue {
         }
     }
 }
+
+/// `ExtraThreat` event structure from version 0.42.x
+///
+/// This structure represents the persisted event before the fields
+/// extracted from its content were added.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct ExtraThreatV0_42 {
+    #[serde(with = "ts_nanoseconds")]
+    pub(crate) time: DateTime<Utc>,
+    pub(crate) sensor: String,
+    pub(crate) service: String,
+    pub(crate) content: String,
+    pub(crate) db_name: String,
+    pub(crate) rule_id: u32,
+    pub(crate) matched_to: String,
+    pub(crate) cluster_id: Option<usize>,
+    pub(crate) attack_kind: String,
+    pub(crate) confidence: f32,
+    pub(crate) category: Option<EventCategory>,
+    pub(crate) triage_scores: Option<Vec<TriageScore>>,
+}
+
+impl From<ExtraThreatV0_42> for crate::event::ExtraThreat {
+    fn from(old: ExtraThreatV0_42) -> Self {
+        Self {
+            time: old.time,
+            sensor: old.sensor,
+            service: old.service,
+            content: old.content,
+            db_name: old.db_name,
+            rule_id: old.rule_id,
+            matched_to: old.matched_to,
+            cluster_id: old.cluster_id,
+            attack_kind: old.attack_kind,
+            confidence: old.confidence,
+            category: old.category,
+            fields: None,
+            triage_scores: old.triage_scores,
+        }
+    }
+}
+
+/// `Account` structure from version 0.42.x
+///
+/// This structure represents the persisted account before the password
+/// history, the explicit requirement to change the password, and the state
+/// for progressive lockouts were added. Its role is always a built-in one,
+/// which the migrated account keeps, and the addresses it can sign in from
+/// are single hosts.
+/// An account that has never signed in, or whose last sign-in time was reset,
+/// must change its password.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct AccountV0_42 {
+    pub(crate) username: String,
+    pub(crate) password: SaltedPassword,
+    pub(crate) role: Role,
+    pub(crate) name: String,
+    pub(crate) department: String,
+    pub(crate) language: Option<String>,
+    pub(crate) theme: Option<String>,
+    pub(crate) creation_time: DateTime<Utc>,
+    pub(crate) last_signin_time: Option<DateTime<Utc>>,
+    pub(crate) allow_access_from: Option<Vec<IpAddr>>,
+    pub(crate) max_parallel_sessions: Option<u8>,
+    pub(crate) password_hash_algorithm: PasswordHashAlgorithm,
+    pub(crate) password_last_modified_at: DateTime<Utc>,
+    pub(crate) customer_ids: Option<Vec<u32>>,
+    pub(crate) failed_login_attempts: u8,
+    pub(crate) locked_out_until: Option<DateTime<Utc>>,
+    pub(crate) is_suspended: bool,
+}
+
+impl From<AccountV0_42> for crate::types::Account {
+    fn from(old: AccountV0_42) -> Self {
+        Self {
+            username: old.username,
+            password: old.password,
+            role: old.role.into(),
+            name: old.name,
+            department: old.department,
+            language: old.language,
+            theme: old.theme,
+            creation_time: old.creation_time,
+            last_signin_time: old.last_signin_time,
+            allow_access_from: old
+                .allow_access_from
+                .map(|hosts| HostNetworkGroup::new(hosts, Vec::new(), Vec::new())),
+            max_parallel_sessions: old.max_parallel_sessions,
+            password_hash_algorithm: old.password_hash_algorithm,
+            password_last_modified_at: old.password_last_modified_at,
+            customer_ids: old.customer_ids,
+            failed_login_attempts: old.failed_login_attempts,
+            locked_out_until: old.locked_out_until,
+            is_suspended: old.is_suspended,
+            password_history: Vec::new(),
+            must_change_password: old.last_signin_time.is_none(),
+            last_failed_login_at: None,
+            lockout_count: 0,
+            is_locked_permanently: false,
+            totp: None,
+        }
+    }
+}
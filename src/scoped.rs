@@ -0,0 +1,427 @@
+//! A view of the store limited to the customers an account can access.
+
+use anyhow::Result;
+use rocksdb::Direction;
+
+use crate::{
+    Customer, EventDb, Host, Iterable, Network, Node, Store,
+    event::{Event, EventFilter, EventIterator, InvalidEvent},
+    types::Account,
+};
+
+/// A view of the store that applies the customer restriction of an account.
+///
+/// An account without `Account::customer_ids` sees everything.
+pub struct ScopedStore<'a> {
+    store: &'a Store,
+    customer_ids: Option<Vec<u32>>,
+}
+
+impl Store {
+    /// Returns a view of the store limited to the customers `account` can access.
+    #[must_use]
+    pub fn for_account(&self, account: &Account) -> ScopedStore<'_> {
+        ScopedStore {
+            store: self,
+            customer_ids: account.customer_ids.clone(),
+        }
+    }
+}
+
+impl<'a> ScopedStore<'a> {
+    /// Returns the IDs of the customers the account can access, or `None` if it can access all.
+    #[must_use]
+    pub fn customer_ids(&self) -> Option<&[u32]> {
+        self.customer_ids.as_deref()
+    }
+
+    #[must_use]
+    pub fn is_customer_allowed(&self, customer_id: u32) -> bool {
+        self.customer_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&customer_id))
+    }
+
+    /// Returns the customers the account can access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored customer is invalid.
+    pub fn customers(&self) -> Result<Vec<Customer>> {
+        let map = self.store.customer_map();
+        let mut customers = Vec::new();
+        for customer in map.iter(Direction::Forward, None) {
+            let customer = customer?;
+            if self.is_customer_allowed(customer.id) {
+                customers.push(customer);
+            }
+        }
+        Ok(customers)
+    }
+
+    /// Returns the networks of the customers the account can access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored network is invalid.
+    pub fn networks(&self) -> Result<Vec<Network>> {
+        let map = self.store.network_map();
+        let mut networks = Vec::new();
+        for network in map.iter(Direction::Forward, None) {
+            let network = network?;
+            if self.customer_ids.is_none()
+                || network
+                    .customer_ids
+                    .iter()
+                    .any(|&id| self.is_customer_allowed(id))
+            {
+                networks.push(network);
+            }
+        }
+        Ok(networks)
+    }
+
+    /// Returns the hosts of the customers the account can access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored host is invalid.
+    pub fn hosts(&self) -> Result<Vec<Host>> {
+        let map = self.store.hosts_map();
+        let mut hosts = Vec::new();
+        for host in map.iter(Direction::Forward, None) {
+            let host = host?;
+            if self.is_customer_allowed(host.customer_id) {
+                hosts.push(host);
+            }
+        }
+        Ok(hosts)
+    }
+
+    /// Returns the nodes whose profile belongs to a customer the account can access. If the
+    /// account is restricted, nodes without a profile are excluded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored node is invalid.
+    pub fn nodes(&self) -> Result<Vec<Node>> {
+        let map = self.store.node_map();
+        let mut nodes = Vec::new();
+        for node in map.iter(Direction::Forward, None) {
+            let node = node?;
+            if self.customer_ids.is_none()
+                || node
+                    .profile
+                    .as_ref()
+                    .is_some_and(|profile| self.is_customer_allowed(profile.customer_id))
+            {
+                nodes.push(node);
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Returns the events whose addresses are in the networks of the customers the account can
+    /// access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored customer is invalid.
+    pub fn events(&self) -> Result<ScopedEventDb<'a>> {
+        let customers = if self.customer_ids.is_some() {
+            Some(self.customers()?)
+        } else {
+            None
+        };
+        Ok(ScopedEventDb {
+            events: self.store.events(),
+            customers,
+        })
+    }
+
+    /// Limits `filter` to the customers the account can access.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored customer is invalid.
+    pub fn event_filter(&self, mut filter: EventFilter) -> Result<EventFilter> {
+        if self.customer_ids.is_some() {
+            filter.restrict_customers(&self.customers()?);
+        }
+        Ok(filter)
+    }
+}
+
+/// The events of the customers an account can access.
+pub struct ScopedEventDb<'a> {
+    events: EventDb<'a>,
+    customers: Option<Vec<Customer>>,
+}
+
+impl ScopedEventDb<'_> {
+    /// Creates an iterator over the events the account can access, starting from `key`.
+    #[must_use]
+    pub fn iter_from(&self, key: i128, direction: Direction) -> ScopedEventIterator<'_> {
+        ScopedEventIterator {
+            inner: self.events.iter_from(key, direction),
+            customers: self.customers.as_deref(),
+        }
+    }
+
+    /// Creates an iterator over all the events the account can access.
+    #[must_use]
+    pub fn iter_forward(&self) -> ScopedEventIterator<'_> {
+        ScopedEventIterator {
+            inner: self.events.iter_forward(),
+            customers: self.customers.as_deref(),
+        }
+    }
+}
+
+/// An iterator over the events an account can access.
+///
+/// For an account limited to some customers, events that cannot be decoded are skipped, since
+/// their customers cannot be told.
+pub struct ScopedEventIterator<'i> {
+    inner: EventIterator<'i>,
+    customers: Option<&'i [Customer]>,
+}
+
+impl Iterator for ScopedEventIterator<'_> {
+    type Item = Result<(i128, Event), InvalidEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = self.inner.next()?;
+            let allowed = match (&item, self.customers) {
+                (_, None) => true,
+                (Ok((_, event)), Some(customers)) => event.involves(customers),
+                (Err(_), Some(_)) => false,
+            };
+            if allowed {
+                return Some(item);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::IpAddr, sync::Arc};
+
+    use chrono::Utc;
+
+    use crate::{
+        Customer, CustomerNetwork, EventKind, EventMessage, HostNetworkGroup, Network, Node,
+        NodeProfile, PasswordPolicy, Role, Store,
+        event::{DnsEventFields, EventFilter, NetworkType},
+        types::{Account, EventCategory},
+    };
+
+    #[test]
+    fn customers_networks_and_hosts() {
+        let store = setup_store();
+        let customers = store.customer_map();
+        let a = customers.put(customer("a", "10.0.0.0")).unwrap();
+        let b = customers.put(customer("b", "10.0.1.0")).unwrap();
+        let networks = store.network_map();
+        networks
+            .put(Network::new(
+                "a".to_string(),
+                String::new(),
+                HostNetworkGroup::default(),
+                vec![a],
+                Vec::new(),
+            ))
+            .unwrap();
+        networks
+            .put(Network::new(
+                "b".to_string(),
+                String::new(),
+                HostNetworkGroup::default(),
+                vec![b],
+                Vec::new(),
+            ))
+            .unwrap();
+        let hosts = store.hosts_map();
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        hosts.update_agents(a, ip, &Vec::new(), &[]).unwrap();
+        hosts.update_agents(b, ip, &Vec::new(), &[]).unwrap();
+
+        let unrestricted = store.for_account(&account(None));
+        assert_eq!(unrestricted.customers().unwrap().len(), 2);
+        assert_eq!(unrestricted.networks().unwrap().len(), 2);
+        assert_eq!(unrestricted.hosts().unwrap().len(), 2);
+
+        let scoped = store.for_account(&account(Some(vec![b])));
+        assert!(!scoped.is_customer_allowed(a));
+        let customers = scoped.customers().unwrap();
+        assert_eq!(customers.len(), 1);
+        assert_eq!(customers[0].id, b);
+        let networks = scoped.networks().unwrap();
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].name, "b");
+        let hosts = scoped.hosts().unwrap();
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].customer_id, b);
+    }
+
+    #[test]
+    fn events() {
+        let store = setup_store();
+        let customers = store.customer_map();
+        let a = customers.put(customer("a", "10.0.0.0")).unwrap();
+        let b = customers.put(customer("b", "10.0.1.0")).unwrap();
+        let events = store.events();
+        events.put(&dns_event("10.0.0.1")).unwrap();
+        events.put(&dns_event("10.0.1.1")).unwrap();
+        events.put(&dns_event("192.168.0.1")).unwrap();
+        let mut invalid = dns_event("10.0.0.2");
+        invalid.fields = vec![0xff];
+        events.put(&invalid).unwrap();
+
+        let unrestricted = store.for_account(&account(None));
+        let unrestricted = unrestricted.events().unwrap();
+        assert_eq!(unrestricted.iter_forward().count(), 4);
+        assert_eq!(
+            unrestricted.iter_forward().filter(Result::is_err).count(),
+            1
+        );
+
+        let scoped = store.for_account(&account(Some(vec![a])));
+        let events = scoped.events().unwrap();
+        let visible: Vec<_> = events.iter_forward().collect();
+        assert_eq!(visible.len(), 1);
+        let (_, event) = visible[0].as_ref().unwrap();
+        assert!(event.involves(&[customer("a", "10.0.0.0")]));
+
+        let filter = scoped.event_filter(event_filter(None)).unwrap();
+        let (matches, _) = event.matches(None, &filter).unwrap();
+        assert!(matches);
+        let other = store.customer_map().get_by_id(b).unwrap().unwrap();
+        let filter = scoped
+            .event_filter(event_filter(Some(vec![other])))
+            .unwrap();
+        let (matches, _) = event.matches(None, &filter).unwrap();
+        assert!(!matches);
+
+        let nobody = store.for_account(&account(Some(Vec::new())));
+        assert_eq!(nobody.events().unwrap().iter_forward().count(), 0);
+    }
+
+    #[test]
+    fn nodes() {
+        let store = setup_store();
+        let nodes = store.node_map();
+        nodes.put(&node("node1", Some(1))).unwrap();
+        nodes.put(&node("node2", Some(2))).unwrap();
+        nodes.put(&node("node3", None)).unwrap();
+
+        assert_eq!(store.for_account(&account(None)).nodes().unwrap().len(), 3);
+        let scoped = store.for_account(&account(Some(vec![2])));
+        let visible = scoped.nodes().unwrap();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].name, "node2");
+    }
+
+    fn dns_event(src_addr: &str) -> EventMessage {
+        let fields = DnsEventFields {
+            sensor: "collector1".to_string(),
+            src_addr: src_addr.parse().unwrap(),
+            src_port: 10000,
+            dst_addr: "8.8.8.8".parse().unwrap(),
+            dst_port: 53,
+            proto: 17,
+            start_time: 0,
+            duration: 0,
+            orig_pkts: 0,
+            resp_pkts: 0,
+            orig_l2_bytes: 0,
+            resp_l2_bytes: 0,
+            query: "foo.com".to_string(),
+            answer: Vec::new(),
+            trans_id: 1,
+            rtt: 1,
+            qclass: 0,
+            qtype: 0,
+            rcode: 0,
+            aa_flag: false,
+            tc_flag: false,
+            rd_flag: false,
+            ra_flag: false,
+            ttl: Vec::new(),
+            confidence: 0.8,
+            category: Some(EventCategory::CommandAndControl),
+        };
+        EventMessage {
+            time: Utc::now(),
+            kind: EventKind::DnsCovertChannel,
+            fields: bincode::serialize(&fields).unwrap(),
+        }
+    }
+
+    fn event_filter(customers: Option<Vec<Customer>>) -> EventFilter {
+        EventFilter::new(
+            customers, None, None, None, None, None, None, None, None, None, None, None, None, None,
+        )
+    }
+
+    fn node(name: &str, customer_id: Option<u32>) -> Node {
+        Node {
+            id: u32::MAX,
+            name: name.to_string(),
+            name_draft: None,
+            profile: customer_id.map(|customer_id| NodeProfile {
+                customer_id,
+                description: String::new(),
+                hostname: name.to_string(),
+            }),
+            profile_draft: None,
+            agents: Vec::new(),
+            external_services: Vec::new(),
+            creation_time: Utc::now(),
+        }
+    }
+
+    fn customer(name: &str, network: &str) -> Customer {
+        Customer {
+            id: u32::MAX,
+            name: name.to_string(),
+            description: String::new(),
+            networks: vec![CustomerNetwork {
+                name: name.to_string(),
+                description: String::new(),
+                network_type: NetworkType::Intranet,
+                network_group: HostNetworkGroup::new(
+                    Vec::new(),
+                    vec![format!("{network}/24").parse().unwrap()],
+                    Vec::new(),
+                ),
+            }],
+            creation_time: Utc::now(),
+        }
+    }
+
+    fn account(customer_ids: Option<Vec<u32>>) -> Account {
+        Account::new(
+            "user",
+            "password",
+            Role::SecurityMonitor.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            customer_ids,
+            &PasswordPolicy::default(),
+        )
+        .unwrap()
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
@@ -9,19 +9,28 @@ mod cluster;
 mod column_stats;
 mod config;
 mod csv_column_extra;
+mod custom_event_schema;
 mod customer;
 mod data_source;
+mod event_dedup;
+mod event_enrichment;
 mod external_service;
 mod filter;
 mod hosts;
+mod incident;
+mod log_template;
 mod model;
 mod model_indicator;
 mod network;
 mod node;
 mod outlier_info;
 mod qualifier;
+mod role;
 mod sampling_policy;
 mod scores;
+mod sensor;
+mod session;
+mod severity_override;
 mod status;
 mod template;
 mod tidb;
@@ -46,11 +55,19 @@ pub use self::block_network::{BlockNetwork, Update as BlockNetworkUpdate};
 pub use self::cluster::Cluster;
 pub use self::column_stats::{ColumnStats, TopColumnsOfCluster, TopMultimaps};
 pub use self::csv_column_extra::CsvColumnExtra;
+pub use self::custom_event_schema::{CustomAttrSpec, CustomEventSchema};
 pub use self::customer::{Customer, Network as CustomerNetwork, Update as CustomerUpdate};
 pub use self::data_source::{DataSource, DataType, Update as DataSourceUpdate};
+pub use self::event_dedup::{DedupEntry, DedupPolicy};
+pub(crate) use self::event_enrichment::Enricher;
+pub use self::event_enrichment::{AddrEnrichment, EventEnrichment};
 pub use self::external_service::{ExternalService, ExternalServiceKind};
 pub use self::filter::{Filter, PeriodForSearch, Value as FilterValue};
 pub use self::hosts::{Host, UserAgent};
+pub(crate) use self::incident::CorrelationInput;
+pub use self::incident::{CorrelationKey, CorrelationPolicy, Incident};
+pub(crate) use self::log_template::RegexCache;
+pub use self::log_template::{LogParser, LogTemplate};
 pub use self::model::Model;
 pub use self::model_indicator::ModelIndicator;
 pub use self::network::{Network, Update as NetworkUpdate};
@@ -61,15 +78,21 @@ pub use self::node::{
     Update as NodeUpdate,
 };
 pub use self::outlier_info::{Key as OutlierInfoKey, OutlierInfo, Value as OutlierInfoValue};
+pub use self::role::{CustomRole, Update as RoleUpdate};
 pub use self::sampling_policy::{
     Interval as SamplingInterval, Kind as SamplingKind, Period as SamplingPeriod, SamplingPolicy,
     Update as SamplingPolicyUpdate,
 };
+pub use self::sensor::{Sensor, SensorIngest};
+pub use self::session::{Session, SessionLimitAction};
+pub use self::severity_override::{SeverityOverride, SeverityOverrides, SeverityScope};
 pub use self::template::{
     Structured, StructuredClusteringAlgorithm, Template, Unstructured,
     UnstructuredClusteringAlgorithm,
 };
-pub use self::tidb::{Kind as TidbKind, Rule as TidbRule, RuleKind as TidbRuleKind, Tidb};
+pub use self::tidb::{
+    Kind as TidbKind, Rule as TidbRule, RuleKind as TidbRuleKind, RuleTechniques, Tidb,
+};
 pub use self::time_series::{Cluster as ClusterTimeSeries, Column as ColumnTimeSeries, TimeSeries};
 pub use self::tor_exit_node::TorExitNode;
 pub use self::traffic_filter::{ProtocolPorts, TrafficFilter};
@@ -103,10 +126,16 @@ pub(super) const CLUSTER: &str = "cluster";
 pub(super) const COLUMN_STATS: &str = "column stats";
 pub(super) const CONFIGS: &str = "configs";
 pub(super) const CSV_COLUMN_EXTRAS: &str = "csv column extras";
+pub(super) const CUSTOM_EVENT_SCHEMAS: &str = "custom event schemas";
 pub(super) const CUSTOMERS: &str = "customers";
 pub(super) const DATA_SOURCES: &str = "data sources";
+pub(super) const EVENT_DEDUP_EXPIRY: &str = "event dedup expiry";
+pub(super) const EVENT_DEDUP_KEYS: &str = "event dedup keys";
+pub(super) const EVENT_ENRICHMENTS: &str = "event enrichments";
 pub(super) const FILTERS: &str = "filters";
 pub(super) const HOSTS: &str = "hosts";
+pub(super) const INCIDENTS: &str = "incidents";
+pub(super) const LOG_TEMPLATES: &str = "log templates";
 pub(super) const MODELS: &str = "models";
 pub(super) const MODEL_INDICATORS: &str = "model indicators";
 const META: &str = "meta";
@@ -114,9 +143,14 @@ pub(super) const NETWORKS: &str = "networks";
 pub(super) const NODES: &str = "nodes";
 pub(super) const OUTLIERS: &str = "outliers";
 pub(super) const QUALIFIERS: &str = "qualifiers";
+pub(super) const ROLES: &str = "roles";
 pub(super) const EXTERNAL_SERVICES: &str = "external services";
 pub(super) const SAMPLING_POLICY: &str = "sampling policy";
 pub(super) const SCORES: &str = "scores";
+pub(super) const SENSORS: &str = "sensors";
+pub(super) const SENSOR_INGEST: &str = "sensor ingest";
+pub(super) const SESSIONS: &str = "sessions";
+pub(super) const SEVERITY_OVERRIDES: &str = "severity overrides";
 pub(super) const STATUSES: &str = "statuses";
 pub(super) const TEMPLATES: &str = "templates";
 pub(super) const TIDB: &str = "TI database";
@@ -128,7 +162,7 @@ pub(super) const TRIAGE_RESPONSE: &str = "triage response";
 pub(super) const TRUSTED_DNS_SERVERS: &str = "trusted DNS servers";
 pub(super) const TRUSTED_USER_AGENTS: &str = "trusted user agents";
 
-pub(crate) const MAP_NAMES: [&str; 35] = [
+pub(crate) const MAP_NAMES: [&str; 46] = [
     ACCESS_TOKENS,
     ACCOUNTS,
     AGENTS,
@@ -140,10 +174,16 @@ pub(crate) const MAP_NAMES: [&str; 35] = [
     COLUMN_STATS,
     CONFIGS,
     CSV_COLUMN_EXTRAS,
+    CUSTOM_EVENT_SCHEMAS,
     CUSTOMERS,
     DATA_SOURCES,
+    EVENT_DEDUP_EXPIRY,
+    EVENT_DEDUP_KEYS,
+    EVENT_ENRICHMENTS,
     FILTERS,
     HOSTS,
+    INCIDENTS,
+    LOG_TEMPLATES,
     MODELS,
     MODEL_INDICATORS,
     META,
@@ -151,9 +191,14 @@ pub(crate) const MAP_NAMES: [&str; 35] = [
     NODES,
     OUTLIERS,
     QUALIFIERS,
+    ROLES,
     EXTERNAL_SERVICES,
     SAMPLING_POLICY,
     SCORES,
+    SENSORS,
+    SENSOR_INGEST,
+    SESSIONS,
+    SEVERITY_OVERRIDES,
     STATUSES,
     TEMPLATES,
     TIDB,
@@ -217,6 +262,18 @@ impl StateDb {
         Table::<BatchInfo>::open(inner).expect("{BATCH_INFO} table must be present")
     }
 
+    #[must_use]
+    pub(crate) fn event_dedup_keys(&self) -> Table<'_, DedupEntry> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<DedupEntry>::open(inner).expect("{EVENT_DEDUP_KEYS} table must be present")
+    }
+
+    #[must_use]
+    pub(crate) fn event_enrichments(&self) -> Table<'_, EventEnrichment> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<EventEnrichment>::open(inner).expect("{EVENT_ENRICHMENTS} table must be present")
+    }
+
     #[must_use]
     pub(crate) fn filters(&self) -> Table<'_, Filter> {
         let inner = self.inner.as_ref().expect("database must be open");
@@ -259,6 +316,12 @@ impl StateDb {
         IndexedTable::<Qualifier>::open(inner).expect("{QUALIFIERS} table must be present")
     }
 
+    #[must_use]
+    pub(crate) fn roles(&self) -> IndexedTable<'_, CustomRole> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        IndexedTable::<CustomRole>::open(inner).expect("{ROLES} table must be present")
+    }
+
     #[must_use]
     pub(crate) fn statuses(&self) -> IndexedTable<'_, Status> {
         let inner = self.inner.as_ref().expect("database must be open");
@@ -396,6 +459,49 @@ impl StateDb {
         Table::<Host>::open(inner).expect("{HOSTS} table must be present")
     }
 
+    #[must_use]
+    pub(crate) fn custom_event_schemas(&self) -> Table<'_, CustomEventSchema> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<CustomEventSchema>::open(inner)
+            .expect("{CUSTOM_EVENT_SCHEMAS} table must be present")
+    }
+
+    #[must_use]
+    pub(crate) fn log_templates(&self) -> Table<'_, LogTemplate> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<LogTemplate>::open(inner).expect("{LOG_TEMPLATES} table must be present")
+    }
+
+    #[must_use]
+    pub(crate) fn incidents(&self) -> Table<'_, Incident> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<Incident>::open(inner).expect("{INCIDENTS} table must be present")
+    }
+
+    #[must_use]
+    pub(crate) fn sensors(&self) -> Table<'_, Sensor> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<Sensor>::open(inner).expect("{SENSORS} table must be present")
+    }
+
+    #[must_use]
+    pub(crate) fn sensor_ingest(&self) -> Table<'_, SensorIngest> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<SensorIngest>::open(inner).expect("{SENSOR_INGEST} table must be present")
+    }
+
+    #[must_use]
+    pub(crate) fn sessions(&self) -> Table<'_, Session> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<Session>::open(inner).expect("{SESSIONS} table must be present")
+    }
+
+    #[must_use]
+    pub(crate) fn severity_overrides(&self) -> Table<'_, SeverityOverride> {
+        let inner = self.inner.as_ref().expect("database must be open");
+        Table::<SeverityOverride>::open(inner).expect("{SEVERITY_OVERRIDES} table must be present")
+    }
+
     #[must_use]
     pub(super) fn indexed_set(&self, name: &'static [u8]) -> Option<IndexedSet<'_>> {
         let inner = self.inner.as_ref().expect("database must be open");
@@ -736,11 +842,12 @@ impl<'d, R> IndexedTable<'d, R> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the database operation fails.
+    /// Returns an error if the record cannot be stored or the database operation fails.
     pub fn put(&self, entry: R) -> Result<u32>
     where
         R: Indexable,
     {
+        entry.check_insert(self.indexed_map.db())?;
         self.indexed_map.insert(entry)
     }
 
@@ -748,7 +855,7 @@ impl<'d, R> IndexedTable<'d, R> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the database operation fails.
+    /// Returns an error if the record cannot be stored or the database operation fails.
     pub fn put_with_transaction(
         &self,
         entry: R,
@@ -757,6 +864,7 @@ impl<'d, R> IndexedTable<'d, R> {
     where
         R: Indexable,
     {
+        entry.check_insert(self.indexed_map.db())?;
         self.indexed_map.insert_with_transaction(entry, txn)
     }
 
@@ -764,11 +872,12 @@ impl<'d, R> IndexedTable<'d, R> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the database operation fails.
+    /// Returns an error if the record cannot be removed or the database operation fails.
     pub fn remove(&self, id: u32) -> Result<Vec<u8>>
     where
         R: Indexable,
     {
+        R::check_remove(self.indexed_map.db(), id)?;
         self.indexed_map.remove::<R>(id)
     }
 
@@ -776,7 +885,7 @@ impl<'d, R> IndexedTable<'d, R> {
     ///
     /// # Errors
     ///
-    /// Returns an error if the database operation fails.
+    /// Returns an error if the record cannot be removed or the database operation fails.
     pub fn remove_with_transaction(
         &self,
         id: u32,
@@ -785,6 +894,7 @@ impl<'d, R> IndexedTable<'d, R> {
     where
         R: Indexable,
     {
+        R::check_remove(self.indexed_map.db(), id)?;
         self.indexed_map.remove_with_transaction::<R>(id, txn)
     }
 
@@ -1,37 +1,85 @@
 //! The `access_token` map.
+//!
+//! Tokens are not stored. Each is stored as its HMAC-SHA256 under a key kept outside the database,
+//! so that the database or its backup cannot be used to replay tokens.
 
-use anyhow::Result;
-use rocksdb::OptimisticTransactionDB;
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use ring::hmac;
+use rocksdb::{IteratorMode, OptimisticTransactionDB};
+use serde::{Deserialize, Serialize};
 
 use super::TableIter;
-use crate::{Iterable, Map, Table, types::FromKeyValue};
+use crate::{EXCLUSIVE, Iterable, Map, Table, types::FromKeyValue};
 
 #[derive(Debug, PartialEq)]
 pub struct AccessToken {
     pub username: String,
-    pub token: String,
+    /// The HMAC-SHA256 of the token.
+    pub token_hash: Vec<u8>,
+    /// The scopes the token grants access to.
+    pub scopes: Vec<String>,
+    pub creation_time: DateTime<Utc>,
+    /// When the token expires, or `None` if it never expires.
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
 }
 
 impl AccessToken {
-    fn create_key_value(username: &str, token: &str) -> (Vec<u8>, Vec<u8>) {
+    #[must_use]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    #[must_use]
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+
+    fn create_key(username: &str, token_hash: &[u8]) -> Vec<u8> {
         let mut key = username.as_bytes().to_owned();
         key.push(0);
-        key.extend(token.as_bytes());
-        (key, vec![])
+        key.extend(token_hash);
+        key
     }
 }
 
-impl FromKeyValue for AccessToken {
-    fn from_key_value(key: &[u8], _value: &[u8]) -> Result<Self> {
-        use anyhow::anyhow;
+/// The metadata of a token, stored as the value.
+#[derive(Deserialize, Serialize)]
+struct Metadata {
+    scopes: Vec<String>,
+    creation_time: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<&AccessToken> for Metadata {
+    fn from(token: &AccessToken) -> Self {
+        Self {
+            scopes: token.scopes.clone(),
+            creation_time: token.creation_time,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+        }
+    }
+}
 
+impl FromKeyValue for AccessToken {
+    fn from_key_value(key: &[u8], value: &[u8]) -> Result<Self> {
         let sep = key
             .iter()
             .position(|c| *c == 0)
             .ok_or(anyhow!("corruptted access token"))?;
         let username = String::from_utf8_lossy(&key[..sep]).into_owned();
-        let token = String::from_utf8_lossy(&key[sep + 1..]).into_owned();
-        Ok(AccessToken { username, token })
+        let metadata: Metadata = super::deserialize(value)?;
+        Ok(AccessToken {
+            username,
+            token_hash: key[sep + 1..].to_vec(),
+            scopes: metadata.scopes,
+            creation_time: metadata.creation_time,
+            expires_at: metadata.expires_at,
+            last_used_at: metadata.last_used_at,
+        })
     }
 }
 
@@ -44,35 +92,85 @@ impl<'d> Table<'d, AccessToken> {
         Map::open(db, super::ACCESS_TOKENS).map(Table::new)
     }
 
-    /// Inserts `(username, token)` into map in the database.
+    /// Inserts the hash of `token` for `username` under `key`, with the scopes it grants access
+    /// to and its expiry time.
     ///
     /// # Errors
     ///
-    /// Returns an error if the database operation fails.
-    pub fn insert(&self, username: &str, token: &str) -> Result<()> {
-        let (key, value) = AccessToken::create_key_value(username, token);
-        self.map.insert(&key, &value)
+    /// Returns an error if `key` is empty, the token already exists, or the database operation
+    /// fails.
+    pub fn insert(
+        &self,
+        username: &str,
+        token: &str,
+        scopes: &[String],
+        expires_at: Option<DateTime<Utc>>,
+        key: &[u8],
+    ) -> Result<()> {
+        let metadata = Metadata {
+            scopes: scopes.to_vec(),
+            creation_time: Utc::now(),
+            expires_at,
+            last_used_at: None,
+        };
+        self.map.insert(
+            &AccessToken::create_key(username, hash(token, key)?.as_ref()),
+            &super::serialize(&metadata)?,
+        )
     }
 
-    /// Removes `(username, token)` from map in the database.
+    /// Removes `token` of `username` from map in the database.
     ///
     /// # Errors
     ///
-    /// Returns an error if the combo does not exist or the database operation fails.
-    pub fn revoke(&self, username: &str, token: &str) -> Result<()> {
-        let (key, _value) = AccessToken::create_key_value(username, token);
-
-        self.map.delete(&key)
+    /// Returns an error if `key` is empty, the combo does not exist, or the database operation
+    /// fails.
+    pub fn revoke(&self, username: &str, token: &str, key: &[u8]) -> Result<()> {
+        self.map.delete(&AccessToken::create_key(
+            username,
+            hash(token, key)?.as_ref(),
+        ))
     }
 
-    /// Finds whether `username` `token` exists in the database.
+    /// Returns the token of `username` if it exists and has not expired, and records that it
+    /// has been used.
     ///
     /// # Errors
     ///
-    /// Returns an error if the database operation fails.
-    pub fn contains(&self, username: &str, token: &str) -> Result<bool> {
-        let (key, _value) = AccessToken::create_key_value(username, token);
-        self.map.get(&key).map(|v| v.is_some())
+    /// Returns an error if `key` is empty, the database operation fails, or the stored token is
+    /// invalid.
+    pub fn verify(&self, username: &str, token: &str, key: &[u8]) -> Result<Option<AccessToken>> {
+        let db_key = AccessToken::create_key(username, hash(token, key)?.as_ref());
+        let now = Utc::now();
+        loop {
+            let txn = self.map.db.transaction();
+            let Some(value) = txn
+                .get_for_update_cf(self.map.cf, &db_key, EXCLUSIVE)
+                .context("cannot read access token")?
+            else {
+                return Ok(None);
+            };
+            let mut found = AccessToken::from_key_value(&db_key, &value)?;
+            if found.is_expired(now) {
+                return Ok(None);
+            }
+
+            found.last_used_at = Some(now);
+            txn.put_cf(
+                self.map.cf,
+                &db_key,
+                super::serialize(&Metadata::from(&found))?,
+            )
+            .context("cannot update access token")?;
+            match txn.commit() {
+                Ok(()) => return Ok(Some(found)),
+                Err(e) => {
+                    if !e.as_ref().starts_with("Resource busy:") {
+                        return Err(e).context("failed to verify access token");
+                    }
+                }
+            }
+        }
     }
 
     /// Finds all tokens for `username` in the database.
@@ -84,14 +182,46 @@ impl<'d> Table<'d, AccessToken> {
         prefix.push(0);
         self.prefix_iter(Direction::Forward, None, &prefix)
     }
+
+    /// Removes the expired tokens. Returns the number of tokens removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored token is invalid.
+    pub fn purge_expired(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut purged = 0;
+        for item in self.map.db.iterator_cf(self.map.cf, IteratorMode::Start) {
+            let (key, value) = item.map_err(|e| anyhow!("database error: {e}"))?;
+            if AccessToken::from_key_value(&key, &value)?.is_expired(now) {
+                self.map.delete(&key)?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+}
+
+fn hash(token: &str, key: &[u8]) -> Result<hmac::Tag> {
+    if key.is_empty() {
+        bail!("access token key must not be empty");
+    }
+    Ok(hmac::sign(
+        &hmac::Key::new(hmac::HMAC_SHA256, key),
+        token.as_bytes(),
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
+    use chrono::{TimeDelta, Utc};
+
     use crate::Store;
 
+    const KEY: &[u8] = b"access token key";
+
     #[test]
     fn operations() {
         let store = setup_store();
@@ -100,20 +230,64 @@ mod tests {
 
         for (count, name) in names.iter().enumerate() {
             for i in 0..=count {
-                assert!(table.insert(name, &i.to_string()).is_ok());
+                assert!(table.insert(name, &i.to_string(), &[], None, KEY).is_ok());
             }
         }
 
         for (count, name) in names.iter().enumerate() {
             assert_eq!(count + 1, table.tokens(name).count());
             for i in 0..=count {
-                assert!(table.contains(name, &i.to_string()).unwrap());
+                assert!(table.verify(name, &i.to_string(), KEY).unwrap().is_some());
             }
-            assert!(table.revoke(name, &0.to_string()).is_ok());
-            assert!(!table.contains(name, &0.to_string()).unwrap());
+            assert!(table.revoke(name, &0.to_string(), KEY).is_ok());
+            assert!(table.verify(name, &0.to_string(), KEY).unwrap().is_none());
         }
     }
 
+    #[test]
+    fn hashed_scoped_and_expiring() {
+        let store = setup_store();
+        let table = store.access_token_map();
+        let scopes = vec!["events:read".to_string()];
+
+        table.insert("abc", "token", &scopes, None, KEY).unwrap();
+        table
+            .insert(
+                "abc",
+                "expired",
+                &scopes,
+                Some(Utc::now() - TimeDelta::seconds(1)),
+                KEY,
+            )
+            .unwrap();
+        assert!(table.insert("abc", "token", &scopes, None, KEY).is_err());
+
+        for stored in table.tokens("abc") {
+            let stored = stored.unwrap();
+            assert_ne!(stored.token_hash, b"token");
+            assert_eq!(stored.last_used_at, None);
+        }
+        assert!(
+            table
+                .verify("abc", "token", b"other key")
+                .unwrap()
+                .is_none()
+        );
+        assert!(table.verify("def", "token", KEY).unwrap().is_none());
+        assert!(table.insert("abc", "other", &scopes, None, b"").is_err());
+        assert!(table.verify("abc", "token", b"").is_err());
+        assert!(table.revoke("abc", "token", b"").is_err());
+        assert!(table.verify("abc", "expired", KEY).unwrap().is_none());
+
+        let verified = table.verify("abc", "token", KEY).unwrap().unwrap();
+        assert!(verified.has_scope("events:read"));
+        assert!(!verified.has_scope("events:write"));
+        assert!(verified.last_used_at.is_some());
+
+        assert_eq!(table.purge_expired().unwrap(), 1);
+        assert_eq!(table.tokens("abc").count(), 1);
+    }
+
     fn setup_store() -> Arc<Store> {
         let db_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
@@ -1,14 +1,13 @@
 //! The accounts table.
 
-use std::net::IpAddr;
-
 use anyhow::{Context, bail};
 use bincode::Options;
 use chrono::Utc;
-use rocksdb::OptimisticTransactionDB;
+use rocksdb::{OptimisticTransactionDB, Transaction};
 
 use crate::{
-    EXCLUSIVE, Map, Role, Table,
+    CustomRole, EXCLUSIVE, HostNetworkGroup, IndexedTable, LockoutState, Map, PasswordStatus,
+    Permission, RoleId, Session, Table,
     types::{Account, FromKeyValue},
 };
 
@@ -36,13 +35,68 @@ impl<'d> Table<'d, Account> {
         self.map.get(username.as_bytes()).map(|v| v.is_some())
     }
 
-    /// Deletes an account with the given username.
+    /// Creates an account with the given information, checking its password
+    /// against the stored password policy, and adds it to the table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the following cases:
+    ///
+    /// * The password violates the password policy, in which case the error is
+    ///   a `PasswordPolicyError`.
+    /// * The role is a custom role that does not exist.
+    /// * An account with the same username exists.
+    /// * Random number generation for a password salt fails, the stored policy
+    ///   is invalid, or the database operation fails.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        &self,
+        username: &str,
+        password: &str,
+        role: RoleId,
+        name: String,
+        department: String,
+        language: Option<String>,
+        theme: Option<String>,
+        allow_access_from: Option<HostNetworkGroup>,
+        max_parallel_sessions: Option<u8>,
+        customer_ids: Option<Vec<u32>>,
+    ) -> Result<(), anyhow::Error> {
+        let policy = Table::<String>::open(self.map.db)
+            .context("configs table must be present")?
+            .password_policy()?;
+        if let RoleId::Custom(id) = role
+            && IndexedTable::<CustomRole>::open(self.map.db)
+                .context("roles table must be present")?
+                .get_by_id(id)?
+                .is_none()
+        {
+            bail!("no such role");
+        }
+        let account = Account::new(
+            username,
+            password,
+            role,
+            name,
+            department,
+            language,
+            theme,
+            allow_access_from,
+            max_parallel_sessions,
+            customer_ids,
+            &policy,
+        )?;
+        self.insert(&account)
+    }
+
+    /// Deletes an account with the given username, and revokes its sessions.
     ///
     /// # Errors
     ///
     /// Returns an error if the account does not exist or the database operation fails.
     pub fn delete(&self, username: &str) -> Result<(), anyhow::Error> {
-        self.map.delete(username.as_bytes())
+        self.map.delete(username.as_bytes())?;
+        self.revoke_sessions(username)
     }
 
     /// Returns an account with the given username.
@@ -57,7 +111,65 @@ impl<'d> Table<'d, Account> {
         Ok(Some(super::deserialize(value.as_ref())?))
     }
 
-    /// Updates an entry in account map.
+    /// Returns an account with the given username, read in the transaction so that the
+    /// transaction fails to commit if the account changes in the meantime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub(super) fn get_with_transaction(
+        &self,
+        username: &str,
+        txn: &Transaction<'_, OptimisticTransactionDB>,
+    ) -> Result<Option<Account>, anyhow::Error> {
+        let Some(value) = txn
+            .get_for_update_cf(self.map.cf, username.as_bytes(), EXCLUSIVE)
+            .context("cannot read account")?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(super::deserialize(value.as_ref())?))
+    }
+
+    /// Returns the state of the password of an account with the given
+    /// username under the stored password policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account does not exist, the stored policy is
+    /// invalid, or the database operation fails.
+    pub fn password_status(&self, username: &str) -> Result<PasswordStatus, anyhow::Error> {
+        let Some(account) = self.get(username)? else {
+            bail!("no such entry");
+        };
+        let policy = Table::<String>::open(self.map.db)
+            .context("configs table must be present")?
+            .password_policy()?;
+        Ok(account.password_status(&policy, Utc::now()))
+    }
+
+    /// Returns `true` if the role of an account with the given username
+    /// grants `permission`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account does not exist or the database
+    /// operation fails.
+    pub fn is_permitted(
+        &self,
+        username: &str,
+        permission: Permission,
+    ) -> Result<bool, anyhow::Error> {
+        let Some(account) = self.get(username)? else {
+            bail!("no such entry");
+        };
+        IndexedTable::<CustomRole>::open(self.map.db)
+            .context("roles table must be present")?
+            .is_permitted(account.role, permission)
+    }
+
+    /// Updates an entry in account map. Changing the password revokes the
+    /// sessions of the account.
     ///
     /// # Errors
     ///
@@ -65,6 +177,9 @@ impl<'d> Table<'d, Account> {
     ///
     /// * The account stored in the database in invalid.
     /// * Random number generation for a password salt fails.
+    /// * The new password violates the password policy, in which case the
+    ///   error is a `PasswordPolicyError`.
+    /// * The new role is a custom role that does not exist.
     /// * The old values do not match the values in the database.
     /// * The underlying database operation fails.
     #[allow(clippy::too_many_arguments, clippy::type_complexity)]
@@ -72,15 +187,26 @@ impl<'d> Table<'d, Account> {
         &self,
         username: &[u8],
         new_password: &Option<String>,
-        role: Option<(Role, Role)>,
+        role: Option<(RoleId, RoleId)>,
         name: &Option<(String, String)>,
         department: &Option<(String, String)>,
         language: &Option<(Option<String>, Option<String>)>,
         theme: &Option<(Option<String>, Option<String>)>,
-        allow_access_from: &Option<(Option<Vec<IpAddr>>, Option<Vec<IpAddr>>)>,
+        allow_access_from: &Option<(Option<HostNetworkGroup>, Option<HostNetworkGroup>)>,
         max_parallel_sessions: &Option<(Option<u8>, Option<u8>)>,
         customer_ids: &Option<(Option<Vec<u32>>, Option<Vec<u32>>)>,
     ) -> Result<(), anyhow::Error> {
+        let policy = Table::<String>::open(self.map.db)
+            .context("configs table must be present")?
+            .password_policy()?;
+        if let Some((_, RoleId::Custom(id))) = role
+            && IndexedTable::<CustomRole>::open(self.map.db)
+                .context("roles table must be present")?
+                .get_by_id(id)?
+                .is_none()
+        {
+            bail!("no such role");
+        }
         loop {
             let txn = self.map.db.transaction();
             if let Some(old_value) = txn
@@ -90,7 +216,7 @@ impl<'d> Table<'d, Account> {
                 let mut account = super::deserialize::<Account>(old_value.as_ref())?;
 
                 if let Some(password) = &new_password {
-                    account.update_password(password)?;
+                    account.update_password(password, &policy)?;
                 }
 
                 if let Some((old, new)) = &role {
@@ -158,98 +284,113 @@ impl<'d> Table<'d, Account> {
                 }
             }
         }
+        if new_password.is_some() {
+            self.revoke_sessions(&String::from_utf8_lossy(username))?;
+        }
         Ok(())
     }
 
     /// Increments the failed login attempts for an account with the given username.
-    /// If the account reaches the lockout threshold, it will be locked out.
+    /// If the account reaches the lockout threshold of the stored lockout policy,
+    /// it will be locked out, permanently if it has been locked out as many times
+    /// as the policy allows.
     ///
     /// # Errors
     ///
-    /// Returns an error if the account does not exist or the database operation fails.
+    /// Returns an error if the account does not exist, the stored policy is
+    /// invalid, or the database operation fails.
     pub fn increment_failed_login(&self, username: &str) -> Result<(), anyhow::Error> {
-        const LOCKOUT_THRESHOLD: u8 = 5;
-        const LOCKOUT_DURATION_MINUTES: i64 = 30;
-
-        loop {
-            let txn = self.map.db.transaction();
-            if let Some(old_value) = txn
-                .get_for_update_cf(self.map.cf, username.as_bytes(), EXCLUSIVE)
-                .context("cannot read old entry")?
-            {
-                let options = bincode::DefaultOptions::new();
-                let Ok(mut account) = options.deserialize::<Account>(old_value.as_ref()) else {
-                    return Err(anyhow::anyhow!("Failed to deserialize account data"));
-                };
-
-                account.failed_login_attempts = account.failed_login_attempts.saturating_add(1);
-
-                if account.failed_login_attempts >= LOCKOUT_THRESHOLD {
-                    account.locked_out_until =
-                        Some(Utc::now() + chrono::Duration::minutes(LOCKOUT_DURATION_MINUTES));
-                }
-
-                let value = bincode::DefaultOptions::new().serialize(&account)?;
-                txn.put_cf(self.map.cf, username.as_bytes(), value)
-                    .context("failed to write updated entry")?;
-            } else {
-                bail!("no such entry");
-            }
-
-            match txn.commit() {
-                Ok(()) => break,
-                Err(e) => {
-                    if !e.as_ref().starts_with("Resource busy:") {
-                        return Err(e).context("failed to increment failed login");
-                    }
-                }
-            }
-        }
-        Ok(())
+        let policy = Table::<String>::open(self.map.db)
+            .context("configs table must be present")?
+            .lockout_policy()?;
+        self.modify(username, "failed to increment failed login", |account| {
+            account.record_failed_login(&policy, Utc::now());
+        })
     }
 
     /// Clears the failed login attempts for an account with the given username.
-    /// Also unlocks the account if it was locked out.
+    /// Also unlocks the account if it was locked out temporarily, and resets the
+    /// number of lockouts. A permanent lockout is kept; use `unlock_account`.
     ///
     /// # Errors
     ///
     /// Returns an error if the account does not exist or the database operation fails.
     pub fn clear_failed_logins(&self, username: &str) -> Result<(), anyhow::Error> {
-        loop {
-            let txn = self.map.db.transaction();
-            if let Some(old_value) = txn
-                .get_for_update_cf(self.map.cf, username.as_bytes(), EXCLUSIVE)
-                .context("cannot read old entry")?
-            {
-                let options = bincode::DefaultOptions::new();
-                let Ok(mut account) = options.deserialize::<Account>(old_value.as_ref()) else {
-                    return Err(anyhow::anyhow!("Failed to deserialize account data"));
-                };
+        self.modify(username, "failed to clear failed logins", |account| {
+            account.failed_login_attempts = 0;
+            account.locked_out_until = None;
+            account.lockout_count = 0;
+        })
+    }
 
-                account.failed_login_attempts = 0;
-                account.locked_out_until = None;
+    /// Unlocks an account with the given username, including a permanent
+    /// lockout, and clears its failed login attempts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account does not exist or the database operation fails.
+    pub fn unlock_account(&self, username: &str) -> Result<(), anyhow::Error> {
+        self.modify(username, "failed to unlock account", |account| {
+            account.failed_login_attempts = 0;
+            account.locked_out_until = None;
+            account.lockout_count = 0;
+            account.is_locked_permanently = false;
+        })
+    }
 
-                let value = bincode::DefaultOptions::new().serialize(&account)?;
-                txn.put_cf(self.map.cf, username.as_bytes(), value)
-                    .context("failed to write updated entry")?;
-            } else {
-                bail!("no such entry");
+    /// Verifies a TOTP code for an account with the given username, and
+    /// stores the step of the code so that it cannot be used again. See
+    /// `Account::verify_totp`. A code that does not match counts as a failed
+    /// login under the stored lockout policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account does not exist or is not enrolled in
+    /// TOTP, the secret cannot be decrypted with `key`, the stored policy is
+    /// invalid, or the database operation fails.
+    pub fn verify_totp(
+        &self,
+        username: &str,
+        code: &str,
+        key: &[u8],
+    ) -> Result<bool, anyhow::Error> {
+        let policy = Table::<String>::open(self.map.db)
+            .context("configs table must be present")?
+            .lockout_policy()?;
+        self.modify(username, "failed to verify TOTP code", |account| {
+            let now = Utc::now();
+            let verified = account.verify_totp(code, key, now)?;
+            if !verified {
+                account.record_failed_login(&policy, now);
             }
+            Ok(verified)
+        })?
+    }
 
-            match txn.commit() {
-                Ok(()) => break,
-                Err(e) => {
-                    if !e.as_ref().starts_with("Resource busy:") {
-                        return Err(e).context("failed to clear failed logins");
-                    }
-                }
+    /// Signs in to an account with the given username with a recovery code,
+    /// which cannot be used again. Returns `false` if the code does not match,
+    /// which counts as a failed login under the stored lockout policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the account does not exist, the stored policy is
+    /// invalid, or the database operation fails.
+    pub fn use_recovery_code(&self, username: &str, code: &str) -> Result<bool, anyhow::Error> {
+        let policy = Table::<String>::open(self.map.db)
+            .context("configs table must be present")?
+            .lockout_policy()?;
+        self.modify(username, "failed to use recovery code", |account| {
+            let used = account.use_recovery_code(code);
+            if !used {
+                account.record_failed_login(&policy, Utc::now());
             }
-        }
-        Ok(())
+            used
+        })
     }
 
     /// Checks if an account is currently locked out.
-    /// Automatically unlocks accounts whose lockout period has expired.
+    /// Automatically unlocks accounts whose lockout period has expired, keeping
+    /// the number of lockouts for progressive lockout durations.
     ///
     /// # Errors
     ///
@@ -259,19 +400,60 @@ impl<'d> Table<'d, Account> {
             bail!("no such entry");
         };
 
-        if let Some(locked_until) = account.locked_out_until {
-            if Utc::now() >= locked_until {
-                self.clear_failed_logins(username)?;
+        let now = Utc::now();
+        match account.lockout_state(now) {
+            LockoutState::Unlocked => {
+                if account.locked_out_until.is_some() {
+                    self.modify(username, "failed to unlock account", |account| {
+                        if account.lockout_state(now) == LockoutState::Unlocked {
+                            account.failed_login_attempts = 0;
+                            account.locked_out_until = None;
+                        }
+                    })?;
+                }
                 Ok(false)
-            } else {
-                Ok(true)
             }
-        } else {
-            Ok(false)
+            LockoutState::Locked(_) | LockoutState::LockedPermanently => Ok(true),
+        }
+    }
+
+    /// Applies `f` to the account with the given username and stores it.
+    /// Returns the output of `f`.
+    fn modify<T, F>(&self, username: &str, error: &'static str, f: F) -> Result<T, anyhow::Error>
+    where
+        F: Fn(&mut Account) -> T,
+    {
+        loop {
+            let txn = self.map.db.transaction();
+            let Some(old_value) = txn
+                .get_for_update_cf(self.map.cf, username.as_bytes(), EXCLUSIVE)
+                .context("cannot read old entry")?
+            else {
+                bail!("no such entry");
+            };
+            let options = bincode::DefaultOptions::new();
+            let Ok(mut account) = options.deserialize::<Account>(old_value.as_ref()) else {
+                return Err(anyhow::anyhow!("Failed to deserialize account data"));
+            };
+
+            let output = f(&mut account);
+
+            let value = bincode::DefaultOptions::new().serialize(&account)?;
+            txn.put_cf(self.map.cf, username.as_bytes(), value)
+                .context("failed to write updated entry")?;
+
+            match txn.commit() {
+                Ok(()) => return Ok(output),
+                Err(e) => {
+                    if !e.as_ref().starts_with("Resource busy:") {
+                        return Err(e).context(error);
+                    }
+                }
+            }
         }
     }
 
-    /// Suspends an account with the given username.
+    /// Suspends an account with the given username, and revokes its sessions.
     ///
     /// # Errors
     ///
@@ -306,7 +488,7 @@ impl<'d> Table<'d, Account> {
                 }
             }
         }
-        Ok(())
+        self.revoke_sessions(username)
     }
 
     /// Unsuspends an account with the given username.
@@ -347,20 +529,33 @@ impl<'d> Table<'d, Account> {
         Ok(())
     }
 
-    /// Returns all accounts with their security status information.
+    fn revoke_sessions(&self, username: &str) -> Result<(), anyhow::Error> {
+        Table::<Session>::open(self.map.db)
+            .context("sessions table must be present")?
+            .revoke_all(username)?;
+        Ok(())
+    }
+
+    /// Returns all accounts with their security status information, including
+    /// whether they are locked out and for how long.
     /// This method is useful for administrative dashboards showing user security states.
     ///
     /// # Errors
     ///
     /// Returns an error if the database operation fails.
-    pub fn get_accounts_with_security_status(&self) -> Result<Vec<Account>, anyhow::Error> {
+    pub fn get_accounts_with_security_status(
+        &self,
+    ) -> Result<Vec<(Account, LockoutState)>, anyhow::Error> {
         use crate::Iterable;
 
+        let now = Utc::now();
         let mut accounts = Vec::new();
         let iter = self.iter(rocksdb::Direction::Forward, None);
 
         for account in iter {
-            accounts.push(account?);
+            let account = account?;
+            let lockout = account.lockout_state(now);
+            accounts.push((account, lockout));
         }
 
         Ok(accounts)
@@ -371,7 +566,11 @@ impl<'d> Table<'d, Account> {
 mod tests {
     use std::sync::Arc;
 
-    use crate::{Role, Store, tables::Direction, types::Account};
+    use crate::{
+        Action, CustomRole, LockoutPolicy, LockoutState, PasswordPolicy, PasswordPolicyError,
+        PasswordStatus, Permission, Resource, Role, RoleId, Store, tables::Direction,
+        types::Account,
+    };
 
     #[test]
     fn put_delete() {
@@ -384,7 +583,7 @@ mod tests {
         let acc1 = Account::new(
             "user1",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1".to_string(),
             "Department 1".to_string(),
             None,
@@ -392,6 +591,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&acc1).unwrap();
@@ -400,7 +600,7 @@ mod tests {
         let acc2 = Account::new(
             "user2",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 2".to_string(),
             "Department 2".to_string(),
             None,
@@ -408,6 +608,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&acc2).unwrap();
@@ -417,6 +618,46 @@ mod tests {
         assert!(!table.contains("user1").unwrap());
     }
 
+    #[test]
+    fn create() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.account_map();
+        store
+            .config_map()
+            .set_password_policy(&PasswordPolicy {
+                min_length: 12,
+                ..PasswordPolicy::default()
+            })
+            .unwrap();
+
+        let create = |username: &str, password: &str, role: RoleId| {
+            table.create(
+                username,
+                password,
+                role,
+                "User".to_string(),
+                "Department".to_string(),
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+        let err = create("user1", "password", Role::SystemAdministrator.into()).unwrap_err();
+        assert!(err.downcast_ref::<PasswordPolicyError>().is_some());
+        assert!(!table.contains("user1").unwrap());
+        assert!(create("user1", "long password", RoleId::Custom(1)).is_err());
+
+        create("user1", "long password", Role::SystemAdministrator.into()).unwrap();
+        let account = table.get("user1").unwrap().unwrap();
+        assert!(account.verify_password("long password"));
+        assert!(account.must_change_password());
+        assert!(create("user1", "other password", Role::SystemAdministrator.into()).is_err());
+    }
+
     #[test]
     fn iter() {
         use crate::Iterable;
@@ -432,7 +673,7 @@ mod tests {
         let acc1 = Account::new(
             "user1",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1".to_string(),
             "Department 1".to_string(),
             None,
@@ -440,6 +681,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&acc1).unwrap();
@@ -452,7 +694,7 @@ mod tests {
         let acc2 = Account::new(
             "user2",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 2".to_string(),
             "Department 2".to_string(),
             None,
@@ -460,6 +702,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&acc2).unwrap();
@@ -490,7 +733,7 @@ mod tests {
         let acc1 = Account::new(
             "user1",
             "password1",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1".to_string(),
             "Department 1".to_string(),
             None,
@@ -498,6 +741,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&acc1).unwrap();
@@ -513,7 +757,7 @@ mod tests {
         let acc2 = Account::new(
             "user1",     // Same username
             "password2", // Different password
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1 Updated".to_string(), // Different name
             "Department 2".to_string(),   // Different department
             None,
@@ -521,6 +765,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&acc2).unwrap();
@@ -553,7 +798,7 @@ mod tests {
         let account = Account::new(
             "user1",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1".to_string(),
             "Department 1".to_string(),
             None,
@@ -561,6 +806,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&account).unwrap();
@@ -598,7 +844,7 @@ mod tests {
         let account = Account::new(
             "user1",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1".to_string(),
             "Department 1".to_string(),
             None,
@@ -606,6 +852,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&account).unwrap();
@@ -632,7 +879,7 @@ mod tests {
         let account1 = Account::new(
             "user1",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1".to_string(),
             "Department 1".to_string(),
             None,
@@ -640,6 +887,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&account1).unwrap();
@@ -647,7 +895,7 @@ mod tests {
         let account2 = Account::new(
             "user2",
             "password",
-            Role::SecurityMonitor,
+            Role::SecurityMonitor.into(),
             "User 2".to_string(),
             "Department 2".to_string(),
             None,
@@ -655,6 +903,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
         table.put(&account2).unwrap();
@@ -665,19 +914,313 @@ mod tests {
         let security_accounts = table.get_accounts_with_security_status().unwrap();
         assert_eq!(security_accounts.len(), 2);
 
-        let user1 = security_accounts
+        let (user1, lockout) = security_accounts
             .iter()
-            .find(|a| a.username == "user1")
+            .find(|(a, _)| a.username == "user1")
             .unwrap();
         assert!(user1.is_suspended);
         assert_eq!(user1.failed_login_attempts, 0);
+        assert_eq!(*lockout, LockoutState::Unlocked);
 
-        let user2 = security_accounts
+        let (user2, lockout) = security_accounts
             .iter()
-            .find(|a| a.username == "user2")
+            .find(|(a, _)| a.username == "user2")
             .unwrap();
         assert!(!user2.is_suspended);
         assert_eq!(user2.failed_login_attempts, 1);
+        assert_eq!(*lockout, LockoutState::Unlocked);
+
+        for _ in 0..4 {
+            table.increment_failed_login("user2").unwrap();
+        }
+        let security_accounts = table.get_accounts_with_security_status().unwrap();
+        let (_, lockout) = security_accounts
+            .iter()
+            .find(|(a, _)| a.username == "user2")
+            .unwrap();
+        let LockoutState::Locked(remaining) = *lockout else {
+            panic!("user2 should be locked out");
+        };
+        assert!(remaining > chrono::Duration::minutes(29));
+        assert!(remaining <= chrono::Duration::minutes(30));
+    }
+
+    #[test]
+    fn lockout_policy() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.account_map();
+        store
+            .config_map()
+            .set_lockout_policy(&LockoutPolicy {
+                threshold: 2,
+                observation_window_minutes: Some(10),
+                durations_minutes: vec![5, 60],
+                permanent_after: Some(3),
+            })
+            .unwrap();
+
+        let mut account = Account::new(
+            "user1",
+            "password",
+            Role::SystemAdministrator.into(),
+            "User 1".to_string(),
+            "Department 1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        account.failed_login_attempts = 1;
+        account.last_failed_login_at = Some(chrono::Utc::now() - chrono::Duration::minutes(15));
+        table.put(&account).unwrap();
+
+        // The earlier failure is outside the observation window.
+        table.increment_failed_login("user1").unwrap();
+        assert!(!table.is_account_locked("user1").unwrap());
+        table.increment_failed_login("user1").unwrap();
+        let locked = table.get("user1").unwrap().unwrap();
+        assert_eq!(locked.lockout_count, 1);
+        let LockoutState::Locked(remaining) = locked.lockout_state(chrono::Utc::now()) else {
+            panic!("user1 should be locked out");
+        };
+        assert!(remaining <= chrono::Duration::minutes(5));
+
+        // The second lockout lasts longer.
+        let mut expired = locked;
+        expired.locked_out_until = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        table.put(&expired).unwrap();
+        assert!(!table.is_account_locked("user1").unwrap());
+        table.increment_failed_login("user1").unwrap();
+        table.increment_failed_login("user1").unwrap();
+        let locked = table.get("user1").unwrap().unwrap();
+        assert_eq!(locked.lockout_count, 2);
+        let LockoutState::Locked(remaining) = locked.lockout_state(chrono::Utc::now()) else {
+            panic!("user1 should be locked out");
+        };
+        assert!(remaining > chrono::Duration::minutes(59));
+
+        // The third lockout is permanent.
+        let mut expired = locked;
+        expired.locked_out_until = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+        table.put(&expired).unwrap();
+        table.increment_failed_login("user1").unwrap();
+        table.increment_failed_login("user1").unwrap();
+        assert!(table.is_account_locked("user1").unwrap());
+        table.clear_failed_logins("user1").unwrap();
+        assert_eq!(
+            table
+                .get("user1")
+                .unwrap()
+                .unwrap()
+                .lockout_state(chrono::Utc::now()),
+            LockoutState::LockedPermanently
+        );
+        table.unlock_account("user1").unwrap();
+        assert!(!table.is_account_locked("user1").unwrap());
+    }
+
+    #[test]
+    fn password_status() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.account_map();
+
+        let mut account = Account::new(
+            "user1",
+            "password",
+            Role::SystemAdministrator.into(),
+            "User 1".to_string(),
+            "Department 1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        table.put(&account).unwrap();
+        assert_eq!(
+            table.password_status("user1").unwrap(),
+            PasswordStatus::MustChange
+        );
+        account
+            .update_password("changed", &PasswordPolicy::default())
+            .unwrap();
+        account.password_last_modified_at = chrono::Utc::now() - chrono::Duration::days(85);
+        table.put(&account).unwrap();
+        assert_eq!(table.password_status("user1").unwrap(), PasswordStatus::Ok);
+        assert!(table.password_status("user2").is_err());
+
+        store
+            .config_map()
+            .set_password_policy(&PasswordPolicy {
+                max_age_days: Some(90),
+                expiry_warning_days: 7,
+                ..PasswordPolicy::default()
+            })
+            .unwrap();
+        assert_eq!(
+            table.password_status("user1").unwrap(),
+            PasswordStatus::Expiring(4)
+        );
+
+        account.require_password_change();
+        table.put(&account).unwrap();
+        assert_eq!(
+            table.password_status("user1").unwrap(),
+            PasswordStatus::MustChange
+        );
+    }
+
+    #[test]
+    fn custom_role() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.account_map();
+        let read_events = Permission::new(Resource::Event, Action::Read);
+        let update_settings = Permission::new(Resource::Setting, Action::Update);
+
+        let account = Account::new(
+            "user1",
+            "password",
+            Role::SecurityMonitor.into(),
+            "User 1".to_string(),
+            "Department 1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        table.put(&account).unwrap();
+        assert!(table.is_permitted("user1", read_events).unwrap());
+        assert!(!table.is_permitted("user1", update_settings).unwrap());
+        assert!(table.is_permitted("user2", read_events).is_err());
+
+        let role = Some((Role::SecurityMonitor.into(), RoleId::Custom(0)));
+        assert!(
+            table
+                .update(
+                    b"user1", &None, role, &None, &None, &None, &None, &None, &None, &None
+                )
+                .is_err()
+        );
+        let id = store
+            .role_map()
+            .put(CustomRole {
+                id: u32::MAX,
+                name: "Settings Manager".to_string(),
+                description: String::new(),
+                permissions: vec![update_settings],
+                creation_time: chrono::Utc::now(),
+            })
+            .unwrap();
+        let role = Some((Role::SecurityMonitor.into(), RoleId::Custom(id)));
+        table
+            .update(
+                b"user1", &None, role, &None, &None, &None, &None, &None, &None, &None,
+            )
+            .unwrap();
+        assert!(!table.is_permitted("user1", read_events).unwrap());
+        assert!(table.is_permitted("user1", update_settings).unwrap());
+    }
+
+    #[test]
+    fn totp() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.account_map();
+        let key = [1_u8; 32];
+
+        let mut account = Account::new(
+            "user1",
+            "password",
+            Role::SystemAdministrator.into(),
+            "User 1".to_string(),
+            "Department 1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        table.put(&account).unwrap();
+        assert!(table.verify_totp("user1", "000000", &key).is_err());
+
+        let enrollment = account.enroll_totp(&key, "REview").unwrap();
+        table.put(&account).unwrap();
+        assert!(table.get("user1").unwrap().unwrap().has_totp());
+        assert!(table.verify_totp("user2", "000000", &key).is_err());
+
+        let code = &enrollment.recovery_codes[0];
+        assert!(table.use_recovery_code("user1", code).unwrap());
+        assert!(!table.use_recovery_code("user1", code).unwrap());
+        assert_eq!(
+            table
+                .get("user1")
+                .unwrap()
+                .unwrap()
+                .remaining_recovery_codes(),
+            9
+        );
+        assert_eq!(
+            table.get("user1").unwrap().unwrap().failed_login_attempts,
+            1
+        );
+    }
+
+    #[test]
+    fn totp_failures_lock_out() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.account_map();
+        let key = [1_u8; 32];
+        store
+            .config_map()
+            .set_lockout_policy(&LockoutPolicy {
+                threshold: 3,
+                ..LockoutPolicy::default()
+            })
+            .unwrap();
+
+        let mut account = Account::new(
+            "user1",
+            "password",
+            Role::SystemAdministrator.into(),
+            "User 1".to_string(),
+            "Department 1".to_string(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        account.enroll_totp(&key, "REview").unwrap();
+        table.put(&account).unwrap();
+
+        // A six-digit code never contains a letter, so none of these match.
+        for _ in 0..2 {
+            assert!(!table.verify_totp("user1", "abcdef", &key).unwrap());
+        }
+        assert!(!table.is_account_locked("user1").unwrap());
+        assert!(!table.verify_totp("user1", "abcdef", &key).unwrap());
+        assert!(table.is_account_locked("user1").unwrap());
     }
 
     #[test]
@@ -692,7 +1235,7 @@ mod tests {
         let mut account = Account::new(
             "user1",
             "password",
-            Role::SystemAdministrator,
+            Role::SystemAdministrator.into(),
             "User 1".to_string(),
             "Department 1".to_string(),
             None,
@@ -700,6 +1243,7 @@ mod tests {
             None,
             None,
             None,
+            &PasswordPolicy::default(),
         )
         .unwrap();
 
@@ -1,16 +1,44 @@
 //! The `configs` map.
 
-use anyhow::Result;
+use anyhow::{Context, Result, bail};
 use rocksdb::OptimisticTransactionDB;
 
-use crate::{Map, Table};
+use crate::{
+    CorrelationPolicy, CustomRole, DedupPolicy, IndexedTable, LockoutPolicy, Map, PasswordPolicy,
+    RoleId, Table,
+};
+
+/// The maximum number of seconds of a correlation window, which is a year.
+const MAX_CORRELATION_WINDOW: i64 = 365 * 24 * 60 * 60;
+
+/// The maximum number of seconds a dedup key is remembered, which is a year.
+const MAX_DEDUP_TTL: i64 = 365 * 24 * 60 * 60;
+
+/// The maximum number of passwords kept in the password history, each of which is checked
+/// against a new password.
+const MAX_PASSWORD_HISTORY: usize = 24;
+
+/// The maximum number of minutes of a lockout duration or an observation
+/// window, which is a year.
+const MAX_LOCKOUT_MINUTES: i64 = 365 * 24 * 60;
+
+/// The key of the policy that groups events into incidents.
+const CORRELATION_POLICY: &str = "correlation policy";
+/// The key of the policy that limits the dedup keys of events.
+const DEDUP_POLICY: &str = "dedup policy";
+/// The key of the password policy.
+const PASSWORD_POLICY: &str = "password policy";
+/// The key of the account lockout policy.
+const LOCKOUT_POLICY: &str = "lockout policy";
+/// The key of the roles required to sign in with a second factor.
+const MFA_REQUIRED_ROLES: &str = "mfa required roles";
 
 /// Functions for the `configs` map.
 impl<'d> Table<'d, String> {
     /// Opens the  `configs` map in the database.
     ///
     /// Returns `None` if the map does not exist.
-    pub(super) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
         Map::open(db, super::CONFIGS).map(Table::new)
     }
 
@@ -54,13 +82,224 @@ impl<'d> Table<'d, String> {
             .map(|p| String::from_utf8(p.as_ref().to_owned()).map_err(|e| anyhow!("{e}")))
             .transpose()
     }
+
+    /// Returns the correlation policy, or `None` if correlation is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails or the stored policy is
+    /// invalid.
+    pub fn correlation_policy(&self) -> Result<Option<CorrelationPolicy>> {
+        self.current(CORRELATION_POLICY)?
+            .map(|policy| Ok(serde_json::from_str(&policy)?))
+            .transpose()
+    }
+
+    /// Sets the correlation policy. Events stored afterwards are grouped
+    /// according to the new policy; existing incidents are kept as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the window is negative or longer than a year, or
+    /// if database operation fails.
+    pub fn set_correlation_policy(&self, policy: &CorrelationPolicy) -> Result<()> {
+        if policy.window < 0 {
+            bail!("correlation window must not be negative");
+        }
+        if policy.window > MAX_CORRELATION_WINDOW {
+            bail!("correlation window cannot be longer than a year");
+        }
+        self.update(CORRELATION_POLICY, &serde_json::to_string(policy)?)
+    }
+
+    /// Disables correlation. Existing incidents are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails.
+    pub fn clear_correlation_policy(&self) -> Result<()> {
+        self.map.delete(CORRELATION_POLICY.as_bytes())
+    }
+
+    /// Returns the dedup policy of `EventDb::put_idempotent`, or the default
+    /// one if it hasn't been set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails or the stored policy is
+    /// invalid.
+    pub fn dedup_policy(&self) -> Result<DedupPolicy> {
+        let Some(policy) = self.current(DEDUP_POLICY)? else {
+            return Ok(DedupPolicy::default());
+        };
+        Ok(serde_json::from_str(&policy)?)
+    }
+
+    /// Sets the dedup policy of `EventDb::put_idempotent`. Keys remembered
+    /// beyond the new limits are forgotten the next time an event is stored
+    /// with a dedup key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TTL or the capacity is not positive, if the
+    /// TTL is longer than a year, or if database operation fails.
+    pub fn set_dedup_policy(&self, policy: &DedupPolicy) -> Result<()> {
+        if policy.ttl <= 0 {
+            bail!("dedup TTL must be positive");
+        }
+        if policy.ttl > MAX_DEDUP_TTL {
+            bail!("dedup TTL cannot be longer than a year");
+        }
+        if policy.capacity == 0 {
+            bail!("dedup capacity must be positive");
+        }
+        self.update(DEDUP_POLICY, &serde_json::to_string(policy)?)
+    }
+
+    /// Returns the password policy, or the default one if it hasn't been
+    /// set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails or the stored policy is
+    /// invalid.
+    pub fn password_policy(&self) -> Result<PasswordPolicy> {
+        let Some(policy) = self.current(PASSWORD_POLICY)? else {
+            return Ok(PasswordPolicy::default());
+        };
+        Ok(serde_json::from_str(&policy)?)
+    }
+
+    /// Sets the password policy. Existing passwords are checked against it
+    /// when they are changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the maximum password age is not positive, the
+    /// warning window is not shorter than it, more than 24 passwords are to
+    /// be kept in the history, or if database operation fails.
+    pub fn set_password_policy(&self, policy: &PasswordPolicy) -> Result<()> {
+        if let Some(max_age_days) = policy.max_age_days {
+            if max_age_days == 0 {
+                bail!("maximum password age must be positive");
+            }
+            if policy.expiry_warning_days >= max_age_days {
+                bail!("password expiry warning must be shorter than the maximum age");
+            }
+        }
+        if policy.history_size > MAX_PASSWORD_HISTORY {
+            bail!("password history cannot be larger than {MAX_PASSWORD_HISTORY}");
+        }
+        self.update(PASSWORD_POLICY, &serde_json::to_string(policy)?)
+    }
+
+    /// Returns the account lockout policy, or the default one if it hasn't
+    /// been set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails or the stored policy is
+    /// invalid.
+    pub fn lockout_policy(&self) -> Result<LockoutPolicy> {
+        let Some(policy) = self.current(LOCKOUT_POLICY)? else {
+            return Ok(LockoutPolicy::default());
+        };
+        Ok(serde_json::from_str(&policy)?)
+    }
+
+    /// Sets the account lockout policy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the threshold, a duration, the observation window,
+    /// or the number of lockouts before a permanent one is not positive, if
+    /// a duration or the observation window is longer than a year, if there
+    /// is no duration, or if database operation fails.
+    pub fn set_lockout_policy(&self, policy: &LockoutPolicy) -> Result<()> {
+        if policy.threshold == 0 {
+            bail!("lockout threshold must be positive");
+        }
+        if policy.durations_minutes.is_empty() {
+            bail!("at least one lockout duration is required");
+        }
+        if policy.durations_minutes.iter().any(|minutes| *minutes <= 0) {
+            bail!("lockout durations must be positive");
+        }
+        if policy
+            .durations_minutes
+            .iter()
+            .any(|minutes| *minutes > MAX_LOCKOUT_MINUTES)
+        {
+            bail!("lockout durations cannot be longer than a year");
+        }
+        if let Some(minutes) = policy.observation_window_minutes {
+            if minutes <= 0 {
+                bail!("observation window must be positive");
+            }
+            if minutes > MAX_LOCKOUT_MINUTES {
+                bail!("observation window cannot be longer than a year");
+            }
+        }
+        if policy.permanent_after == Some(0) {
+            bail!("number of lockouts before a permanent one must be positive");
+        }
+        self.update(LOCKOUT_POLICY, &serde_json::to_string(policy)?)
+    }
+
+    /// Returns the roles whose accounts must sign in with a second factor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails or the stored roles are
+    /// invalid.
+    pub fn mfa_required_roles(&self) -> Result<Vec<RoleId>> {
+        let Some(roles) = self.current(MFA_REQUIRED_ROLES)? else {
+            return Ok(Vec::new());
+        };
+        Ok(serde_json::from_str(&roles)?)
+    }
+
+    /// Sets the roles whose accounts must sign in with a second factor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a custom role does not exist, or if database
+    /// operation fails.
+    pub fn set_mfa_required_roles(&self, roles: &[RoleId]) -> Result<()> {
+        let custom_roles =
+            IndexedTable::<CustomRole>::open(self.map.db).context("roles table must be present")?;
+        for role in roles {
+            if let RoleId::Custom(id) = role
+                && custom_roles.get_by_id(*id)?.is_none()
+            {
+                bail!("no such role");
+            }
+        }
+        self.update(MFA_REQUIRED_ROLES, &serde_json::to_string(roles)?)
+    }
+
+    /// Returns `true` if accounts of the given role must sign in with a
+    /// second factor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if database operation fails or the stored roles are
+    /// invalid.
+    pub fn is_mfa_required(&self, role: RoleId) -> Result<bool> {
+        Ok(self.mfa_required_roles()?.contains(&role))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use crate::Store;
+    use chrono::Utc;
+
+    use crate::{
+        Action, CustomRole, LockoutPolicy, PasswordPolicy, Permission, Resource, Role, RoleId,
+        Store,
+    };
 
     #[test]
     fn operations() {
@@ -74,4 +313,112 @@ mod tests {
         assert!(table.update("test", "20").is_ok());
         assert_eq!(table.current("test").unwrap(), Some("20".to_string()));
     }
+
+    #[test]
+    fn password_policy() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.config_map();
+
+        assert_eq!(table.password_policy().unwrap(), PasswordPolicy::default());
+        let policy = PasswordPolicy {
+            min_length: 12,
+            require_digit: true,
+            banned_words: vec!["password".to_string()],
+            history_size: 3,
+            ..PasswordPolicy::default()
+        };
+        table.set_password_policy(&policy).unwrap();
+        assert_eq!(table.password_policy().unwrap(), policy);
+
+        let invalid = PasswordPolicy {
+            max_age_days: Some(0),
+            ..policy.clone()
+        };
+        assert!(table.set_password_policy(&invalid).is_err());
+        let invalid = PasswordPolicy {
+            max_age_days: Some(30),
+            expiry_warning_days: 30,
+            ..policy.clone()
+        };
+        assert!(table.set_password_policy(&invalid).is_err());
+        let invalid = PasswordPolicy {
+            history_size: 25,
+            ..policy
+        };
+        assert!(table.set_password_policy(&invalid).is_err());
+        assert_eq!(table.password_policy().unwrap().history_size, 3);
+    }
+
+    #[test]
+    fn lockout_policy() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.config_map();
+
+        assert_eq!(table.lockout_policy().unwrap(), LockoutPolicy::default());
+        let policy = LockoutPolicy {
+            threshold: 3,
+            observation_window_minutes: Some(15),
+            durations_minutes: vec![15, 60, 240],
+            permanent_after: Some(4),
+        };
+        table.set_lockout_policy(&policy).unwrap();
+        assert_eq!(table.lockout_policy().unwrap(), policy);
+
+        let invalid = LockoutPolicy {
+            durations_minutes: vec![],
+            ..policy.clone()
+        };
+        assert!(table.set_lockout_policy(&invalid).is_err());
+        let invalid = LockoutPolicy {
+            durations_minutes: vec![15, i64::MAX],
+            ..policy.clone()
+        };
+        assert!(table.set_lockout_policy(&invalid).is_err());
+        let invalid = LockoutPolicy {
+            observation_window_minutes: Some(i64::MAX),
+            ..policy
+        };
+        assert!(table.set_lockout_policy(&invalid).is_err());
+    }
+
+    #[test]
+    fn mfa_required_roles() {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap());
+        let table = store.config_map();
+
+        assert!(table.mfa_required_roles().unwrap().is_empty());
+        assert!(table.set_mfa_required_roles(&[RoleId::Custom(1)]).is_err());
+        assert!(table.mfa_required_roles().unwrap().is_empty());
+
+        let id = store
+            .role_map()
+            .put(CustomRole {
+                id: u32::MAX,
+                name: "Event Reader".to_string(),
+                description: String::new(),
+                permissions: vec![Permission::new(Resource::Event, Action::Read)],
+                creation_time: Utc::now(),
+            })
+            .unwrap();
+        table
+            .set_mfa_required_roles(&[
+                Role::SystemAdministrator.into(),
+                Role::SecurityAdministrator.into(),
+                RoleId::Custom(id),
+            ])
+            .unwrap();
+        assert!(table.is_mfa_required(RoleId::Custom(id)).unwrap());
+        assert!(
+            table
+                .is_mfa_required(Role::SystemAdministrator.into())
+                .unwrap()
+        );
+        assert!(!table.is_mfa_required(Role::SecurityMonitor.into()).unwrap());
+    }
 }
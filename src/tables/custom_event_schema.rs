@@ -0,0 +1,313 @@
+//! The `custom event schemas` table.
+
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use rocksdb::{Direction, OptimisticTransactionDB};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Iterable, Map, Table, UniqueKey,
+    event::{CustomEventFields, CustomValueKind},
+    tables::Value,
+    types::FromKeyValue,
+};
+
+/// An attribute of custom events.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CustomAttrSpec {
+    pub name: String,
+    pub kind: CustomValueKind,
+    pub description: String,
+}
+
+/// The description of the attributes of custom events reported by a detector.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CustomEventSchema {
+    pub name: String,
+    pub version: u32,
+    pub description: String,
+    pub attributes: Vec<CustomAttrSpec>,
+}
+
+impl CustomEventSchema {
+    fn key_prefix(name: &str) -> Vec<u8> {
+        let mut key = name.as_bytes().to_vec();
+        key.push(0);
+        key
+    }
+
+    fn key(name: &str, version: u32) -> Vec<u8> {
+        let mut key = Self::key_prefix(name);
+        key.extend(version.to_be_bytes());
+        key
+    }
+
+    /// Returns the specification of the attribute with the given name.
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&CustomAttrSpec> {
+        self.attributes.iter().find(|spec| spec.name == name)
+    }
+
+    /// Checks that the fields of a custom event conform to the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fields refer to another schema, have a level greater than 5, have
+    /// an attribute not defined in the schema or defined more than once, or have a value of a
+    /// type different from the one defined in the schema.
+    pub fn validate(&self, fields: &CustomEventFields) -> Result<()> {
+        if fields.schema != self.name || fields.schema_version != self.version {
+            bail!(
+                "event is for schema {} version {}, not {} version {}",
+                fields.schema,
+                fields.schema_version,
+                self.name,
+                self.version
+            );
+        }
+        if fields.level.get() > 5 {
+            bail!("level must be between 1 and 5, not {}", fields.level);
+        }
+        let mut seen = HashSet::new();
+        for (name, value) in &fields.attributes {
+            let Some(spec) = self.attribute(name) else {
+                bail!("attribute {name} is not defined in schema {}", self.name);
+            };
+            if !seen.insert(name.as_str()) {
+                bail!("attribute {name} appears more than once");
+            }
+            if value.kind() != spec.kind {
+                bail!(
+                    "attribute {name} must be of type {:?}, not {:?}",
+                    spec.kind,
+                    value.kind()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromKeyValue for CustomEventSchema {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for CustomEventSchema {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn unique_key(&self) -> Vec<u8> {
+        Self::key(&self.name, self.version)
+    }
+}
+
+impl Value for CustomEventSchema {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// Functions for the `custom event schemas` table.
+impl<'d> Table<'d, CustomEventSchema> {
+    /// Opens the `custom event schemas` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::CUSTOM_EVENT_SCHEMAS).map(Table::new)
+    }
+
+    /// Registers a schema. A schema cannot be changed once registered; a new version should be
+    /// registered instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema has no name or a name with a `.` or NUL character, defines
+    /// an attribute more than once or with a name that is empty or has a character other than
+    /// ASCII letters, digits, and underscores, has the same name and version as a registered one,
+    /// or the database operation fails.
+    pub fn register(&self, schema: &CustomEventSchema) -> Result<()> {
+        if schema.name.is_empty() {
+            bail!("schema name must not be empty");
+        }
+        // Triage rules refer to attributes as `<schema>.<attribute>`, and keys end the name with
+        // a NUL character.
+        if schema.name.contains(['.', '\0']) {
+            bail!("invalid schema name {:?}", schema.name);
+        }
+        if let Some(spec) = schema.attributes.iter().find(|spec| {
+            spec.name.is_empty()
+                || !spec
+                    .name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'_')
+        }) {
+            bail!("invalid attribute name {:?}", spec.name);
+        }
+        let mut names = HashSet::new();
+        if let Some(spec) = schema
+            .attributes
+            .iter()
+            .find(|spec| !names.insert(spec.name.as_str()))
+        {
+            bail!("attribute {} is defined more than once", spec.name);
+        }
+        self.insert(schema)
+    }
+
+    /// Returns the schema with the given name and version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored schema is invalid.
+    pub fn get(&self, name: &str, version: u32) -> Result<Option<CustomEventSchema>> {
+        self.map
+            .get(&CustomEventSchema::key(name, version))?
+            .map(|value| super::deserialize(value.as_ref()))
+            .transpose()
+    }
+
+    /// Returns all versions of the schema with the given name, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored schema is invalid.
+    pub fn versions(&self, name: &str) -> Result<Vec<CustomEventSchema>> {
+        self.prefix_iter(
+            Direction::Forward,
+            None,
+            &CustomEventSchema::key_prefix(name),
+        )
+        .collect()
+    }
+
+    /// Removes the schema with the given name and version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn remove(&self, name: &str, version: u32) -> Result<()> {
+        self.map.delete(&CustomEventSchema::key(name, version))
+    }
+
+    /// Checks that the fields of a custom event conform to their registered schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the schema is not registered, the fields do not conform to it, or the
+    /// database operation fails.
+    pub fn validate(&self, fields: &CustomEventFields) -> Result<()> {
+        let Some(schema) = self.get(&fields.schema, fields.schema_version)? else {
+            bail!(
+                "schema {} version {} is not registered",
+                fields.schema,
+                fields.schema_version
+            );
+        };
+        schema.validate(fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::IpAddr, num::NonZeroU8, sync::Arc};
+
+    use super::{CustomAttrSpec, CustomEventSchema};
+    use crate::{
+        Store,
+        event::{CustomEventFields, CustomValue, CustomValueKind},
+    };
+
+    fn schema(version: u32) -> CustomEventSchema {
+        CustomEventSchema {
+            name: "partner detector".to_string(),
+            version,
+            description: String::new(),
+            attributes: vec![
+                CustomAttrSpec {
+                    name: "peer".to_string(),
+                    kind: CustomValueKind::Addr,
+                    description: String::new(),
+                },
+                CustomAttrSpec {
+                    name: "score".to_string(),
+                    kind: CustomValueKind::Float,
+                    description: String::new(),
+                },
+            ],
+        }
+    }
+
+    fn fields(attributes: Vec<(String, CustomValue)>) -> CustomEventFields {
+        CustomEventFields {
+            sensor: "sensor1".to_string(),
+            schema: "partner detector".to_string(),
+            schema_version: 1,
+            src_addrs: vec![],
+            src_port: 0,
+            dst_addrs: vec![],
+            dst_port: 0,
+            proto: 0,
+            level: NonZeroU8::new(3).unwrap(),
+            attributes,
+            confidence: 1.0,
+            category: None,
+        }
+    }
+
+    #[test]
+    fn register_and_validate() {
+        let store = setup_store();
+        let table = store.custom_event_schema_map();
+
+        table.register(&schema(1)).unwrap();
+        table.register(&schema(2)).unwrap();
+        assert!(table.register(&schema(1)).is_err());
+        let mut duplicate = schema(3);
+        duplicate.attributes.push(duplicate.attributes[0].clone());
+        assert!(table.register(&duplicate).is_err());
+        for name in ["", "peer addr", "peer=\"x\"", "partner.score"] {
+            let mut invalid = schema(3);
+            invalid.attributes[0].name = name.to_string();
+            assert!(table.register(&invalid).is_err());
+        }
+        for name in ["", "partner.detector", "partner\0detector"] {
+            let mut invalid = schema(3);
+            invalid.name = name.to_string();
+            assert!(table.register(&invalid).is_err());
+        }
+        let mut valid = schema(3);
+        valid.attributes[0].name = "peer_addr2".to_string();
+        table.register(&valid).unwrap();
+
+        assert_eq!(table.get("partner detector", 1).unwrap(), Some(schema(1)));
+        assert_eq!(table.versions("partner detector").unwrap().len(), 3);
+        assert!(table.versions("partner").unwrap().is_empty());
+
+        let peer: IpAddr = "10.0.0.1".parse().unwrap();
+        let valid = fields(vec![
+            ("peer".to_string(), CustomValue::Addr(peer)),
+            ("score".to_string(), CustomValue::Float(0.5)),
+        ]);
+        assert!(table.validate(&valid).is_ok());
+        let wrong_type = fields(vec![("score".to_string(), CustomValue::UInteger(1))]);
+        assert!(table.validate(&wrong_type).is_err());
+        let unknown = fields(vec![("other".to_string(), CustomValue::Bool(true))]);
+        assert!(table.validate(&unknown).is_err());
+        let mut too_high = valid.clone();
+        too_high.level = NonZeroU8::new(6).unwrap();
+        assert!(table.validate(&too_high).is_err());
+
+        table.remove("partner detector", 1).unwrap();
+        assert!(table.validate(&valid).is_err());
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
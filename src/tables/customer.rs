@@ -139,7 +139,7 @@ impl<'d> IndexedTable<'d, Customer> {
     /// Opens the `customer` table in the database.
     ///
     /// Returns `None` if the table does not exist.
-    pub(super) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
         IndexedMap::new(db, super::CUSTOMERS)
             .map(IndexedTable::new)
             .ok()
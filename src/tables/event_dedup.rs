@@ -0,0 +1,389 @@
+//! The `event dedup keys` table.
+//!
+//! The entries are stored in the `event dedup keys` column family along with the number of
+//! entries, which is split into shards so that events stored at the same time rarely update the
+//! same counter. The `event dedup expiry` column family orders the entries by the time they were
+//! recorded.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use rocksdb::{IteratorMode, OptimisticTransactionDB, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::{EXCLUSIVE, Map, Table, UniqueKey, tables::Value, types::FromKeyValue};
+
+/// The prefix of the keys of dedup entries.
+const ENTRY_PREFIX: u8 = 1;
+/// The prefix of the keys of the shards of the number of dedup entries.
+const COUNT_PREFIX: u8 = 3;
+/// The number of shards the number of dedup entries is split into.
+const COUNT_SHARDS: u8 = 16;
+
+/// Determines how long and how many dedup keys are remembered.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DedupPolicy {
+    /// The number of seconds a dedup key is remembered after its event is stored.
+    pub ttl: i64,
+    /// The maximum number of dedup keys remembered. The oldest ones are forgotten first.
+    pub capacity: u64,
+}
+
+impl Default for DedupPolicy {
+    fn default() -> Self {
+        Self {
+            ttl: 3600,
+            capacity: 100_000,
+        }
+    }
+}
+
+/// A dedup key supplied by the producer of an event, and the event stored with it.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct DedupEntry {
+    pub dedup_key: Vec<u8>,
+    /// The key of the event in the event database.
+    pub event: i128,
+    /// When the event was stored.
+    pub time: DateTime<Utc>,
+}
+
+impl DedupEntry {
+    fn entry_key(dedup_key: &[u8]) -> Vec<u8> {
+        let mut key = Vec::with_capacity(dedup_key.len() + 1);
+        key.push(ENTRY_PREFIX);
+        key.extend(dedup_key);
+        key
+    }
+
+    fn expiry_key(&self) -> Vec<u8> {
+        let mut key = Vec::with_capacity(self.dedup_key.len() + 8);
+        key.extend(
+            self.time
+                .timestamp_nanos_opt()
+                .unwrap_or(i64::MAX)
+                .to_be_bytes(),
+        );
+        key.extend(&self.dedup_key);
+        key
+    }
+
+    /// Returns the key of the shard of the number of entries that counts this entry.
+    fn count_key(dedup_key: &[u8]) -> [u8; 2] {
+        let hash = dedup_key
+            .iter()
+            .fold(0_u8, |hash, byte| hash.wrapping_mul(31).wrapping_add(*byte));
+        [COUNT_PREFIX, hash % COUNT_SHARDS]
+    }
+
+    /// Returns `true` if the TTL of the policy has passed since the event was stored. An expiry
+    /// beyond the range of `DateTime` is never reached.
+    fn is_expired(&self, policy: &DedupPolicy, now: DateTime<Utc>) -> bool {
+        TimeDelta::try_seconds(policy.ttl)
+            .and_then(|ttl| self.time.checked_add_signed(ttl))
+            .is_some_and(|expiry| expiry <= now)
+    }
+}
+
+impl FromKeyValue for DedupEntry {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for DedupEntry {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn unique_key(&self) -> Vec<u8> {
+        Self::entry_key(&self.dedup_key)
+    }
+}
+
+impl Value for DedupEntry {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// Functions for the `event dedup keys` table.
+impl<'d> Table<'d, DedupEntry> {
+    /// Opens the `event dedup keys` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::EVENT_DEDUP_EXPIRY)?;
+        Map::open(db, super::EVENT_DEDUP_KEYS).map(Table::new)
+    }
+
+    /// Returns the entry for the given dedup key, even if it has expired but not been forgotten
+    /// yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored entry is invalid.
+    pub fn get(&self, dedup_key: &[u8]) -> Result<Option<DedupEntry>> {
+        self.map
+            .get(&DedupEntry::entry_key(dedup_key))?
+            .map(|v| super::deserialize(v.as_ref()))
+            .transpose()
+    }
+
+    /// Returns the number of dedup keys remembered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn count(&self) -> Result<u64> {
+        let mut count = 0;
+        for shard in 0..COUNT_SHARDS {
+            if let Some(value) = self.map.get(&[COUNT_PREFIX, shard])? {
+                count += decode_count(value.as_ref())?;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Returns the key of the event stored with the given dedup key, unless it has expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored entry is invalid.
+    pub(crate) fn lookup_with_transaction(
+        &self,
+        dedup_key: &[u8],
+        policy: &DedupPolicy,
+        now: DateTime<Utc>,
+        txn: &Transaction<OptimisticTransactionDB>,
+    ) -> Result<Option<i128>> {
+        let Some(value) = txn
+            .get_for_update_cf(self.map.cf, DedupEntry::entry_key(dedup_key), EXCLUSIVE)
+            .context("cannot read dedup entry")?
+        else {
+            return Ok(None);
+        };
+        let entry: DedupEntry = super::deserialize(&value)?;
+        Ok((!entry.is_expired(policy, now)).then_some(entry.event))
+    }
+
+    /// Records the entry, replacing an expired one with the same dedup key, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored entry is invalid.
+    pub(crate) fn record_with_transaction(
+        &self,
+        entry: &DedupEntry,
+        txn: &Transaction<OptimisticTransactionDB>,
+    ) -> Result<()> {
+        let expiry = self.expiry()?;
+        let entry_key = entry.unique_key();
+        if let Some(old) = txn
+            .get_for_update_cf(self.map.cf, &entry_key, EXCLUSIVE)
+            .context("cannot read dedup entry")?
+        {
+            let old: DedupEntry = super::deserialize(&old)?;
+            txn.delete_cf(expiry.cf, old.expiry_key())
+                .context("cannot delete dedup entry")?;
+        } else {
+            let count_key = DedupEntry::count_key(&entry.dedup_key);
+            let count = self.count_with_transaction(&count_key, txn)?;
+            txn.put_cf(self.map.cf, count_key, (count + 1).to_be_bytes())
+                .context("cannot write dedup entry count")?;
+        }
+        txn.put_cf(self.map.cf, &entry_key, entry.value())
+            .context("cannot write dedup entry")?;
+        txn.put_cf(expiry.cf, entry.expiry_key(), [])
+            .context("cannot write dedup entry")
+    }
+
+    /// Forgets the expired dedup keys, and the oldest ones beyond the capacity. Returns the number
+    /// of keys forgotten.
+    ///
+    /// The keys are kept if another thread updates them at the same time; they are forgotten next
+    /// time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored entry is invalid.
+    pub(crate) fn prune(&self, policy: &DedupPolicy, now: DateTime<Utc>) -> Result<u64> {
+        let expiry = self.expiry()?;
+        let txn = self.map.db.transaction();
+        let mut count = 0;
+        for shard in 0..COUNT_SHARDS {
+            if let Some(value) = txn
+                .get_cf(self.map.cf, [COUNT_PREFIX, shard])
+                .context("cannot read dedup entry count")?
+            {
+                count += decode_count(&value)?;
+            }
+        }
+        let start = count;
+        let mut shards = [None; COUNT_SHARDS as usize];
+        for item in txn.iterator_cf(expiry.cf, IteratorMode::Start) {
+            let (key, _) = item.context("cannot read dedup entry")?;
+            let Some((time, dedup_key)) = key.split_first_chunk::<8>() else {
+                bail!("invalid dedup entry expiry");
+            };
+            let entry = DedupEntry {
+                dedup_key: dedup_key.to_vec(),
+                event: 0,
+                time: DateTime::from_timestamp_nanos(i64::from_be_bytes(*time)),
+            };
+            if !entry.is_expired(policy, now) && count <= policy.capacity {
+                break;
+            }
+            txn.get_for_update_cf(self.map.cf, entry.unique_key(), EXCLUSIVE)
+                .context("cannot read dedup entry")?;
+            txn.delete_cf(self.map.cf, entry.unique_key())
+                .context("cannot delete dedup entry")?;
+            txn.delete_cf(expiry.cf, &key)
+                .context("cannot delete dedup entry")?;
+            let count_key = DedupEntry::count_key(&entry.dedup_key);
+            let shard = usize::from(count_key[1]);
+            let shard_count = match shards[shard] {
+                Some(shard_count) => shard_count,
+                None => self.count_with_transaction(&count_key, &txn)?,
+            };
+            shards[shard] = Some(shard_count.saturating_sub(1));
+            count = count.saturating_sub(1);
+        }
+        if count == start {
+            return Ok(0);
+        }
+        for (shard, shard_count) in (0..COUNT_SHARDS).zip(shards) {
+            if let Some(shard_count) = shard_count {
+                txn.put_cf(
+                    self.map.cf,
+                    [COUNT_PREFIX, shard],
+                    shard_count.to_be_bytes(),
+                )
+                .context("cannot write dedup entry count")?;
+            }
+        }
+        match txn.commit() {
+            Ok(()) => Ok(start - count),
+            Err(e) => {
+                if e.as_ref().starts_with("Resource busy:") {
+                    Ok(0)
+                } else {
+                    Err(e).context("failed to prune dedup entries")
+                }
+            }
+        }
+    }
+
+    fn expiry(&self) -> Result<Map<'d>> {
+        Map::open(self.map.db, super::EVENT_DEDUP_EXPIRY).context("no event dedup expiry table")
+    }
+
+    fn count_with_transaction(
+        &self,
+        count_key: &[u8],
+        txn: &Transaction<OptimisticTransactionDB>,
+    ) -> Result<u64> {
+        txn.get_for_update_cf(self.map.cf, count_key, EXCLUSIVE)
+            .context("cannot read dedup entry count")?
+            .map_or(Ok(0), |v| decode_count(&v))
+    }
+}
+
+fn decode_count(value: &[u8]) -> Result<u64> {
+    Ok(u64::from_be_bytes(
+        value.try_into().context("invalid dedup entry count")?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{TimeDelta, Utc};
+
+    use super::{DedupEntry, DedupPolicy};
+    use crate::Store;
+
+    fn entry(dedup_key: &str, event: i128, seconds_ago: i64) -> DedupEntry {
+        DedupEntry {
+            dedup_key: dedup_key.as_bytes().to_vec(),
+            event,
+            time: Utc::now() - TimeDelta::seconds(seconds_ago),
+        }
+    }
+
+    #[test]
+    fn prune_expired_and_beyond_capacity() {
+        let store = setup_store();
+        let table = store.event_dedup_map();
+        let configs = store.config_map();
+        assert_eq!(configs.dedup_policy().unwrap(), DedupPolicy::default());
+        assert!(
+            configs
+                .set_dedup_policy(&DedupPolicy {
+                    ttl: 0,
+                    capacity: 1
+                })
+                .is_err()
+        );
+        assert!(
+            configs
+                .set_dedup_policy(&DedupPolicy {
+                    ttl: i64::MAX,
+                    capacity: 1
+                })
+                .is_err()
+        );
+        let unbounded = DedupPolicy {
+            ttl: i64::MAX,
+            capacity: 1,
+        };
+        assert!(!entry("old", 1, 120).is_expired(&unbounded, Utc::now()));
+        let policy = DedupPolicy {
+            ttl: 60,
+            capacity: 2,
+        };
+        configs.set_dedup_policy(&policy).unwrap();
+        assert_eq!(configs.dedup_policy().unwrap(), policy);
+
+        for entry in [
+            entry("expired", 1, 120),
+            entry("old", 2, 30),
+            entry("new", 3, 10),
+            entry("newest", 4, 0),
+        ] {
+            let txn = table.map.db.transaction();
+            table.record_with_transaction(&entry, &txn).unwrap();
+            txn.commit().unwrap();
+        }
+        assert_eq!(table.count().unwrap(), 4);
+
+        let now = Utc::now();
+        let txn = table.map.db.transaction();
+        assert_eq!(
+            table
+                .lookup_with_transaction(b"expired", &policy, now, &txn)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            table
+                .lookup_with_transaction(b"old", &policy, now, &txn)
+                .unwrap(),
+            Some(2)
+        );
+        drop(txn);
+
+        assert_eq!(table.prune(&policy, now).unwrap(), 2);
+        assert_eq!(table.count().unwrap(), 2);
+        assert!(table.get(b"expired").unwrap().is_none());
+        assert!(table.get(b"old").unwrap().is_none());
+        assert_eq!(table.get(b"newest").unwrap().map(|e| e.event), Some(4));
+        assert_eq!(table.prune(&policy, now).unwrap(), 0);
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
@@ -0,0 +1,243 @@
+//! The `event enrichments` table.
+
+use std::net::IpAddr;
+
+use anyhow::{Context, Result};
+use rocksdb::{Direction, OptimisticTransactionDB};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Customer, IndexedTable, Iterable, Map, Network, Table, UniqueKey,
+    tables::Value,
+    types::FromKeyValue,
+    util::{find_ip_asn, find_ip_country},
+};
+
+/// Information about an event computed when the event is stored, so that queries do not need to
+/// look it up again.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EventEnrichment {
+    /// The key of the event in the event database.
+    pub key: i128,
+    pub src: Vec<AddrEnrichment>,
+    pub dst: Vec<AddrEnrichment>,
+}
+
+impl EventEnrichment {
+    /// Returns the information about the given address, looking at the source addresses first.
+    #[must_use]
+    pub fn get(&self, addr: IpAddr) -> Option<&AddrEnrichment> {
+        self.src
+            .iter()
+            .chain(self.dst.iter())
+            .find(|info| info.addr == addr)
+    }
+}
+
+impl FromKeyValue for EventEnrichment {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for EventEnrichment {
+    type AsBytes<'a> = [u8; 16];
+
+    fn unique_key(&self) -> [u8; 16] {
+        self.key.to_be_bytes()
+    }
+}
+
+impl Value for EventEnrichment {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// Information about a source or destination address of an event.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AddrEnrichment {
+    pub addr: IpAddr,
+    /// The two-letter country code, or `None` if no ip2location database was available.
+    pub country: Option<String>,
+    pub asn: Option<u32>,
+    /// The ID of the customer whose networks contain the address.
+    pub customer_id: Option<u32>,
+    /// The IDs of the networks containing the address, in ascending order.
+    pub network_ids: Vec<u32>,
+}
+
+/// Computes the enrichment of events with the customers and networks loaded once.
+pub(crate) struct Enricher {
+    customers: Vec<Customer>,
+    networks: Vec<Network>,
+}
+
+impl Enricher {
+    /// Returns the enrichment of the event with the given key and addresses. If no ip2location
+    /// database is available, the countries and ASNs are taken from `previous`.
+    pub(crate) fn enrichment(
+        &self,
+        locator: Option<&ip2location::DB>,
+        key: i128,
+        src_addrs: &[IpAddr],
+        dst_addrs: &[IpAddr],
+        previous: Option<&EventEnrichment>,
+    ) -> EventEnrichment {
+        let previous_of = |addr: IpAddr| previous.and_then(|p| p.get(addr));
+        EventEnrichment {
+            key,
+            src: src_addrs
+                .iter()
+                .map(|&addr| self.addr(locator, addr, previous_of(addr)))
+                .collect(),
+            dst: dst_addrs
+                .iter()
+                .map(|&addr| self.addr(locator, addr, previous_of(addr)))
+                .collect(),
+        }
+    }
+
+    /// Returns the information about `addr`. If no ip2location database is available, the
+    /// country and ASN are taken from `previous`.
+    fn addr(
+        &self,
+        locator: Option<&ip2location::DB>,
+        addr: IpAddr,
+        previous: Option<&AddrEnrichment>,
+    ) -> AddrEnrichment {
+        let (country, asn) = if let Some(locator) = locator {
+            (
+                Some(find_ip_country(locator, addr)),
+                find_ip_asn(locator, addr),
+            )
+        } else {
+            previous.map_or((None, None), |p| (p.country.clone(), p.asn))
+        };
+        AddrEnrichment {
+            addr,
+            country,
+            asn,
+            customer_id: self
+                .customers
+                .iter()
+                .find(|customer| customer.contains(addr))
+                .map(|customer| customer.id),
+            network_ids: self
+                .networks
+                .iter()
+                .filter(|network| network.networks.contains(addr))
+                .map(|network| network.id)
+                .collect(),
+        }
+    }
+}
+
+/// Functions for the `event enrichments` table.
+impl<'d> Table<'d, EventEnrichment> {
+    /// Opens the `event enrichments` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::EVENT_ENRICHMENTS).map(Table::new)
+    }
+
+    /// Returns the enrichment of the event with the given key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored value is invalid.
+    pub fn get(&self, key: i128) -> Result<Option<EventEnrichment>> {
+        let key = key.to_be_bytes();
+        self.map
+            .get(&key)?
+            .map(|value| EventEnrichment::from_key_value(&key, value.as_ref()))
+            .transpose()
+    }
+
+    /// Returns an `Enricher` with the current customers and networks.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the customers or networks cannot be read.
+    pub(crate) fn enricher(&self) -> Result<Enricher> {
+        let customers = IndexedTable::<Customer>::open(self.map.db)
+            .context("customers table must be present")?
+            .iter(Direction::Forward, None)
+            .collect::<Result<Vec<_>>>()?;
+        let networks = IndexedTable::<Network>::open(self.map.db)
+            .context("networks table must be present")?
+            .iter(Direction::Forward, None)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Enricher {
+            customers,
+            networks,
+        })
+    }
+
+    /// Computes and stores the enrichment of an event. If no ip2location database is available,
+    /// the countries and ASNs of the stored enrichment, if any, are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub(crate) fn enrich(
+        &self,
+        enricher: &Enricher,
+        locator: Option<&ip2location::DB>,
+        key: i128,
+        src_addrs: &[IpAddr],
+        dst_addrs: &[IpAddr],
+    ) -> Result<EventEnrichment> {
+        let previous = self.get(key)?;
+        let enrichment = enricher.enrichment(locator, key, src_addrs, dst_addrs, previous.as_ref());
+        self.put(&enrichment)?;
+        Ok(enrichment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::IpAddr, sync::Arc};
+
+    use crate::{Customer, CustomerNetwork, HostNetworkGroup, Store, event::NetworkType};
+
+    #[test]
+    fn enrich_with_customer() {
+        let store = setup_store();
+        let addr: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "192.168.0.1".parse().unwrap();
+        let customer_id = store
+            .customer_map()
+            .put(Customer {
+                id: u32::MAX,
+                name: "customer".to_string(),
+                description: String::new(),
+                networks: vec![CustomerNetwork {
+                    name: "network".to_string(),
+                    description: String::new(),
+                    network_type: NetworkType::Intranet,
+                    network_group: HostNetworkGroup::new(vec![addr], vec![], vec![]),
+                }],
+                creation_time: chrono::Utc::now(),
+            })
+            .unwrap();
+
+        let table = store.event_enrichment_map();
+        let enricher = table.enricher().unwrap();
+        let enrichment = table.enrich(&enricher, None, 1, &[addr], &[other]).unwrap();
+        assert_eq!(enrichment.get(addr).unwrap().customer_id, Some(customer_id));
+        assert_eq!(enrichment.get(other).unwrap().customer_id, None);
+        assert_eq!(enrichment.get(addr).unwrap().country, None);
+        assert_eq!(table.get(1).unwrap(), Some(enrichment));
+        assert_eq!(table.get(2).unwrap(), None);
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
@@ -0,0 +1,441 @@
+//! The `incidents` table.
+
+use std::{net::IpAddr, num::NonZeroU8};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeDelta, Utc};
+use rocksdb::{Direction, IteratorMode, OptimisticTransactionDB, ReadOptions, Transaction};
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use super::TableIter;
+use crate::{
+    EXCLUSIVE, EventKind, Iterable, Map, Table, UniqueKey, tables::Value, types::FromKeyValue,
+};
+
+/// The highest severity an incident can reach.
+const MAX_SEVERITY: u8 = 5;
+
+/// The maximum number of event keys recorded in an incident.
+const MAX_EVENTS: usize = 1000;
+
+/// An attribute of an event used to group it with other events.
+#[derive(Clone, Copy, Debug, Deserialize, Display, EnumString, Eq, PartialEq, Serialize)]
+#[strum(serialize_all = "snake_case")]
+pub enum CorrelationKey {
+    SrcAddr,
+    DstAddr,
+    Sensor,
+    Customer,
+}
+
+/// Determines which events belong to the same incident.
+///
+/// Two events belong to the same incident if they have the same values for all `keys` and no
+/// more than `window` seconds pass between them and the rest of the incident.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CorrelationPolicy {
+    pub keys: Vec<CorrelationKey>,
+    pub window: i64,
+}
+
+/// A group of correlated events.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Incident {
+    /// The values of the correlation keys shared by all the member events, e.g.
+    /// `src_addr=10.0.0.1,sensor=sensor1`.
+    pub group: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: DateTime<Utc>,
+    /// The keys of the member events in the event database. Only the first 1,000 are recorded;
+    /// later members still extend the time range and update the kinds, level, and triage score.
+    pub events: Vec<i128>,
+    pub kinds: Vec<EventKind>,
+    pub max_level: NonZeroU8,
+    pub max_triage_score: f64,
+}
+
+impl Incident {
+    /// Returns the aggregate severity of the incident.
+    ///
+    /// The severity starts from the highest level among the member events and is raised by one
+    /// for each additional kind of event, up to 5. The highest triage score among the member
+    /// events is added so that incidents matching triage policies rank above the others.
+    #[must_use]
+    pub fn severity(&self) -> f64 {
+        let extra_kinds = u8::try_from(self.kinds.len().saturating_sub(1)).unwrap_or(u8::MAX);
+        let level = self
+            .max_level
+            .get()
+            .saturating_add(extra_kinds)
+            .min(MAX_SEVERITY);
+        f64::from(level) + self.max_triage_score
+    }
+
+    fn key_prefix(group: &str) -> Vec<u8> {
+        let mut prefix = group.as_bytes().to_vec();
+        prefix.push(0);
+        prefix
+    }
+
+    /// Returns `true` if `time` is within `window` of the time range of the incident. A bound
+    /// beyond the range of `DateTime` does not limit the time.
+    fn contains_time(&self, time: DateTime<Utc>, window: TimeDelta) -> bool {
+        self.start_time
+            .checked_sub_signed(window)
+            .is_none_or(|start| start <= time)
+            && self
+                .end_time
+                .checked_add_signed(window)
+                .is_none_or(|end| time <= end)
+    }
+
+    fn add(&mut self, member: &CorrelationInput) {
+        if self.events.len() < MAX_EVENTS && !self.events.contains(&member.key) {
+            self.events.push(member.key);
+        }
+        if !self.kinds.contains(&member.kind) {
+            self.kinds.push(member.kind);
+        }
+        self.start_time = self.start_time.min(member.time);
+        self.end_time = self.end_time.max(member.time);
+        self.max_level = self.max_level.max(member.level);
+        self.max_triage_score = self.max_triage_score.max(member.triage_score);
+    }
+}
+
+impl FromKeyValue for Incident {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for Incident {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn unique_key(&self) -> Vec<u8> {
+        let mut key = Self::key_prefix(&self.group);
+        key.extend(
+            self.start_time
+                .timestamp_nanos_opt()
+                .unwrap_or(i64::MAX)
+                .to_be_bytes(),
+        );
+        key
+    }
+}
+
+impl Value for Incident {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// An event as seen by the correlation engine.
+pub(crate) struct CorrelationInput<'a> {
+    pub(crate) key: i128,
+    pub(crate) time: DateTime<Utc>,
+    pub(crate) kind: EventKind,
+    pub(crate) src_addr: Option<IpAddr>,
+    pub(crate) dst_addr: Option<IpAddr>,
+    pub(crate) sensor: &'a str,
+    /// The ID of the customer owning the source address, or the destination address if no
+    /// customer owns the source.
+    pub(crate) customer: Option<u32>,
+    pub(crate) level: NonZeroU8,
+    pub(crate) triage_score: f64,
+}
+
+/// Functions for the `incidents` table.
+impl<'d> Table<'d, Incident> {
+    /// Opens the `incidents` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::INCIDENTS).map(Table::new)
+    }
+
+    /// Returns the incidents of the given group, from the oldest to the newest.
+    #[must_use]
+    pub fn incidents_of(&self, group: &str) -> TableIter<'_, Incident> {
+        self.prefix_iter(Direction::Forward, None, &Incident::key_prefix(group))
+    }
+
+    /// Adds an event to the incident it belongs to under `policy`, creating a new incident if
+    /// none of the existing ones is close enough in time. Returns the updated incident, or `None`
+    /// if the event lacks a value for one of the correlation keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub(crate) fn correlate(
+        &self,
+        policy: &CorrelationPolicy,
+        member: &CorrelationInput,
+    ) -> Result<Option<Incident>> {
+        let Some(group) = group_of(policy, member) else {
+            return Ok(None);
+        };
+        let window = TimeDelta::try_seconds(policy.window).unwrap_or(TimeDelta::MAX);
+
+        loop {
+            let txn = self.map.db.transaction();
+            let latest = self
+                .latest_with_transaction(&group, &txn)?
+                .filter(|incident| incident.contains_time(member.time, window));
+
+            let incident = if let Some(old) = latest {
+                let mut new = old.clone();
+                new.add(member);
+                self.update_with_transaction(&old, &new, &txn)?;
+                new
+            } else {
+                let new = Incident {
+                    group: group.clone(),
+                    start_time: member.time,
+                    end_time: member.time,
+                    events: vec![member.key],
+                    kinds: vec![member.kind],
+                    max_level: member.level,
+                    max_triage_score: member.triage_score,
+                };
+                self.insert_with_transaction(&new, &txn)?;
+                new
+            };
+
+            match txn.commit() {
+                Ok(()) => return Ok(Some(incident)),
+                Err(e) => {
+                    if !e.as_ref().starts_with("Resource busy:") {
+                        return Err(e).context("failed to correlate event");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the latest incident of the group, read for update in the transaction so that a
+    /// concurrent change to it makes the transaction fail.
+    fn latest_with_transaction(
+        &self,
+        group: &str,
+        txn: &Transaction<OptimisticTransactionDB>,
+    ) -> Result<Option<Incident>> {
+        let prefix = Incident::key_prefix(group);
+        let mut readopts = ReadOptions::default();
+        readopts.set_iterate_range(rocksdb::PrefixRange(prefix.as_slice()));
+        let Some(item) = txn
+            .iterator_cf_opt(self.map.cf, readopts, IteratorMode::End)
+            .next()
+        else {
+            return Ok(None);
+        };
+        let (key, _) = item.context("cannot read incident")?;
+        txn.get_for_update_cf(self.map.cf, &key, EXCLUSIVE)
+            .context("cannot read incident")?
+            .map(|value| super::deserialize(&value).context("invalid incident in database"))
+            .transpose()
+    }
+}
+
+/// Returns the values of the correlation keys for the event, or `None` if any of them is missing.
+fn group_of(policy: &CorrelationPolicy, member: &CorrelationInput) -> Option<String> {
+    if policy.keys.is_empty() {
+        return None;
+    }
+    let mut values = Vec::with_capacity(policy.keys.len());
+    for key in &policy.keys {
+        let value = match key {
+            CorrelationKey::SrcAddr => member.src_addr.map(|addr| addr.to_string()),
+            CorrelationKey::DstAddr => member.dst_addr.map(|addr| addr.to_string()),
+            CorrelationKey::Sensor => {
+                (!member.sensor.is_empty()).then(|| member.sensor.to_string())
+            }
+            CorrelationKey::Customer => member.customer.map(|id| id.to_string()),
+        };
+        values.push(format!("{key}={}", value?));
+    }
+    Some(values.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::IpAddr, num::NonZeroU8, sync::Arc};
+
+    use chrono::{DateTime, TimeDelta, Utc};
+
+    use super::{CorrelationInput, MAX_EVENTS};
+    use crate::{CorrelationKey, CorrelationPolicy, EventKind, Iterable, Store};
+
+    fn input(
+        key: i128,
+        time: DateTime<Utc>,
+        kind: EventKind,
+        level: u8,
+    ) -> CorrelationInput<'static> {
+        CorrelationInput {
+            key,
+            time,
+            kind,
+            src_addr: Some("10.0.0.1".parse::<IpAddr>().unwrap()),
+            dst_addr: Some("10.0.0.2".parse::<IpAddr>().unwrap()),
+            sensor: "sensor1",
+            customer: None,
+            level: NonZeroU8::new(level).unwrap(),
+            triage_score: 0.0,
+        }
+    }
+
+    #[test]
+    fn policy() {
+        let store = setup_store();
+        let configs = store.config_map();
+        assert_eq!(configs.correlation_policy().unwrap(), None);
+
+        let policy = CorrelationPolicy {
+            keys: vec![CorrelationKey::SrcAddr],
+            window: 300,
+        };
+        configs.set_correlation_policy(&policy).unwrap();
+        assert_eq!(configs.correlation_policy().unwrap(), Some(policy));
+        assert!(
+            configs
+                .set_correlation_policy(&CorrelationPolicy {
+                    keys: vec![CorrelationKey::SrcAddr],
+                    window: -1,
+                })
+                .is_err()
+        );
+        assert!(
+            configs
+                .set_correlation_policy(&CorrelationPolicy {
+                    keys: vec![CorrelationKey::SrcAddr],
+                    window: i64::MAX,
+                })
+                .is_err()
+        );
+
+        configs.clear_correlation_policy().unwrap();
+        assert_eq!(configs.correlation_policy().unwrap(), None);
+        assert_eq!(
+            store
+                .incident_map()
+                .iter(rocksdb::Direction::Forward, None)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn group_within_window() {
+        let store = setup_store();
+        let table = store.incident_map();
+        let policy = CorrelationPolicy {
+            keys: vec![CorrelationKey::SrcAddr, CorrelationKey::Sensor],
+            window: 300,
+        };
+
+        let now = Utc::now();
+        table
+            .correlate(&policy, &input(1, now, EventKind::PortScan, 2))
+            .unwrap();
+        let incident = table
+            .correlate(
+                &policy,
+                &input(2, now + TimeDelta::minutes(4), EventKind::FtpBruteForce, 3),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(incident.group, "src_addr=10.0.0.1,sensor=sensor1");
+        assert_eq!(incident.events, vec![1, 2]);
+        assert_eq!(incident.max_level.get(), 3);
+        assert!((incident.severity() - 4.0).abs() < f64::EPSILON);
+
+        // An event outside the window starts a new incident.
+        table
+            .correlate(
+                &policy,
+                &input(3, now + TimeDelta::minutes(20), EventKind::BlocklistConn, 4),
+            )
+            .unwrap();
+        let incidents = table
+            .incidents_of("src_addr=10.0.0.1,sensor=sensor1")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(incidents.len(), 2);
+        assert_eq!(incidents[0].events, vec![1, 2]);
+        assert_eq!(incidents[1].events, vec![3]);
+    }
+
+    #[test]
+    fn window_overflow() {
+        let store = setup_store();
+        let table = store.incident_map();
+        let policy = CorrelationPolicy {
+            keys: vec![CorrelationKey::SrcAddr],
+            window: i64::MAX,
+        };
+
+        let now = Utc::now();
+        table
+            .correlate(&policy, &input(1, now, EventKind::PortScan, 2))
+            .unwrap();
+        let incident = table
+            .correlate(
+                &policy,
+                &input(2, DateTime::<Utc>::MIN_UTC, EventKind::PortScan, 2),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(incident.events, vec![1, 2]);
+    }
+
+    #[test]
+    fn customer_key() {
+        let store = setup_store();
+        let table = store.incident_map();
+        let policy = CorrelationPolicy {
+            keys: vec![CorrelationKey::Customer],
+            window: 300,
+        };
+
+        let mut member = input(1, Utc::now(), EventKind::PortScan, 2);
+        assert!(table.correlate(&policy, &member).unwrap().is_none());
+        member.customer = Some(7);
+        let incident = table.correlate(&policy, &member).unwrap().unwrap();
+        assert_eq!(incident.group, "customer=7");
+    }
+
+    #[test]
+    fn bounded_events() {
+        let store = setup_store();
+        let table = store.incident_map();
+        let policy = CorrelationPolicy {
+            keys: vec![CorrelationKey::SrcAddr],
+            window: 300,
+        };
+
+        let now = Utc::now();
+        let mut incident = None;
+        for key in 0..=i128::try_from(MAX_EVENTS).unwrap() {
+            incident = table
+                .correlate(&policy, &input(key, now, EventKind::PortScan, 2))
+                .unwrap();
+        }
+        let incident = incident.unwrap();
+        assert_eq!(incident.events.len(), MAX_EVENTS);
+        let incidents = table
+            .incidents_of("src_addr=10.0.0.1")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(incidents.len(), 1);
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
@@ -0,0 +1,337 @@
+//! The `log templates` table.
+
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{Mutex, PoisonError},
+};
+
+use anyhow::{Context, Result, bail};
+use regex::Regex;
+use rocksdb::OptimisticTransactionDB;
+use serde::{Deserialize, Serialize};
+
+use crate::{Map, Table, UniqueKey, event::LogFields, tables::Value, types::FromKeyValue};
+
+/// How the fields of a log are parsed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum LogParser {
+    /// A regular expression whose named capture groups are the fields.
+    Regex(String),
+    /// `key=value` pairs. Values may be enclosed in double quotes, but cannot contain the pair
+    /// delimiter.
+    KeyValue {
+        pair_delimiter: char,
+        kv_delimiter: char,
+    },
+}
+
+/// The template to extract fields from the logs of a service.
+///
+/// The fields named `src_addr`, `src_port`, `dst_addr`, `dst_port`, `user`, and `action` are
+/// extracted into the corresponding fields of `LogFields`, and the others into
+/// `LogFields::others`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LogTemplate {
+    pub service: String,
+    pub parser: LogParser,
+    /// Pairs of a field name in the log and the name to extract it as, e.g., `("src", "src_addr")`.
+    pub aliases: Vec<(String, String)>,
+}
+
+impl LogTemplate {
+    /// Extracts the fields from the content of a log.
+    ///
+    /// Returns `None` if the content does not match the template.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the regular expression of the template is invalid.
+    pub fn extract(&self, content: &str) -> Result<Option<LogFields>> {
+        self.extract_with(content, &RegexCache::default())
+    }
+
+    /// Extracts the fields from the content of a log, taking the compiled regular expression of
+    /// the template from `regexes`.
+    pub(crate) fn extract_with(
+        &self,
+        content: &str,
+        regexes: &RegexCache,
+    ) -> Result<Option<LogFields>> {
+        let re;
+        let pairs: Vec<(&str, &str)> = match &self.parser {
+            LogParser::Regex(pattern) => {
+                re = regexes.get(pattern)?;
+                let Some(captures) = re.captures(content) else {
+                    return Ok(None);
+                };
+                re.capture_names()
+                    .flatten()
+                    .filter_map(|name| captures.name(name).map(|m| (name, m.as_str())))
+                    .collect()
+            }
+            LogParser::KeyValue {
+                pair_delimiter,
+                kv_delimiter,
+            } => content
+                .split(*pair_delimiter)
+                .filter_map(|pair| pair.split_once(*kv_delimiter))
+                .map(|(key, value)| (key.trim(), value.trim().trim_matches('"')))
+                .collect(),
+        };
+        if pairs.is_empty() {
+            return Ok(None);
+        }
+
+        let mut fields = LogFields::default();
+        for (name, value) in pairs {
+            let name = self
+                .aliases
+                .iter()
+                .find_map(|(alias, target)| (alias == name).then_some(target.as_str()))
+                .unwrap_or(name);
+            let extracted = match name {
+                "src_addr" => set(&mut fields.src_addr, value.parse::<IpAddr>().ok()),
+                "src_port" => set(&mut fields.src_port, value.parse::<u16>().ok()),
+                "dst_addr" => set(&mut fields.dst_addr, value.parse::<IpAddr>().ok()),
+                "dst_port" => set(&mut fields.dst_port, value.parse::<u16>().ok()),
+                "user" => set(&mut fields.user, Some(value.to_string())),
+                "action" => set(&mut fields.action, Some(value.to_string())),
+                _ => false,
+            };
+            if !extracted {
+                fields.others.push((name.to_string(), value.to_string()));
+            }
+        }
+        Ok(Some(fields))
+    }
+}
+
+/// The compiled regular expressions of log templates, by pattern, so that a pattern is compiled
+/// only once however many logs it is applied to.
+#[derive(Debug, Default)]
+pub(crate) struct RegexCache(Mutex<HashMap<String, Regex>>);
+
+impl RegexCache {
+    /// Returns the compiled regular expression of `pattern`, compiling it if it is not cached.
+    fn get(&self, pattern: &str) -> Result<Regex> {
+        let mut regexes = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(re) = regexes.get(pattern) {
+            return Ok(re.clone());
+        }
+        let re = Regex::new(pattern).context("invalid regular expression")?;
+        regexes.insert(pattern.to_string(), re.clone());
+        Ok(re)
+    }
+}
+
+/// Sets `field` to `value` if it is not set yet and `value` is valid. Returns whether `field` was
+/// set.
+fn set<T>(field: &mut Option<T>, value: Option<T>) -> bool {
+    if field.is_some() || value.is_none() {
+        return false;
+    }
+    *field = value;
+    true
+}
+
+impl FromKeyValue for LogTemplate {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for LogTemplate {
+    type AsBytes<'a> = &'a [u8];
+
+    fn unique_key(&self) -> &[u8] {
+        self.service.as_bytes()
+    }
+}
+
+impl Value for LogTemplate {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// Functions for the `log templates` table.
+impl<'d> Table<'d, LogTemplate> {
+    /// Opens the `log templates` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::LOG_TEMPLATES).map(Table::new)
+    }
+
+    /// Sets the template for the service of `template`, replacing the existing one, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template has no service name or an invalid regular expression, or
+    /// the database operation fails.
+    pub fn set(&self, template: &LogTemplate) -> Result<()> {
+        if template.service.is_empty() {
+            bail!("service name must not be empty");
+        }
+        if let LogParser::Regex(pattern) = &template.parser {
+            Regex::new(pattern).context("invalid regular expression")?;
+        }
+        self.put(template)
+    }
+
+    /// Returns the template for the given service.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored template is invalid.
+    pub fn get(&self, service: &str) -> Result<Option<LogTemplate>> {
+        self.map
+            .get(service.as_bytes())?
+            .map(|value| super::deserialize(value.as_ref()))
+            .transpose()
+    }
+
+    /// Removes the template for the given service.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn remove(&self, service: &str) -> Result<()> {
+        self.map.delete(service.as_bytes())
+    }
+
+    /// Extracts the fields from the content of a log of the given service.
+    ///
+    /// Returns `None` if there is no template for the service or the content does not match it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored template is invalid.
+    pub fn extract(&self, service: &str, content: &str) -> Result<Option<LogFields>> {
+        self.extract_with(service, content, &RegexCache::default())
+    }
+
+    /// Extracts the fields from the content of a log of the given service, taking the compiled
+    /// regular expression of the template from `regexes`.
+    pub(crate) fn extract_with(
+        &self,
+        service: &str,
+        content: &str,
+        regexes: &RegexCache,
+    ) -> Result<Option<LogFields>> {
+        let Some(template) = self.get(service)? else {
+            return Ok(None);
+        };
+        template.extract_with(content, regexes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::IpAddr, sync::Arc};
+
+    use super::{LogParser, LogTemplate, RegexCache};
+    use crate::Store;
+
+    #[test]
+    fn extract() {
+        let store = setup_store();
+        let table = store.log_template_map();
+
+        let regex = LogTemplate {
+            service: "sshd".to_string(),
+            parser: LogParser::Regex(
+                r"(?P<action>Accepted|Failed) password for (?P<user>\S+) from (?P<src_addr>\S+) port (?P<src_port>\d+)"
+                    .to_string(),
+            ),
+            aliases: vec![],
+        };
+        table.set(&regex).unwrap();
+        let fields = table
+            .extract(
+                "sshd",
+                "Failed password for root from 10.0.0.1 port 22022 ssh2",
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(fields.src_addr, Some("10.0.0.1".parse::<IpAddr>().unwrap()));
+        assert_eq!(fields.src_port, Some(22022));
+        assert_eq!(fields.user.as_deref(), Some("root"));
+        assert_eq!(fields.action.as_deref(), Some("Failed"));
+        assert!(table.extract("sshd", "session opened").unwrap().is_none());
+        assert!(table.extract("httpd", "anything").unwrap().is_none());
+
+        let key_value = LogTemplate {
+            service: "firewall".to_string(),
+            parser: LogParser::KeyValue {
+                pair_delimiter: ' ',
+                kv_delimiter: '=',
+            },
+            aliases: vec![
+                ("src".to_string(), "src_addr".to_string()),
+                ("dst".to_string(), "dst_addr".to_string()),
+            ],
+        };
+        table.set(&key_value).unwrap();
+        let fields = table
+            .extract(
+                "firewall",
+                r#"src=10.0.0.1 dst=not-an-address act="deny" rule=7"#,
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(fields.src_addr, Some("10.0.0.1".parse::<IpAddr>().unwrap()));
+        assert_eq!(fields.dst_addr, None);
+        assert_eq!(
+            fields.others,
+            vec![
+                ("dst_addr".to_string(), "not-an-address".to_string()),
+                ("act".to_string(), "deny".to_string()),
+                ("rule".to_string(), "7".to_string()),
+            ]
+        );
+
+        let invalid = LogTemplate {
+            service: "broken".to_string(),
+            parser: LogParser::Regex("(".to_string()),
+            aliases: vec![],
+        };
+        assert!(table.set(&invalid).is_err());
+        table.remove("sshd").unwrap();
+        assert!(table.get("sshd").unwrap().is_none());
+    }
+
+    #[test]
+    fn regex_cache() {
+        let template = LogTemplate {
+            service: "sshd".to_string(),
+            parser: LogParser::Regex(r"from (?P<src_addr>\S+)".to_string()),
+            aliases: vec![],
+        };
+        let regexes = RegexCache::default();
+        for content in ["from 10.0.0.1", "from 10.0.0.2"] {
+            let fields = template.extract_with(content, &regexes).unwrap().unwrap();
+            assert_eq!(
+                fields.src_addr.unwrap().to_string(),
+                content.trim_start_matches("from ")
+            );
+        }
+        assert_eq!(regexes.0.lock().unwrap().len(), 1);
+
+        let invalid = LogTemplate {
+            parser: LogParser::Regex("(".to_string()),
+            ..template
+        };
+        assert!(invalid.extract_with("from 10.0.0.1", &regexes).is_err());
+        assert_eq!(regexes.0.lock().unwrap().len(), 1);
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
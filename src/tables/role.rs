@@ -0,0 +1,299 @@
+//! The `roles` table.
+//!
+//! Only custom roles are stored. The built-in roles are defined by `Role`.
+
+use std::borrow::Cow;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Utc};
+use rocksdb::OptimisticTransactionDB;
+use serde::{Deserialize, Serialize};
+
+use super::UniqueKey;
+use crate::{
+    Indexable, IndexedMap, IndexedMapUpdate, IndexedTable, Iterable, Permission, RoleId, Table,
+    types::{Account, FromKeyValue},
+};
+
+/// A named role defined as a set of permissions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CustomRole {
+    pub id: u32,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<Permission>,
+    pub creation_time: DateTime<Utc>,
+}
+
+impl CustomRole {
+    #[must_use]
+    pub fn is_permitted(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+}
+
+impl FromKeyValue for CustomRole {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for CustomRole {
+    type AsBytes<'a> = &'a [u8];
+
+    fn unique_key(&self) -> &[u8] {
+        self.name.as_bytes()
+    }
+}
+
+impl Indexable for CustomRole {
+    fn key(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(self.name.as_bytes())
+    }
+    fn index(&self) -> u32 {
+        self.id
+    }
+    fn make_indexed_key(key: Cow<[u8]>, _index: u32) -> Cow<[u8]> {
+        key
+    }
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+
+    fn set_index(&mut self, index: u32) {
+        self.id = index;
+    }
+
+    /// Refuses to remove a role that an account or the MFA configuration refers to, since its ID
+    /// is reused by the next role created.
+    fn check_remove(db: &OptimisticTransactionDB, id: u32) -> Result<()> {
+        let role = RoleId::Custom(id);
+        let accounts = Table::<Account>::open(db).context("accounts table must be present")?;
+        for account in accounts.iter(rocksdb::Direction::Forward, None) {
+            if account?.role == role {
+                bail!("role is assigned to an account");
+            }
+        }
+        if Table::<String>::open(db)
+            .context("configs table must be present")?
+            .is_mfa_required(role)?
+        {
+            bail!("role is required to sign in with a second factor");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Update {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub permissions: Option<Vec<Permission>>,
+}
+
+impl IndexedMapUpdate for Update {
+    type Entry = CustomRole;
+
+    fn key(&self) -> Option<Cow<'_, [u8]>> {
+        self.name.as_deref().map(str::as_bytes).map(Cow::Borrowed)
+    }
+
+    fn apply(&self, mut value: Self::Entry) -> Result<Self::Entry> {
+        if let Some(name) = self.name.as_deref() {
+            value.name.clear();
+            value.name.push_str(name);
+        }
+        if let Some(description) = self.description.as_deref() {
+            value.description.clear();
+            value.description.push_str(description);
+        }
+        if let Some(permissions) = self.permissions.as_deref() {
+            value.permissions.clear();
+            value.permissions.extend(permissions.iter().copied());
+        }
+        Ok(value)
+    }
+
+    fn verify(&self, value: &Self::Entry) -> bool {
+        if let Some(v) = self.name.as_deref()
+            && v != value.name
+        {
+            return false;
+        }
+        if let Some(v) = self.description.as_deref()
+            && v != value.description
+        {
+            return false;
+        }
+        if let Some(v) = self.permissions.as_deref()
+            && v != value.permissions
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Functions for the `roles` indexed map.
+impl<'d> IndexedTable<'d, CustomRole> {
+    /// Opens the `roles` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(super) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        IndexedMap::new(db, super::ROLES)
+            .map(IndexedTable::new)
+            .ok()
+    }
+
+    /// Updates the `CustomRole` from `old` to `new`, given `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `id` is invalid or the database operation fails.
+    pub fn update(&mut self, id: u32, old: &Update, new: &Update) -> Result<()> {
+        self.indexed_map.update(id, old, new)
+    }
+
+    /// Returns the permissions granted by the given role.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the custom role does not exist or the database operation fails.
+    pub fn permissions(&self, role: RoleId) -> Result<Vec<Permission>> {
+        match role {
+            RoleId::BuiltIn(role) => Ok(role.permissions()),
+            RoleId::Custom(id) => {
+                let Some(role) = self.get_by_id(id)? else {
+                    bail!("no such role");
+                };
+                Ok(role.permissions)
+            }
+        }
+    }
+
+    /// Returns `true` if the given role grants `permission`. A custom role that no longer exists
+    /// grants no permissions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn is_permitted(&self, role: RoleId, permission: Permission) -> Result<bool> {
+        match role {
+            RoleId::BuiltIn(role) => Ok(role.permissions().contains(&permission)),
+            RoleId::Custom(id) => Ok(self
+                .get_by_id(id)?
+                .is_some_and(|role| role.is_permitted(permission))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use chrono::Utc;
+
+    use crate::{
+        Action, CustomRole, PasswordPolicy, Permission, Resource, Role, RoleId, RoleUpdate, Store,
+        types::Account,
+    };
+
+    #[test]
+    fn permissions() {
+        let store = setup_store();
+        let mut table = store.role_map();
+        let read_events = Permission::new(Resource::Event, Action::Read);
+        let delete_events = Permission::new(Resource::Event, Action::Delete);
+
+        let id = table
+            .put(CustomRole {
+                id: u32::MAX,
+                name: "Event Reader".to_string(),
+                description: String::new(),
+                permissions: vec![read_events],
+                creation_time: Utc::now(),
+            })
+            .unwrap();
+        let custom = RoleId::Custom(id);
+        assert_eq!(table.permissions(custom).unwrap(), vec![read_events]);
+        assert!(table.is_permitted(custom, read_events).unwrap());
+        assert!(!table.is_permitted(custom, delete_events).unwrap());
+
+        let old = RoleUpdate {
+            name: None,
+            description: None,
+            permissions: Some(vec![read_events]),
+        };
+        let new = RoleUpdate {
+            name: None,
+            description: None,
+            permissions: Some(vec![read_events, delete_events]),
+        };
+        table.update(id, &old, &new).unwrap();
+        assert!(table.is_permitted(custom, delete_events).unwrap());
+
+        table.remove(id).unwrap();
+        assert!(table.permissions(custom).is_err());
+        assert!(!table.is_permitted(custom, read_events).unwrap());
+
+        assert!(
+            table
+                .is_permitted(Role::SecurityMonitor.into(), read_events)
+                .unwrap()
+        );
+        assert!(
+            !table
+                .is_permitted(Role::SecurityMonitor.into(), delete_events)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn remove_referenced() {
+        let store = setup_store();
+        let table = store.role_map();
+        let id = table
+            .put(CustomRole {
+                id: u32::MAX,
+                name: "Event Reader".to_string(),
+                description: String::new(),
+                permissions: vec![Permission::new(Resource::Event, Action::Read)],
+                creation_time: Utc::now(),
+            })
+            .unwrap();
+        let role = RoleId::Custom(id);
+
+        let account = Account::new(
+            "user1",
+            "password",
+            role,
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        let accounts = store.account_map();
+        accounts.put(&account).unwrap();
+        assert!(table.remove(id).is_err());
+
+        accounts.delete("user1").unwrap();
+        let configs = store.config_map();
+        configs.set_mfa_required_roles(&[role]).unwrap();
+        assert!(table.remove(id).is_err());
+
+        configs.set_mfa_required_roles(&[]).unwrap();
+        table.remove(id).unwrap();
+        assert!(table.permissions(role).is_err());
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
@@ -0,0 +1,378 @@
+//! The `sensors` and `sensor ingest` tables.
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use rocksdb::{Direction, OptimisticTransactionDB};
+use serde::{Deserialize, Serialize};
+
+use super::{Agent, TableIter};
+use crate::{
+    Customer, EXCLUSIVE, EventKind, IndexedTable, Iterable, Map, Table, UniqueKey, tables::Value,
+    types::FromKeyValue,
+};
+
+/// The number of hours the ingest statistics of a sensor are kept.
+const INGEST_RETENTION_HOURS: i64 = 24 * 7;
+
+/// A sensor that reports events, identified by the `sensor` field of the events.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Sensor {
+    pub name: String,
+    /// The ID of the node the sensor runs on.
+    pub node: u32,
+    /// The key of the agent of the sensor on the node.
+    pub agent_key: String,
+    pub customer_id: u32,
+}
+
+impl FromKeyValue for Sensor {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for Sensor {
+    type AsBytes<'a> = &'a [u8];
+
+    fn unique_key(&self) -> &[u8] {
+        self.name.as_bytes()
+    }
+}
+
+impl Value for Sensor {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// Functions for the `sensors` table.
+impl<'d> Table<'d, Sensor> {
+    /// Opens the `sensors` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::SENSORS).map(Table::new)
+    }
+
+    /// Registers a sensor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sensor has no name, the agent or the customer does not exist, a
+    /// sensor with the same name is already registered, or the database operation fails.
+    pub fn register(&self, sensor: &Sensor) -> Result<()> {
+        if sensor.name.is_empty() {
+            bail!("sensor name must not be empty");
+        }
+        if Table::<Agent>::open(self.map.db)
+            .context("agents table must be present")?
+            .get(sensor.node, &sensor.agent_key)?
+            .is_none()
+        {
+            bail!(
+                "agent {} does not exist on node {}",
+                sensor.agent_key,
+                sensor.node
+            );
+        }
+        if IndexedTable::<Customer>::open(self.map.db)
+            .context("customers table must be present")?
+            .get_by_id(sensor.customer_id)?
+            .is_none()
+        {
+            bail!("customer {} does not exist", sensor.customer_id);
+        }
+        self.insert(sensor)
+    }
+
+    /// Returns the sensor with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored sensor is invalid.
+    pub fn get(&self, name: &str) -> Result<Option<Sensor>> {
+        self.map
+            .get(name.as_bytes())?
+            .map(|value| super::deserialize(value.as_ref()))
+            .transpose()
+    }
+
+    /// Unregisters the sensor with the given name. Its ingest statistics are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn remove(&self, name: &str) -> Result<()> {
+        self.map.delete(name.as_bytes())
+    }
+
+    /// Returns the registered sensors that have not reported any event since `since`, along with
+    /// the time they were last seen, if ever.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored record is invalid.
+    pub fn quiet(&self, since: DateTime<Utc>) -> Result<Vec<(Sensor, Option<DateTime<Utc>>)>> {
+        let ingest = Table::<SensorIngest>::open(self.map.db)
+            .context("sensor ingest table must be present")?;
+        let mut quiet = Vec::new();
+        for sensor in self.iter(Direction::Forward, None) {
+            let sensor = sensor?;
+            let last_seen = ingest.last_seen(&sensor.name)?;
+            if last_seen.is_none_or(|time| time < since) {
+                quiet.push((sensor, last_seen));
+            }
+        }
+        Ok(quiet)
+    }
+}
+
+/// The number of events of each kind a sensor reported in an hour.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SensorIngest {
+    pub sensor: String,
+    /// The start of the hour.
+    pub hour: DateTime<Utc>,
+    pub counts: Vec<(EventKind, u64)>,
+    /// When the last event in the hour was stored.
+    pub last_seen: DateTime<Utc>,
+}
+
+impl SensorIngest {
+    fn key_prefix(sensor: &str) -> Vec<u8> {
+        let mut prefix = sensor.as_bytes().to_vec();
+        prefix.push(0);
+        prefix
+    }
+
+    fn key(sensor: &str, hour: DateTime<Utc>) -> Vec<u8> {
+        let mut key = Self::key_prefix(sensor);
+        key.extend(hour.timestamp().to_be_bytes());
+        key
+    }
+
+    /// Returns the total number of events in the hour.
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    fn add(&mut self, kind: EventKind, time: DateTime<Utc>) {
+        if let Some((_, count)) = self.counts.iter_mut().find(|(k, _)| *k == kind) {
+            *count += 1;
+        } else {
+            self.counts.push((kind, 1));
+        }
+        self.last_seen = self.last_seen.max(time);
+    }
+}
+
+impl FromKeyValue for SensorIngest {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for SensorIngest {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn unique_key(&self) -> Vec<u8> {
+        Self::key(&self.sensor, self.hour)
+    }
+}
+
+impl Value for SensorIngest {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// Functions for the `sensor ingest` table.
+impl<'d> Table<'d, SensorIngest> {
+    /// Opens the `sensor ingest` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::SENSOR_INGEST).map(Table::new)
+    }
+
+    /// Returns the ingest statistics of the given sensor, from the oldest hour to the newest. Only
+    /// the last seven days are kept.
+    #[must_use]
+    pub fn ingest_of(&self, sensor: &str) -> TableIter<'_, SensorIngest> {
+        self.prefix_iter(Direction::Forward, None, &SensorIngest::key_prefix(sensor))
+    }
+
+    /// Returns the time the given sensor last reported an event, or `None` if it never did in the
+    /// last seven days.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored record is invalid.
+    pub fn last_seen(&self, sensor: &str) -> Result<Option<DateTime<Utc>>> {
+        let mut newest =
+            self.prefix_iter(Direction::Reverse, None, &SensorIngest::key_prefix(sensor));
+        newest
+            .next()
+            .transpose()
+            .map(|ingest| ingest.map(|ingest| ingest.last_seen))
+    }
+
+    /// Counts an event of the given kind reported by the sensor at `time`. Statistics older than
+    /// seven days are dropped when the sensor reports the first event in an hour.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored record is invalid.
+    pub(crate) fn record(&self, sensor: &str, kind: EventKind, time: DateTime<Utc>) -> Result<()> {
+        let hour = time
+            .duration_trunc(TimeDelta::hours(1))
+            .context("invalid time")?;
+        let key = SensorIngest::key(sensor, hour);
+        let new_hour = loop {
+            let txn = self.map.db.transaction();
+            let old = txn
+                .get_for_update_cf(self.map.cf, &key, EXCLUSIVE)
+                .context("cannot read sensor ingest")?;
+            let new_hour = old.is_none();
+            let mut ingest = match old {
+                Some(value) => super::deserialize(&value)?,
+                None => SensorIngest {
+                    sensor: sensor.to_string(),
+                    hour,
+                    counts: Vec::new(),
+                    last_seen: time,
+                },
+            };
+            ingest.add(kind, time);
+            txn.put_cf(self.map.cf, &key, ingest.value())
+                .context("cannot write sensor ingest")?;
+            match txn.commit() {
+                Ok(()) => break new_hour,
+                Err(e) => {
+                    if !e.as_ref().starts_with("Resource busy:") {
+                        return Err(e).context("failed to count sensor ingest");
+                    }
+                }
+            }
+        };
+        if new_hour {
+            let cutoff = SensorIngest::key(sensor, hour - TimeDelta::hours(INGEST_RETENTION_HOURS));
+            let prefix = SensorIngest::key_prefix(sensor);
+            let iter = self.map.db.iterator_cf(
+                self.map.cf,
+                rocksdb::IteratorMode::From(&prefix, Direction::Forward),
+            );
+            for item in iter {
+                let (old, _) = item.context("cannot read sensor ingest")?;
+                if !old.starts_with(&prefix) || old.as_ref() >= cutoff.as_slice() {
+                    break;
+                }
+                self.map.delete(&old)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{TimeDelta, TimeZone, Utc};
+
+    use super::Sensor;
+    use crate::{Agent, AgentKind, AgentStatus, Customer, EventKind, Iterable, Store};
+
+    #[test]
+    fn register_and_count() {
+        let store = setup_store();
+        let customer_id = store
+            .customer_map()
+            .put(Customer {
+                id: u32::MAX,
+                name: "customer".to_string(),
+                description: String::new(),
+                networks: vec![],
+                creation_time: Utc::now(),
+            })
+            .unwrap();
+        let agent = Agent::new(
+            1,
+            "piglet".to_string(),
+            AgentKind::Sensor,
+            AgentStatus::Enabled,
+            None,
+            None,
+        )
+        .unwrap();
+        store.agents_map().insert(&agent).unwrap();
+
+        let sensors = store.sensor_map();
+        let sensor = |name: &str, agent_key: &str| Sensor {
+            name: name.to_string(),
+            node: 1,
+            agent_key: agent_key.to_string(),
+            customer_id,
+        };
+        sensors.register(&sensor("sensor1", "piglet")).unwrap();
+        sensors.register(&sensor("sensor2", "piglet")).unwrap();
+        assert!(sensors.register(&sensor("sensor1", "piglet")).is_err());
+        assert!(sensors.register(&sensor("sensor3", "hog")).is_err());
+        assert_eq!(
+            sensors.get("sensor1").unwrap(),
+            Some(sensor("sensor1", "piglet"))
+        );
+
+        let ingest = store.sensor_ingest_map();
+        let time = Utc.with_ymd_and_hms(2025, 1, 1, 10, 30, 0).unwrap();
+        let old = time - TimeDelta::days(8);
+        ingest.record("sensor1", EventKind::PortScan, old).unwrap();
+        ingest.record("sensor1", EventKind::PortScan, time).unwrap();
+        ingest
+            .record(
+                "sensor1",
+                EventKind::DnsCovertChannel,
+                time + TimeDelta::minutes(10),
+            )
+            .unwrap();
+        ingest
+            .record(
+                "sensor1",
+                EventKind::PortScan,
+                time + TimeDelta::minutes(20),
+            )
+            .unwrap();
+        let hours = ingest
+            .ingest_of("sensor1")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(hours.len(), 1);
+        assert_eq!(
+            hours[0].counts,
+            vec![(EventKind::PortScan, 2), (EventKind::DnsCovertChannel, 1)]
+        );
+        assert_eq!(hours[0].total(), 3);
+        assert_eq!(
+            ingest.last_seen("sensor1").unwrap(),
+            Some(time + TimeDelta::minutes(20))
+        );
+        assert_eq!(ingest.last_seen("sensor").unwrap(), None);
+
+        let quiet = sensors.quiet(time).unwrap();
+        assert_eq!(quiet, vec![(sensor("sensor2", "piglet"), None)]);
+        let quiet = sensors.quiet(time + TimeDelta::hours(1)).unwrap();
+        assert_eq!(quiet.len(), 2);
+        assert_eq!(sensors.iter(rocksdb::Direction::Forward, None).count(), 2);
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
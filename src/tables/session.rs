@@ -0,0 +1,474 @@
+//! The `sessions` table.
+//!
+//! The sessions of an account are stored together under its username, so that the limit on
+//! parallel sessions is enforced in a single transaction.
+
+use std::net::IpAddr;
+
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, TimeDelta, Utc};
+use data_encoding::BASE64URL_NOPAD;
+use ring::rand::{SecureRandom, SystemRandom};
+use rocksdb::{IteratorMode, OptimisticTransactionDB, Transaction};
+use serde::{Deserialize, Serialize};
+
+use crate::{EXCLUSIVE, LockoutState, Map, Table, types::Account};
+
+/// The number of random bytes of a session ID.
+const SESSION_ID_LEN: usize = 32;
+
+/// A sign-in session of an account.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Session {
+    pub id: String,
+    pub username: String,
+    pub creation_time: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+    pub client_ip: IpAddr,
+    pub user_agent: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Session {
+    #[must_use]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// What to do when an account already has as many sessions as `Account::max_parallel_sessions`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SessionLimitAction {
+    /// Fails to create a session.
+    Reject,
+    /// Revokes the oldest session to create a new one.
+    EvictOldest,
+}
+
+/// Functions for the `sessions` table.
+impl<'d> Table<'d, Session> {
+    /// Opens the `sessions` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(super) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::SESSIONS).map(Table::new)
+    }
+
+    /// Creates a session of the account with the given username from `client_ip`, which expires
+    /// after `lifetime`. Expired sessions of the account are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in the following cases:
+    ///
+    /// * The account does not exist, or is suspended or locked out.
+    /// * `client_ip` is not in `Account::allow_access_from`.
+    /// * `lifetime` is too long for the expiry time to be represented.
+    /// * The account has as many sessions as `Account::max_parallel_sessions` and `on_limit` is
+    ///   `SessionLimitAction::Reject`.
+    /// * Random number generation for a session ID fails.
+    /// * The database operation fails.
+    pub fn create(
+        &self,
+        username: &str,
+        client_ip: IpAddr,
+        user_agent: &str,
+        lifetime: TimeDelta,
+        on_limit: SessionLimitAction,
+    ) -> Result<Session> {
+        let accounts =
+            Table::<Account>::open(self.map.db).context("accounts table must be present")?;
+        let mut id = [0_u8; SESSION_ID_LEN];
+        SystemRandom::new().fill(&mut id)?;
+        let now = Utc::now();
+        let Some(expires_at) = now.checked_add_signed(lifetime) else {
+            bail!("session lifetime is too long");
+        };
+        let session = Session {
+            id: BASE64URL_NOPAD.encode(&id),
+            username: username.to_string(),
+            creation_time: now,
+            last_activity: now,
+            client_ip,
+            user_agent: user_agent.to_string(),
+            expires_at,
+        };
+
+        // The account is read in the same transaction as the sessions, so that a session is not
+        // created for an account that is suspended or deleted, or whose password is changed,
+        // before the session is stored.
+        self.modify(username, "failed to create session", |txn, sessions| {
+            let Some(account) = accounts.get_with_transaction(username, txn)? else {
+                bail!("no such account");
+            };
+            if !is_active(&account, now) {
+                bail!("account is suspended or locked out");
+            }
+            if !account.is_access_allowed(client_ip) {
+                bail!("access from {client_ip} is not allowed");
+            }
+            sessions.retain(|s| !s.is_expired(now));
+            if let Some(limit) = account.max_parallel_sessions {
+                while sessions.len() >= usize::from(limit) {
+                    if on_limit == SessionLimitAction::Reject || sessions.is_empty() {
+                        bail!("too many sessions");
+                    }
+                    let oldest = sessions
+                        .iter()
+                        .enumerate()
+                        .min_by_key(|(_, s)| s.creation_time)
+                        .map(|(i, _)| i)
+                        .expect("not empty");
+                    sessions.remove(oldest);
+                }
+            }
+            sessions.push(session.clone());
+            Ok(())
+        })?;
+        Ok(session)
+    }
+
+    /// Returns the session with the given ID, even if it has expired but not been purged yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored sessions are invalid.
+    pub fn get(&self, username: &str, id: &str) -> Result<Option<Session>> {
+        Ok(self.sessions(username)?.into_iter().find(|s| s.id == id))
+    }
+
+    /// Returns the sessions of the account with the given username.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored sessions are invalid.
+    pub fn sessions(&self, username: &str) -> Result<Vec<Session>> {
+        self.map
+            .get(username.as_bytes())?
+            .map_or(Ok(Vec::new()), |value| super::deserialize(value.as_ref()))
+    }
+
+    /// Records activity in the session with the given ID. Returns `false` if there is no such
+    /// session, it has expired, or its account no longer exists or is suspended or locked out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored sessions are invalid.
+    pub fn touch(&self, username: &str, id: &str) -> Result<bool> {
+        let now = Utc::now();
+        let accounts =
+            Table::<Account>::open(self.map.db).context("accounts table must be present")?;
+        self.modify(username, "failed to touch session", |txn, sessions| {
+            let active = accounts
+                .get_with_transaction(username, txn)?
+                .is_some_and(|account| is_active(&account, now));
+            if !active {
+                return Ok(false);
+            }
+            let Some(session) = sessions
+                .iter_mut()
+                .find(|s| s.id == id && !s.is_expired(now))
+            else {
+                return Ok(false);
+            };
+            session.last_activity = now;
+            Ok(true)
+        })
+    }
+
+    /// Revokes the session with the given ID. Returns `false` if there is no such session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored sessions are invalid.
+    pub fn revoke(&self, username: &str, id: &str) -> Result<bool> {
+        self.modify(username, "failed to revoke session", |_, sessions| {
+            let len = sessions.len();
+            sessions.retain(|s| s.id != id);
+            Ok(sessions.len() < len)
+        })
+    }
+
+    /// Revokes all the sessions of the account with the given username. Returns the number of
+    /// sessions revoked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored sessions are invalid.
+    pub fn revoke_all(&self, username: &str) -> Result<usize> {
+        self.modify(username, "failed to revoke sessions", |_, sessions| {
+            Ok(std::mem::take(sessions).len())
+        })
+    }
+
+    /// Removes the expired sessions of all accounts. Returns the number of sessions removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or the stored sessions are invalid.
+    pub fn purge_expired(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut usernames = Vec::new();
+        for item in self.map.db.iterator_cf(self.map.cf, IteratorMode::Start) {
+            let (key, _) = item.context("cannot read sessions")?;
+            usernames.push(String::from_utf8_lossy(&key).into_owned());
+        }
+        let mut purged = 0;
+        for username in usernames {
+            purged += self.modify(&username, "failed to purge sessions", |_, sessions| {
+                let len = sessions.len();
+                sessions.retain(|s| !s.is_expired(now));
+                Ok(len - sessions.len())
+            })?;
+        }
+        Ok(purged)
+    }
+
+    /// Applies `f` to the sessions of the account with the given username and stores them in the
+    /// transaction given to `f`. Returns the output of `f`, or its error without storing the
+    /// sessions.
+    fn modify<T, F>(&self, username: &str, error: &'static str, f: F) -> Result<T>
+    where
+        F: Fn(&Transaction<'_, OptimisticTransactionDB>, &mut Vec<Session>) -> Result<T>,
+    {
+        loop {
+            let txn = self.map.db.transaction();
+            let mut sessions = self.sessions_with_transaction(username, &txn)?;
+            let output = f(&txn, &mut sessions)?;
+            if sessions.is_empty() {
+                txn.delete_cf(self.map.cf, username.as_bytes())
+                    .context("cannot delete sessions")?;
+            } else {
+                txn.put_cf(
+                    self.map.cf,
+                    username.as_bytes(),
+                    super::serialize(&sessions)?,
+                )
+                .context("cannot write sessions")?;
+            }
+            match txn.commit() {
+                Ok(()) => return Ok(output),
+                Err(e) => {
+                    if !e.as_ref().starts_with("Resource busy:") {
+                        return Err(e).context(error);
+                    }
+                }
+            }
+        }
+    }
+
+    fn sessions_with_transaction(
+        &self,
+        username: &str,
+        txn: &Transaction<OptimisticTransactionDB>,
+    ) -> Result<Vec<Session>> {
+        txn.get_for_update_cf(self.map.cf, username.as_bytes(), EXCLUSIVE)
+            .context("cannot read sessions")?
+            .map_or(Ok(Vec::new()), |value| super::deserialize(&value))
+    }
+}
+
+/// Returns `true` if `account` can have sessions at `now`.
+fn is_active(account: &Account, now: DateTime<Utc>) -> bool {
+    !account.is_suspended && account.lockout_state(now) == LockoutState::Unlocked
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::IpAddr, sync::Arc};
+
+    use chrono::TimeDelta;
+
+    use super::SessionLimitAction;
+    use crate::{HostNetworkGroup, PasswordPolicy, Role, Store, types::Account};
+
+    #[test]
+    fn limit_and_revoke() {
+        let store = setup_store();
+        let mut account = Account::new(
+            "user1",
+            "password",
+            Role::SecurityMonitor.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            Some(2),
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        let allowed: IpAddr = "10.0.0.1".parse().unwrap();
+        account.allow_access_from =
+            Some(HostNetworkGroup::new(vec![allowed], Vec::new(), Vec::new()));
+        store.account_map().put(&account).unwrap();
+        let table = store.session_map();
+        let hour = TimeDelta::hours(1);
+
+        assert!(
+            table
+                .create("user2", allowed, "", hour, SessionLimitAction::Reject)
+                .is_err()
+        );
+        assert!(
+            table
+                .create(
+                    "user1",
+                    "10.0.0.2".parse().unwrap(),
+                    "",
+                    hour,
+                    SessionLimitAction::Reject
+                )
+                .is_err()
+        );
+
+        let first = table
+            .create("user1", allowed, "agent", hour, SessionLimitAction::Reject)
+            .unwrap();
+        let second = table
+            .create("user1", allowed, "agent", hour, SessionLimitAction::Reject)
+            .unwrap();
+        assert_ne!(first.id, second.id);
+        assert!(
+            table
+                .create("user1", allowed, "agent", hour, SessionLimitAction::Reject)
+                .is_err()
+        );
+        let third = table
+            .create(
+                "user1",
+                allowed,
+                "agent",
+                hour,
+                SessionLimitAction::EvictOldest,
+            )
+            .unwrap();
+        assert!(table.get("user1", &first.id).unwrap().is_none());
+        assert!(table.touch("user1", &third.id).unwrap());
+        assert!(!table.touch("user1", &first.id).unwrap());
+
+        assert!(table.revoke("user1", &second.id).unwrap());
+        assert!(!table.revoke("user1", &second.id).unwrap());
+        assert_eq!(table.revoke_all("user1").unwrap(), 1);
+        assert!(table.sessions("user1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn purge_expired() {
+        let store = setup_store();
+        let account = Account::new(
+            "user1",
+            "password",
+            Role::SecurityMonitor.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        store.account_map().put(&account).unwrap();
+        let table = store.session_map();
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+        let active = table
+            .create(
+                "user1",
+                ip,
+                "",
+                TimeDelta::hours(1),
+                SessionLimitAction::Reject,
+            )
+            .unwrap();
+        let expired = table
+            .create(
+                "user1",
+                ip,
+                "",
+                TimeDelta::zero(),
+                SessionLimitAction::Reject,
+            )
+            .unwrap();
+        assert!(!table.touch("user1", &expired.id).unwrap());
+        assert!(
+            table
+                .create("user1", ip, "", TimeDelta::MAX, SessionLimitAction::Reject)
+                .is_err()
+        );
+        assert_eq!(table.purge_expired().unwrap(), 1);
+        assert_eq!(table.sessions("user1").unwrap(), vec![active]);
+        assert_eq!(table.purge_expired().unwrap(), 0);
+    }
+
+    #[test]
+    fn revoked_with_account() {
+        let store = setup_store();
+        let accounts = store.account_map();
+        let account = Account::new(
+            "user1",
+            "password",
+            Role::SecurityMonitor.into(),
+            String::new(),
+            String::new(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &PasswordPolicy::default(),
+        )
+        .unwrap();
+        accounts.put(&account).unwrap();
+        let table = store.session_map();
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let hour = TimeDelta::hours(1);
+
+        let session = table
+            .create("user1", ip, "", hour, SessionLimitAction::Reject)
+            .unwrap();
+        accounts.suspend_account("user1").unwrap();
+        assert!(table.sessions("user1").unwrap().is_empty());
+        assert!(
+            table
+                .create("user1", ip, "", hour, SessionLimitAction::Reject)
+                .is_err()
+        );
+        assert!(!table.touch("user1", &session.id).unwrap());
+        accounts.unsuspend_account("user1").unwrap();
+
+        let session = table
+            .create("user1", ip, "", hour, SessionLimitAction::Reject)
+            .unwrap();
+        accounts
+            .update(
+                b"user1",
+                &Some("new password".to_string()),
+                None,
+                &None,
+                &None,
+                &None,
+                &None,
+                &None,
+                &None,
+                &None,
+            )
+            .unwrap();
+        assert!(table.get("user1", &session.id).unwrap().is_none());
+
+        table
+            .create("user1", ip, "", hour, SessionLimitAction::Reject)
+            .unwrap();
+        accounts.delete("user1").unwrap();
+        assert!(table.sessions("user1").unwrap().is_empty());
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
@@ -0,0 +1,282 @@
+//! The `severity overrides` table.
+
+use std::{net::IpAddr, num::NonZeroU8};
+
+use anyhow::{Context, Result, bail};
+use num_traits::ToPrimitive;
+use rocksdb::{Direction, OptimisticTransactionDB};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Customer, EventKind, IndexedTable, Iterable, Map, Table, UniqueKey, tables::Value,
+    types::FromKeyValue,
+};
+
+/// The highest level an event can have.
+const MAX_LEVEL: u8 = 5;
+
+/// The events a severity override applies to, in addition to their kind.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SeverityScope {
+    /// All events of the kind.
+    Global,
+    /// Events whose source or destination address belongs to the customer with the given ID.
+    Customer(u32),
+    /// Events reported by the given sensor.
+    Sensor(String),
+}
+
+impl SeverityScope {
+    /// Returns the precedence of the scope; a more specific scope takes precedence.
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Global => 0,
+            Self::Customer(_) => 1,
+            Self::Sensor(_) => 2,
+        }
+    }
+}
+
+/// A level that replaces the level assigned by the detector to events of a kind.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SeverityOverride {
+    pub kind: EventKind,
+    pub scope: SeverityScope,
+    pub level: NonZeroU8,
+}
+
+impl SeverityOverride {
+    fn key_prefix(kind: EventKind) -> Vec<u8> {
+        kind.to_u32()
+            .expect("`EventKind` fits in u32")
+            .to_be_bytes()
+            .to_vec()
+    }
+
+    fn key(kind: EventKind, scope: &SeverityScope) -> Vec<u8> {
+        let mut key = Self::key_prefix(kind);
+        key.push(scope.precedence());
+        match scope {
+            SeverityScope::Global => {}
+            SeverityScope::Customer(id) => key.extend(id.to_be_bytes()),
+            SeverityScope::Sensor(sensor) => key.extend(sensor.as_bytes()),
+        }
+        key
+    }
+}
+
+impl FromKeyValue for SeverityOverride {
+    fn from_key_value(_key: &[u8], value: &[u8]) -> Result<Self> {
+        super::deserialize(value)
+    }
+}
+
+impl UniqueKey for SeverityOverride {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn unique_key(&self) -> Vec<u8> {
+        Self::key(self.kind, &self.scope)
+    }
+}
+
+impl Value for SeverityOverride {
+    type AsBytes<'a> = Vec<u8>;
+
+    fn value(&self) -> Vec<u8> {
+        super::serialize(self).expect("serializable")
+    }
+}
+
+/// All severity overrides, loaded once to resolve the levels of many events.
+#[derive(Clone, Debug, Default)]
+pub struct SeverityOverrides {
+    overrides: Vec<SeverityOverride>,
+    customers: Vec<Customer>,
+}
+
+impl SeverityOverrides {
+    /// Returns the level that overrides the detector level of an event, or `None` if no override
+    /// applies. A sensor-scoped override takes precedence over a customer-scoped one, which takes
+    /// precedence over a global one.
+    pub(crate) fn level(
+        &self,
+        kind: EventKind,
+        sensor: &str,
+        src_addrs: &[IpAddr],
+        dst_addrs: &[IpAddr],
+    ) -> Option<NonZeroU8> {
+        self.overrides
+            .iter()
+            .filter(|o| o.kind == kind)
+            .filter(|o| match &o.scope {
+                SeverityScope::Global => true,
+                SeverityScope::Customer(id) => self
+                    .customers
+                    .iter()
+                    .find(|customer| customer.id == *id)
+                    .is_some_and(|customer| {
+                        src_addrs
+                            .iter()
+                            .chain(dst_addrs)
+                            .any(|&addr| customer.contains(addr))
+                    }),
+                SeverityScope::Sensor(s) => s == sensor,
+            })
+            .max_by_key(|o| o.scope.precedence())
+            .map(|o| o.level)
+    }
+}
+
+/// Functions for the `severity overrides` table.
+impl<'d> Table<'d, SeverityOverride> {
+    /// Opens the `severity overrides` table in the database.
+    ///
+    /// Returns `None` if the table does not exist.
+    pub(crate) fn open(db: &'d OptimisticTransactionDB) -> Option<Self> {
+        Map::open(db, super::SEVERITY_OVERRIDES).map(Table::new)
+    }
+
+    /// Stores an override, replacing the one with the same kind and scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the level is greater than 5 or the database operation fails.
+    pub fn set(&self, entry: &SeverityOverride) -> Result<()> {
+        if entry.level.get() > MAX_LEVEL {
+            bail!("level must be between 1 and {MAX_LEVEL}");
+        }
+        self.put(entry)
+    }
+
+    /// Removes the override with the given kind and scope.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails.
+    pub fn remove(&self, kind: EventKind, scope: &SeverityScope) -> Result<()> {
+        self.map.delete(&SeverityOverride::key(kind, scope))
+    }
+
+    /// Returns the overrides for the given kind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored override is invalid.
+    pub fn overrides_of(&self, kind: EventKind) -> Result<Vec<SeverityOverride>> {
+        self.prefix_iter(
+            Direction::Forward,
+            None,
+            &SeverityOverride::key_prefix(kind),
+        )
+        .collect()
+    }
+
+    /// Loads all overrides, along with the customers they refer to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database operation fails or a stored value is invalid.
+    pub fn load(&self) -> Result<SeverityOverrides> {
+        let overrides = self
+            .iter(Direction::Forward, None)
+            .collect::<Result<Vec<_>>>()?;
+        let customers = if overrides
+            .iter()
+            .any(|o| matches!(o.scope, SeverityScope::Customer(_)))
+        {
+            IndexedTable::<Customer>::open(self.map.db)
+                .context("customers table must be present")?
+                .iter(Direction::Forward, None)
+                .collect::<Result<Vec<_>>>()?
+        } else {
+            Vec::new()
+        };
+        Ok(SeverityOverrides {
+            overrides,
+            customers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::IpAddr, num::NonZeroU8, sync::Arc};
+
+    use super::{SeverityOverride, SeverityScope};
+    use crate::{
+        Customer, CustomerNetwork, EventKind, HostNetworkGroup, Store, event::NetworkType,
+    };
+
+    #[test]
+    fn scope_precedence() {
+        let store = setup_store();
+        let addr: IpAddr = "10.0.0.1".parse().unwrap();
+        let customer_id = store
+            .customer_map()
+            .put(Customer {
+                id: u32::MAX,
+                name: "customer".to_string(),
+                description: String::new(),
+                networks: vec![CustomerNetwork {
+                    name: "network".to_string(),
+                    description: String::new(),
+                    network_type: NetworkType::Intranet,
+                    network_group: HostNetworkGroup::new(vec![addr], vec![], vec![]),
+                }],
+                creation_time: chrono::Utc::now(),
+            })
+            .unwrap();
+
+        let table = store.severity_override_map();
+        let set = |scope, level| {
+            table
+                .set(&SeverityOverride {
+                    kind: EventKind::NonBrowser,
+                    scope,
+                    level: NonZeroU8::new(level).unwrap(),
+                })
+                .unwrap();
+        };
+        set(SeverityScope::Global, 1);
+        set(SeverityScope::Customer(customer_id), 2);
+        set(SeverityScope::Sensor("sensor1".to_string()), 5);
+        assert!(
+            table
+                .set(&SeverityOverride {
+                    kind: EventKind::NonBrowser,
+                    scope: SeverityScope::Global,
+                    level: NonZeroU8::new(6).unwrap(),
+                })
+                .is_err()
+        );
+        assert_eq!(table.overrides_of(EventKind::NonBrowser).unwrap().len(), 3);
+        assert!(table.overrides_of(EventKind::PortScan).unwrap().is_empty());
+
+        let overrides = table.load().unwrap();
+        let other: IpAddr = "192.168.0.1".parse().unwrap();
+        let level = |kind, sensor, addr| {
+            overrides
+                .level(kind, sensor, &[addr], &[])
+                .map(NonZeroU8::get)
+        };
+        assert_eq!(level(EventKind::NonBrowser, "sensor1", addr), Some(5));
+        assert_eq!(level(EventKind::NonBrowser, "sensor2", addr), Some(2));
+        assert_eq!(level(EventKind::NonBrowser, "sensor2", other), Some(1));
+        assert_eq!(level(EventKind::PortScan, "sensor1", addr), None);
+
+        table
+            .remove(EventKind::NonBrowser, &SeverityScope::Global)
+            .unwrap();
+        let overrides = table.load().unwrap();
+        assert_eq!(
+            overrides.level(EventKind::NonBrowser, "sensor2", &[other], &[]),
+            None
+        );
+    }
+
+    fn setup_store() -> Arc<Store> {
+        let db_dir = tempfile::tempdir().unwrap();
+        let backup_dir = tempfile::tempdir().unwrap();
+        Arc::new(Store::new(db_dir.path(), backup_dir.path()).unwrap())
+    }
+}
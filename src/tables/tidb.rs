@@ -1,6 +1,9 @@
 //! The `tidb` table.
 
-use std::io::{BufReader, Read};
+use std::{
+    collections::HashMap,
+    io::{BufReader, Read},
+};
 
 use anyhow::{Context, Result, bail};
 use data_encoding::BASE64;
@@ -76,6 +79,80 @@ pub struct Rule {
     pub confidence: Option<f32>,
 }
 
+impl Rule {
+    /// Returns the MITRE ATT&CK technique IDs listed in the references of the rule, either as
+    /// bare IDs (e.g., `T1110.001`) or as URLs under `attack.mitre.org/techniques/`.
+    #[must_use]
+    pub fn techniques(&self) -> Vec<String> {
+        let Some(references) = &self.references else {
+            return Vec::new();
+        };
+        let mut techniques = Vec::new();
+        for reference in references {
+            let reference = reference.trim();
+            let id = if let Some((_, path)) = reference.split_once("attack.mitre.org/techniques/") {
+                path.trim_end_matches('/').replacen('/', ".", 1)
+            } else {
+                reference.to_string()
+            };
+            if is_technique_id(&id) && !techniques.contains(&id) {
+                techniques.push(id);
+            }
+        }
+        techniques
+    }
+}
+
+/// Returns whether `id` has the form of a MITRE ATT&CK technique ID, `T` followed by four digits
+/// and an optional three-digit sub-technique.
+fn is_technique_id(id: &str) -> bool {
+    let Some(digits) = id.strip_prefix('T') else {
+        return false;
+    };
+    let (technique, sub_technique) = digits.split_once('.').unwrap_or((digits, "000"));
+    technique.len() == 4
+        && technique.bytes().all(|b| b.is_ascii_digit())
+        && sub_technique.len() == 3
+        && sub_technique.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The MITRE ATT&CK techniques of TI rules, which override the techniques of the kinds of the
+/// events that matched the rules.
+#[derive(Clone, Debug, Default)]
+pub struct RuleTechniques {
+    rules: HashMap<(String, u32), Vec<String>>,
+}
+
+impl RuleTechniques {
+    /// Collects the techniques of the rules in the given TI databases. Rules without any
+    /// technique are left out.
+    #[must_use]
+    pub fn new(tidbs: &[Tidb]) -> Self {
+        let rules = tidbs
+            .iter()
+            .flat_map(|tidb| {
+                tidb.patterns.iter().filter_map(|rule| {
+                    let techniques = rule.techniques();
+                    if techniques.is_empty() {
+                        None
+                    } else {
+                        Some(((tidb.name.clone(), rule.rule_id), techniques))
+                    }
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Returns the techniques of the rule `rule_id` in the TI database `db_name`, if any.
+    #[must_use]
+    pub fn get(&self, db_name: &str, rule_id: u32) -> Option<&[String]> {
+        self.rules
+            .get(&(db_name.to_string(), rule_id))
+            .map(Vec::as_slice)
+    }
+}
+
 #[derive(Clone, Copy, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Kind {
@@ -172,6 +249,16 @@ impl<'d> Table<'d, Tidb> {
         self.iter(Direction::Forward, None).collect()
     }
 
+    /// Returns the MITRE ATT&CK techniques of the rules in all TI databases.
+    ///
+    /// # Errors
+    ///
+    /// * Returns an error if it fails to read database
+    /// * Returns an error if it fails to decode TI database
+    pub fn rule_techniques(&self) -> Result<RuleTechniques> {
+        Ok(RuleTechniques::new(&self.get_list()?))
+    }
+
     /// For a specified `(name, version)` in the provided vector,
     /// if matched tidb is found, returns `(name, None)`.
     /// Otherwise, returns `(name, Some(new_tidb))`.
@@ -297,6 +384,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn rule_techniques() {
+        let mut tidb = create_entry("tidb");
+        tidb.patterns = vec![
+            create_rule(
+                1,
+                &[
+                    "https://attack.mitre.org/techniques/T1110/001/",
+                    "T1046",
+                    "T1046",
+                    "https://example.com/T1234",
+                ],
+            ),
+            create_rule(2, &["T12345", "t1046"]),
+        ];
+        assert_eq!(tidb.patterns[0].techniques(), vec!["T1110.001", "T1046"]);
+        assert!(tidb.patterns[1].techniques().is_empty());
+
+        let techniques = super::RuleTechniques::new(&[tidb]);
+        assert_eq!(
+            techniques.get("tidb", 1),
+            Some(["T1110.001".to_string(), "T1046".to_string()].as_slice())
+        );
+        assert_eq!(techniques.get("tidb", 2), None);
+        assert_eq!(techniques.get("other", 1), None);
+    }
+
+    fn create_rule(rule_id: u32, references: &[&str]) -> super::Rule {
+        super::Rule {
+            rule_id,
+            category: crate::EventCategory::Reconnaissance,
+            name: "rule".to_string(),
+            kind: None,
+            description: None,
+            references: Some(references.iter().map(ToString::to_string).collect()),
+            samples: None,
+            signatures: None,
+            confidence: None,
+        }
+    }
+
     fn create_entry(name: &str) -> Tidb {
         Tidb {
             id: 1,
@@ -8,8 +8,8 @@ use std::{
     ops::{BitAnd, RangeInclusive},
 };
 
-use anyhow::{Result, anyhow};
-use attrievent::attribute::RawEventKind;
+use anyhow::{Context, Result, anyhow, bail};
+use attrievent::attribute::{RawEventAttrKind, RawEventKind};
 use chrono::{DateTime, Utc};
 use ipnet::{IpNet, Ipv4Net, Ipv6Net};
 use rocksdb::OptimisticTransactionDB;
@@ -17,7 +17,7 @@ use serde::{Deserialize, Serialize};
 
 use super::UniqueKey;
 use crate::{
-    Indexable, IndexedMap, IndexedMapUpdate, IndexedTable,
+    CustomEventSchema, EventKind, Indexable, IndexedMap, IndexedMapUpdate, IndexedTable, Table,
     collections::Indexed,
     types::{EventCategory, FromKeyValue, HostNetworkGroup},
 };
@@ -67,9 +67,14 @@ impl Indexable for TriagePolicy {
     fn set_index(&mut self, index: u32) {
         self.id = index;
     }
+
+    /// Refuses to store a policy with a packet attribute rule that cannot be applied to any event.
+    fn check_insert(&self, db: &OptimisticTransactionDB) -> Result<()> {
+        verify_packet_attr(db, &self.packet_attr)
+    }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
 pub enum ValueKind {
     String,
     Integer,  // range: i64::MAX
@@ -80,7 +85,7 @@ pub enum ValueKind {
     Bool,
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Deserialize, Serialize)]
 pub enum AttrCmpKind {
     Less,
     Equal,
@@ -297,6 +302,88 @@ pub struct PacketAttr {
     pub weight: Option<f64>,
 }
 
+impl PacketAttr {
+    /// Returns the event kinds that the rule can be applied to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the comparison cannot be applied to the value type of the rule, the
+    /// attribute is unknown, or no event kind has the attribute with a value type the rule can be
+    /// applied to.
+    pub fn event_kinds(&self) -> Result<Vec<EventKind>> {
+        self.verify_comparison()?;
+        let kind = RawEventAttrKind::from_kind_and_attr_name(&self.raw_event_kind, &self.attr_name)
+            .map_err(|_| anyhow!("unknown attribute {}", self.attr_name))?;
+        let mut found = false;
+        let mut kinds = Vec::new();
+        for (event_kind, attr) in EventKind::with_attribute(kind) {
+            found = true;
+            if attr.accepts(self.value_kind) {
+                kinds.push(event_kind);
+            }
+        }
+        if kinds.is_empty() {
+            if found {
+                bail!(
+                    "attribute {} cannot be compared with a value of type {:?}",
+                    self.attr_name,
+                    self.value_kind
+                );
+            }
+            bail!("no event kind has attribute {}", self.attr_name);
+        }
+        Ok(kinds)
+    }
+
+    /// Returns the names of the schema and the attribute if the rule refers to an attribute of
+    /// custom events, which is written as `<schema>.<attribute>`.
+    #[must_use]
+    pub fn custom_attr(&self) -> Option<(&str, &str)> {
+        self.attr_name.rsplit_once('.')
+    }
+
+    /// Checks that the comparison can be applied to the value type of the rule.
+    fn verify_comparison(&self) -> Result<()> {
+        let applicable = match self.value_kind {
+            ValueKind::String | ValueKind::Vector => {
+                matches!(
+                    self.cmp_kind,
+                    AttrCmpKind::Contain | AttrCmpKind::NotContain
+                )
+            }
+            ValueKind::Bool => matches!(self.cmp_kind, AttrCmpKind::Equal | AttrCmpKind::NotEqual),
+            ValueKind::Integer | ValueKind::UInteger | ValueKind::Float | ValueKind::IpAddr => {
+                !matches!(
+                    self.cmp_kind,
+                    AttrCmpKind::Contain | AttrCmpKind::NotContain
+                )
+            }
+        };
+        if !applicable {
+            bail!(
+                "{:?} comparison cannot be applied to a value of type {:?}",
+                self.cmp_kind,
+                self.value_kind
+            );
+        }
+        let range = matches!(
+            self.cmp_kind,
+            AttrCmpKind::OpenRange
+                | AttrCmpKind::CloseRange
+                | AttrCmpKind::LeftOpenRange
+                | AttrCmpKind::RightOpenRange
+                | AttrCmpKind::NotOpenRange
+                | AttrCmpKind::NotCloseRange
+                | AttrCmpKind::NotLeftOpenRange
+                | AttrCmpKind::NotRightOpenRange
+        );
+        if range && self.second_value.is_none() {
+            bail!("{:?} comparison requires a second value", self.cmp_kind);
+        }
+        Ok(())
+    }
+}
+
 impl Eq for PacketAttr {}
 
 impl PartialOrd for PacketAttr {
@@ -410,16 +497,53 @@ impl<'d> IndexedTable<'d, TriagePolicy> {
             .ok()
     }
 
+    /// Inserts a triage policy into the table and returns its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a packet attribute rule cannot be applied to any event, or the database
+    /// operation fails.
+    pub fn insert(&self, entry: TriagePolicy) -> Result<u32> {
+        self.put(entry)
+    }
+
     /// Updates the `TriagePolicy` from `old` to `new`, given `id`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the `id` is invalid or the database operation fails.
+    /// Returns an error if a packet attribute rule in `new` cannot be applied to any event, the
+    /// `id` is invalid, or the database operation fails.
     pub fn update(&mut self, id: u32, old: &Update, new: &Update) -> Result<()> {
+        verify_packet_attr(self.indexed_map.db(), &new.packet_attr)?;
         self.indexed_map.update(id, old, new)
     }
 }
 
+/// Checks that each rule can be applied to an event kind or to an attribute of a registered custom
+/// event schema.
+fn verify_packet_attr(db: &OptimisticTransactionDB, packet_attr: &[PacketAttr]) -> Result<()> {
+    let schemas =
+        Table::<CustomEventSchema>::open(db).context("custom event schemas table not found")?;
+    for attr in packet_attr {
+        attr.verify_comparison()?;
+        let Err(e) = attr.event_kinds() else {
+            continue;
+        };
+        let Some((schema, name)) = attr.custom_attr() else {
+            return Err(e);
+        };
+        let custom = schemas.versions(schema)?.iter().any(|schema| {
+            schema
+                .attribute(name)
+                .is_some_and(|spec| spec.kind.value_kind() == attr.value_kind)
+        });
+        if !custom {
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
 #[derive(Clone)]
 pub struct Update {
     pub name: String,
@@ -561,9 +685,14 @@ fn netmask_by_ipaddr(ipaddr: IpAddr, netmask: IpAddr) -> Option<IpAddr> {
 mod test {
     use std::sync::Arc;
 
+    use attrievent::attribute::{DnsAttr, HttpAttr, RawEventKind};
+    use bincode::Options;
     use chrono::Utc;
 
-    use crate::{Store, TriagePolicy, TriagePolicyUpdate};
+    use crate::{
+        AttrCmpKind, CustomAttrSpec, CustomEventSchema, EventKind, PacketAttr, Store, TriagePolicy,
+        TriagePolicyUpdate, ValueKind, event::CustomValueKind,
+    };
 
     #[test]
     fn update() {
@@ -583,6 +712,98 @@ mod test {
         assert_eq!(entry.map(|e| e.name), Some("b".to_string()));
     }
 
+    #[test]
+    fn reject_impossible_packet_attr() {
+        let store = setup_store();
+        let mut table = store.triage_policy_map();
+
+        let uri = packet_attr(
+            RawEventKind::Http,
+            &HttpAttr::Uri.to_string(),
+            ValueKind::String,
+            AttrCmpKind::Contain,
+        );
+        assert!(uri.event_kinds().unwrap().contains(&EventKind::HttpThreat));
+        let mut entry = create_entry("a");
+        entry.packet_attr = vec![uri.clone()];
+        let id = table.insert(entry).unwrap();
+
+        // An attribute compared as a value of another type
+        let mut wrong_type = uri.clone();
+        wrong_type.value_kind = ValueKind::UInteger;
+        wrong_type.cmp_kind = AttrCmpKind::Equal;
+        assert!(wrong_type.event_kinds().is_err());
+        // A comparison not applicable to the type
+        let aa = packet_attr(
+            RawEventKind::Dns,
+            &DnsAttr::AA.to_string(),
+            ValueKind::Bool,
+            AttrCmpKind::Greater,
+        );
+        assert!(aa.event_kinds().is_err());
+        // An unknown attribute
+        let unknown = packet_attr(
+            RawEventKind::Http,
+            "no such attribute",
+            ValueKind::String,
+            AttrCmpKind::Contain,
+        );
+        assert!(unknown.event_kinds().is_err());
+
+        let mut entry = create_entry("b");
+        entry.packet_attr = vec![wrong_type];
+        assert!(table.insert(entry.clone()).is_err());
+        assert!(table.put(entry).is_err());
+        let old = create_update("a");
+        let mut update = create_update("a");
+        update.packet_attr = vec![uri, aa];
+        assert!(table.update(id, &old, &update).is_err());
+
+        // An attribute of a registered custom event schema
+        let mut score = unknown;
+        score.attr_name = "partner detector.score".to_string();
+        score.value_kind = ValueKind::Float;
+        score.cmp_kind = AttrCmpKind::Greater;
+        score.first_value = bincode::DefaultOptions::new().serialize(&0.5_f64).unwrap();
+        update.packet_attr = vec![score.clone()];
+        assert!(table.update(id, &old, &update).is_err());
+        store
+            .custom_event_schema_map()
+            .register(&CustomEventSchema {
+                name: "partner detector".to_string(),
+                version: 1,
+                description: String::new(),
+                attributes: vec![CustomAttrSpec {
+                    name: "score".to_string(),
+                    kind: CustomValueKind::Float,
+                    description: String::new(),
+                }],
+            })
+            .unwrap();
+        assert!(table.update(id, &old, &update).is_ok());
+        update.packet_attr[0].attr_name = "score".to_string();
+        assert!(table.update(id, &old, &update).is_err());
+        update.packet_attr[0].attr_name = "other detector.score".to_string();
+        assert!(table.update(id, &old, &update).is_err());
+    }
+
+    fn packet_attr(
+        raw_event_kind: RawEventKind,
+        attr_name: &str,
+        value_kind: ValueKind,
+        cmp_kind: AttrCmpKind,
+    ) -> PacketAttr {
+        PacketAttr {
+            raw_event_kind,
+            attr_name: attr_name.to_string(),
+            value_kind,
+            cmp_kind,
+            first_value: bincode::DefaultOptions::new().serialize("a").unwrap(),
+            second_value: None,
+            weight: Some(0.5),
+        }
+    }
+
     fn setup_store() -> Arc<Store> {
         let db_dir = tempfile::tempdir().unwrap();
         let backup_dir = tempfile::tempdir().unwrap();
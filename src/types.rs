@@ -35,18 +35,27 @@ mod private {
     impl Sealed for crate::Category {}
     impl Sealed for tables::Cluster {}
     impl Sealed for tables::CsvColumnExtra {}
+    impl Sealed for tables::CustomEventSchema {}
     impl Sealed for tables::Customer {}
     impl Sealed for tables::DataSource {}
+    impl Sealed for tables::DedupEntry {}
+    impl Sealed for tables::EventEnrichment {}
     impl Sealed for tables::Filter {}
     impl Sealed for tables::Host {}
+    impl Sealed for tables::Incident {}
     impl Sealed for tables::InnerNode {}
+    impl Sealed for tables::LogTemplate {}
     impl Sealed for tables::Model {}
     impl Sealed for tables::ModelIndicator {}
     impl Sealed for tables::Network {}
     impl Sealed for tables::OutlierInfo {}
     impl Sealed for types::Qualifier {}
     impl Sealed for tables::ExternalService {}
+    impl Sealed for tables::CustomRole {}
     impl Sealed for tables::SamplingPolicy {}
+    impl Sealed for tables::Sensor {}
+    impl Sealed for tables::SensorIngest {}
+    impl Sealed for tables::SeverityOverride {}
     impl Sealed for types::Status {}
     impl Sealed for tables::Template {}
     impl Sealed for tables::Tidb {}
@@ -167,6 +176,10 @@ pub struct Cluster {
 pub struct Endpoint {
     pub direction: Option<TrafficDirection>,
     pub network: HostNetworkGroup,
+    /// The ID of the `Network` if the endpoint is a predefined network. Events are then matched
+    /// against the networks in their stored enrichment instead of `network`.
+    #[serde(default)]
+    pub network_id: Option<u32>,
 }
 
 // `hosts` and `networks` must be kept sorted.
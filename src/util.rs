@@ -22,6 +22,26 @@ pub fn find_ip_country(locator: &ip2location::DB, addr: IpAddr) -> String {
         .unwrap_or_else(|| "XX".to_string())
 }
 
+/// Looks up the autonomous system number for the given IP address.
+///
+/// Returns `None` if the lookup fails or the database has no ASN information.
+#[must_use]
+pub(crate) fn find_ip_asn(locator: &ip2location::DB, addr: IpAddr) -> Option<u32> {
+    locator
+        .ip_lookup(addr)
+        .ok()
+        .and_then(|r| get_record_asn(&r))
+}
+
+fn get_record_asn(record: &ip2location::Record) -> Option<u32> {
+    use ip2location::Record;
+    let asn = match record {
+        Record::ProxyDb(r) => r.asn.as_ref(),
+        Record::LocationDb(r) => r.asn.as_ref(),
+    };
+    asn.and_then(|asn| asn.parse().ok())
+}
+
 fn get_record_country_short_name(record: &ip2location::Record) -> Option<String> {
     use ip2location::Record;
     match record {